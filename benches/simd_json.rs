@@ -0,0 +1,88 @@
+// Compare the cost of traversing the same document via the serde_json and simd-json backends.
+// Run via `cargo bench --features trait_serde_json,trait_simd_json`
+use criterion::{criterion_group, criterion_main, Criterion};
+use json_trait_rs::{get_fragment, ToRustType};
+use std::hint::black_box;
+
+const EVENTS_COUNT: usize = 1_000;
+
+fn events_payload() -> String {
+    let events = (0..EVENTS_COUNT)
+        .map(|index| {
+            format!(
+                r#"{{"id":{},"name":"event-{}","score":{}.5,"active":{},"tags":["a","b","c"],"payload":{{"user":{{"id":{},"email":"user-{}@example.com"}},"note":null}}}}"#,
+                index,
+                index,
+                index,
+                index % 2 == 0,
+                index * 7,
+                index
+            )
+        })
+        .collect::<Vec<_>>();
+    format!(r#"{{"version":1,"events":[{}]}}"#, events.join(","))
+}
+
+fn bench_get_fragment(criterion: &mut Criterion) {
+    let payload = events_payload();
+    let fragment = format!("/events/{}/payload/user/email", EVENTS_COUNT - 1);
+
+    let serde_json_value: serde_json::Value = serde_json::from_str(&payload).unwrap();
+    let mut owned_buffer = payload.as_bytes().to_vec();
+    let simd_json_owned_value = simd_json::to_owned_value(&mut owned_buffer).unwrap();
+    let mut borrowed_buffer = payload.as_bytes().to_vec();
+    let simd_json_borrowed_value = simd_json::to_borrowed_value(&mut borrowed_buffer).unwrap();
+
+    let mut group = criterion.benchmark_group("get_fragment");
+    group.bench_function("serde_json", |bencher| bencher.iter(|| get_fragment(black_box(&serde_json_value), black_box(&fragment))));
+    group.bench_function("simd_json_owned", |bencher| {
+        bencher.iter(|| get_fragment(black_box(&simd_json_owned_value), black_box(&fragment)))
+    });
+    group.bench_function("simd_json_borrowed", |bencher| {
+        bencher.iter(|| get_fragment(black_box(&simd_json_borrowed_value), black_box(&fragment)))
+    });
+    group.finish();
+}
+
+fn bench_to_rust_type(criterion: &mut Criterion) {
+    let payload = events_payload();
+
+    let serde_json_value: serde_json::Value = serde_json::from_str(&payload).unwrap();
+    let mut owned_buffer = payload.as_bytes().to_vec();
+    let simd_json_owned_value = simd_json::to_owned_value(&mut owned_buffer).unwrap();
+    let mut borrowed_buffer = payload.as_bytes().to_vec();
+    let simd_json_borrowed_value = simd_json::to_borrowed_value(&mut borrowed_buffer).unwrap();
+
+    let mut group = criterion.benchmark_group("to_rust_type");
+    group.bench_function("serde_json", |bencher| bencher.iter(|| black_box(&serde_json_value).to_rust_type()));
+    group.bench_function("simd_json_owned", |bencher| bencher.iter(|| black_box(&simd_json_owned_value).to_rust_type()));
+    group.bench_function("simd_json_borrowed", |bencher| bencher.iter(|| black_box(&simd_json_borrowed_value).to_rust_type()));
+    group.finish();
+}
+
+// Parsing is included as well as simd-json values are usually built right before being traversed
+fn bench_parse_and_to_rust_type(criterion: &mut Criterion) {
+    let payload = events_payload();
+
+    let mut group = criterion.benchmark_group("parse_and_to_rust_type");
+    group.bench_function("serde_json", |bencher| {
+        bencher.iter(|| serde_json::from_str::<serde_json::Value>(black_box(&payload)).unwrap().to_rust_type())
+    });
+    group.bench_function("simd_json_owned", |bencher| {
+        bencher.iter(|| {
+            let mut buffer = black_box(&payload).as_bytes().to_vec();
+            simd_json::to_owned_value(&mut buffer).unwrap().to_rust_type()
+        })
+    });
+    group.bench_function("simd_json_borrowed", |bencher| {
+        bencher.iter(|| {
+            let mut buffer = black_box(&payload).as_bytes().to_vec();
+            let value = simd_json::to_borrowed_value(&mut buffer).unwrap();
+            value.to_rust_type()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_get_fragment, bench_to_rust_type, bench_parse_and_to_rust_type);
+criterion_main!(benches);
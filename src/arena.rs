@@ -0,0 +1,396 @@
+// Arena allocated JSON documents.
+//
+// `ArenaValue`s are allocated into a `bumpalo::Bump` arena: children of arrays and objects are contiguous slices of the
+// arena instead of individually boxed values and no value owns heap memory, so dropping (or resetting) the arena frees
+// whole batches of documents at once without walking them.
+//
+// Object entries are sorted by key, so `JsonType::get_attribute` is a binary search and the original order of the keys
+// is not retained. Only the last entry of duplicated keys (ie. from MessagePack or CBOR maps) is kept. Big integers and
+// decimals are stored via their textual representation (`ArenaNumber`).
+use crate::{
+    convert::build,
+    error::Error,
+    json_builder::JsonBuilderContext,
+    json_type::{JsonMap, JsonMapTrait, JsonType, ThreadSafeJsonType, ToRustType},
+    rust_type_impl::{Decimal, RustType},
+};
+pub use bumpalo::Bump;
+#[cfg(feature = "big_integer")]
+use num_bigint::BigInt;
+#[cfg(feature = "big_integer")]
+use std::convert::TryInto;
+use std::{borrow::Cow, collections::HashMap};
+
+// Textual representation of a big integer or of a `Decimal`, only built by `ArenaBuilder` from valid values
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ArenaNumber<'arena>(&'arena str);
+
+impl<'arena> ArenaNumber<'arena> {
+    #[must_use]
+    pub const fn as_str(&self) -> &'arena str {
+        self.0
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ArenaValue<'arena> {
+    Null,
+    Boolean(bool),
+    String(&'arena str),
+    Integer(i128),
+    #[cfg(feature = "big_integer")]
+    BigInteger(ArenaNumber<'arena>),
+    Number(f64),
+    Decimal(ArenaNumber<'arena>),
+    List(&'arena [ArenaValue<'arena>]),
+    Object(&'arena [(&'arena str, ArenaValue<'arena>)]),
+}
+
+impl<'arena> ArenaValue<'arena> {
+    // Copy any `JsonType` into `arena`
+    /// # Errors
+    ///
+    /// Will return `Err` if `value` is not a valid JSON value (see `convert::from_json_type`)
+    pub fn from_json_type<T: JsonType>(arena: &'arena Bump, value: &T) -> Result<Self, Error> {
+        build(&ArenaBuilder(arena), value)
+    }
+}
+
+// Builder of values allocated into the wrapped arena
+struct ArenaBuilder<'arena>(&'arena Bump);
+
+impl<'arena> JsonBuilderContext for ArenaBuilder<'arena> {
+    type Value = ArenaValue<'arena>;
+
+    fn null(&self) -> Self::Value {
+        ArenaValue::Null
+    }
+
+    fn bool(&self, value: bool) -> Self::Value {
+        ArenaValue::Boolean(value)
+    }
+
+    fn integer(&self, value: i128) -> Result<Self::Value, Error> {
+        Ok(ArenaValue::Integer(value))
+    }
+
+    #[cfg(feature = "big_integer")]
+    fn big_integer(&self, value: &BigInt) -> Result<Self::Value, Error> {
+        Ok(value
+            .try_into()
+            .map_or_else(|_| ArenaValue::BigInteger(ArenaNumber(self.0.alloc_str(&value.to_string()))), ArenaValue::Integer))
+    }
+
+    fn number(&self, value: f64) -> Self::Value {
+        ArenaValue::Number(value)
+    }
+
+    // The textual representation is preserved only if it cannot be losslessly represented by a f64
+    fn decimal(&self, value: f64, number_str: &str) -> Self::Value {
+        Decimal::new(number_str).map_or(ArenaValue::Number(value), |decimal| ArenaValue::Decimal(ArenaNumber(self.0.alloc_str(decimal.as_str()))))
+    }
+
+    fn string(&self, value: &str) -> Self::Value {
+        ArenaValue::String(self.0.alloc_str(value))
+    }
+
+    fn array<I: IntoIterator<Item = Self::Value>>(&self, items: I) -> Self::Value {
+        ArenaValue::List(self.0.alloc_slice_copy(&items.into_iter().collect::<Vec<_>>()))
+    }
+
+    fn object<'key, I: IntoIterator<Item = (&'key str, Self::Value)>>(&self, items: I) -> Self::Value {
+        let mut entries = items.into_iter().map(|(key, value)| (&*self.0.alloc_str(key), value)).collect::<Vec<_>>();
+        // Stable sort of the reversed entries followed by `dedup_by_key` keeps the last entry of each key
+        entries.reverse();
+        entries.sort_by_key(|(key, _)| *key);
+        entries.dedup_by_key(|(key, _)| *key);
+        ArenaValue::Object(self.0.alloc_slice_copy(&entries))
+    }
+}
+
+impl<'arena> ToRustType for ArenaValue<'arena> {
+    fn to_rust_type(&self) -> RustType {
+        match self {
+            Self::Null => RustType::Null,
+            Self::Boolean(value) => RustType::from(*value),
+            Self::String(value) => RustType::from(*value),
+            Self::Integer(value) => RustType::from(*value),
+            #[cfg(feature = "big_integer")]
+            Self::BigInteger(_) => self.as_big_integer().map_or(RustType::Null, RustType::from),
+            Self::Number(value) => RustType::from(*value),
+            Self::Decimal(value) => Decimal::new(value.as_str()).map_or(RustType::Null, RustType::Decimal),
+            Self::List(items) => RustType::from(items.iter().map(ToRustType::to_rust_type).collect::<Vec<_>>()),
+            Self::Object(entries) => RustType::from(entries.iter().map(|(key, value)| ((*key).to_string(), value.to_rust_type())).collect::<HashMap<_, _>>()),
+        }
+    }
+}
+
+impl<'json, 'arena> JsonMapTrait<'json, ArenaValue<'arena>> for JsonMap<'json, ArenaValue<'arena>> {
+    #[must_use]
+    fn items(&'json self) -> Box<dyn Iterator<Item = (&str, &ArenaValue<'arena>)> + 'json> {
+        if let ArenaValue::Object(entries) = **self {
+            Box::new(entries.iter().map(|(key, value)| (*key, value)))
+        } else {
+            Box::new(std::iter::empty())
+        }
+    }
+}
+
+impl<'arena> JsonType for ArenaValue<'arena> {
+    #[must_use]
+    fn as_array<'json>(&'json self) -> Option<Box<dyn ExactSizeIterator<Item = &Self> + 'json>> {
+        if let Self::List(items) = self {
+            Some(Box::new(items.iter()))
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_boolean(&self) -> Option<bool> {
+        if let Self::Boolean(value) = self {
+            Some(*value)
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_integer(&self) -> Option<i128> {
+        if let Self::Integer(value) = self {
+            Some(*value)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "big_integer")]
+    #[must_use]
+    fn as_big_integer(&self) -> Option<BigInt> {
+        match self {
+            Self::Integer(value) => Some(BigInt::from(*value)),
+            Self::BigInteger(value) => value.as_str().parse().ok(),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    fn as_null(&self) -> Option<()> {
+        if let Self::Null = self {
+            Some(())
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_number(&self) -> Option<f64> {
+        match self {
+            Self::Number(value) => Some(*value),
+            Self::Decimal(value) => value.as_str().parse().ok(),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    fn as_number_str(&self) -> Option<Cow<str>> {
+        if let Self::Decimal(value) = self {
+            Some(Cow::Borrowed(value.as_str()))
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_object(&self) -> Option<JsonMap<Self>> {
+        if let Self::Object(_) = self {
+            Some(JsonMap::new(self))
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_string(&self) -> Option<&str> {
+        if let Self::String(value) = self {
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn get_attribute(&self, attribute_name: &str) -> Option<&Self> {
+        if let Self::Object(entries) = self {
+            entries.binary_search_by_key(&attribute_name, |(key, _)| *key).ok().map(|position| &entries[position].1)
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn get_index(&self, index: usize) -> Option<&Self> {
+        if let Self::List(items) = self {
+            items.get(index)
+        } else {
+            None
+        }
+    }
+}
+
+impl<'arena> ThreadSafeJsonType for ArenaValue<'arena> {}
+
+#[cfg(test)]
+mod tests_arena_map_trait {
+    use super::{ArenaValue, Bump};
+    use crate::json_type::{JsonMap, JsonMapTrait};
+
+    lazy_static! {
+        static ref TESTING_MAP: crate::rust_type_impl::RustType = rust_type!({"k2": "v2", "k1": "v1"});
+    }
+
+    #[test]
+    fn keys() {
+        let arena = Bump::new();
+        let testing_map = ArenaValue::from_json_type(&arena, &*TESTING_MAP).unwrap();
+        assert_eq!(JsonMap::new(&testing_map).keys().collect::<Vec<_>>(), vec!["k1", "k2"]);
+    }
+
+    #[test]
+    fn values() {
+        let arena = Bump::new();
+        let testing_map = ArenaValue::from_json_type(&arena, &*TESTING_MAP).unwrap();
+        assert_eq!(
+            JsonMap::new(&testing_map).values().collect::<Vec<_>>(),
+            vec![&ArenaValue::String("v1"), &ArenaValue::String("v2")]
+        );
+    }
+
+    #[test]
+    fn items() {
+        let arena = Bump::new();
+        let testing_map = ArenaValue::from_json_type(&arena, &*TESTING_MAP).unwrap();
+        assert_eq!(
+            JsonMap::new(&testing_map).items().collect::<Vec<_>>(),
+            vec![("k1", &ArenaValue::String("v1")), ("k2", &ArenaValue::String("v2"))]
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests_primitive_type_trait {
+    use super::{ArenaValue, Bump};
+    use crate::{
+        json_type::{JsonType, PrimitiveType},
+        rust_type_impl::{Decimal, RustType},
+    };
+    use test_case::test_case;
+
+    #[test_case(&rust_type!([]), PrimitiveType::Array)]
+    #[test_case(&rust_type!(true), PrimitiveType::Boolean)]
+    #[test_case(&rust_type!(1), PrimitiveType::Integer)]
+    #[test_case(&rust_type!(null), PrimitiveType::Null)]
+    #[test_case(&rust_type!(1.2), PrimitiveType::Number)]
+    #[test_case(&RustType::Decimal(Decimal::new("0.1000000000000000055511151231257827").unwrap()), PrimitiveType::Number ; "decimal")]
+    #[test_case(&rust_type!({"prop": "value"}), PrimitiveType::Object)]
+    #[test_case(&rust_type!("string"), PrimitiveType::String)]
+    fn test_primitive_type(value: &RustType, expected_value: PrimitiveType) {
+        let arena = Bump::new();
+        assert_eq!(ArenaValue::from_json_type(&arena, value).unwrap().primitive_type(), expected_value);
+    }
+
+    #[test_case(&rust_type!({"present": 1}), "present", Some(&ArenaValue::Integer(1)))]
+    #[test_case(&rust_type!({"present": 1}), "not-present", None)]
+    #[test_case(&rust_type!({"a": 1, "b": 2, "c": 3, "d": 4}), "c", Some(&ArenaValue::Integer(3)) ; "binary search")]
+    #[test_case(&rust_type!([1]), "0", None)]
+    fn test_get_attribute(value: &RustType, attribute_name: &str, expected_value: Option<&ArenaValue>) {
+        let arena = Bump::new();
+        assert_eq!(ArenaValue::from_json_type(&arena, value).unwrap().get_attribute(attribute_name), expected_value);
+    }
+
+    #[test_case(&rust_type!([0, 1, 2]), 1, Some(&ArenaValue::Integer(1)))]
+    #[test_case(&rust_type!([0, 1, 2]), 4, None)]
+    fn test_get_index(value: &RustType, index: usize, expected_value: Option<&ArenaValue>) {
+        let arena = Bump::new();
+        assert_eq!(ArenaValue::from_json_type(&arena, value).unwrap().get_index(index), expected_value);
+    }
+
+    #[test_case(&rust_type!(1.2), Some(1.2))]
+    #[test_case(&RustType::Decimal(Decimal::new("0.1000000000000000055511151231257827").unwrap()), Some(0.1) ; "decimal")]
+    #[test_case(&rust_type!(1), None)]
+    fn test_as_number(value: &RustType, expected_value: Option<f64>) {
+        let arena = Bump::new();
+        assert_eq!(ArenaValue::from_json_type(&arena, value).unwrap().as_number(), expected_value);
+    }
+
+    #[test_case(&rust_type!("1"), Some("1"))]
+    #[test_case(&rust_type!(1), None)]
+    fn test_as_string(value: &RustType, expected_value: Option<&str>) {
+        let arena = Bump::new();
+        assert_eq!(ArenaValue::from_json_type(&arena, value).unwrap().as_string(), expected_value);
+    }
+}
+
+#[cfg(test)]
+mod tests_arena {
+    use super::{ArenaBuilder, ArenaNumber, ArenaValue, Bump};
+    use crate::{
+        json_builder::JsonBuilderContext,
+        json_type::{get_fragment, JsonType, JsonTypeToString, ToRustType},
+        rust_type_impl::{Decimal, RustType},
+    };
+
+    #[test]
+    fn test_round_trip() {
+        let value = rust_type!({"array": [1, 2.3, null, true, "s"], "object": {"nested": {}}});
+        let arena = Bump::new();
+        let arena_value = ArenaValue::from_json_type(&arena, &value).unwrap();
+        assert_eq!(arena_value.to_rust_type(), value);
+        assert_eq!(get_fragment(&arena_value, "/array/4").and_then(JsonType::as_string), Some("s"));
+    }
+
+    #[test]
+    fn test_duplicated_keys_keep_the_last_entry() {
+        let arena = Bump::new();
+        let value = ArenaBuilder(&arena).object(vec![("b", ArenaValue::Integer(1)), ("a", ArenaValue::Integer(2)), ("b", ArenaValue::Integer(3))]);
+        assert_eq!(value, ArenaValue::Object(&[("a", ArenaValue::Integer(2)), ("b", ArenaValue::Integer(3))]));
+        assert_eq!(value.get_attribute("b"), Some(&ArenaValue::Integer(3)));
+    }
+
+    #[test]
+    fn test_decimals() {
+        let arena = Bump::new();
+        let inexact = RustType::Decimal(Decimal::new("0.1000000000000000055").unwrap());
+        assert_eq!(ArenaValue::from_json_type(&arena, &inexact), Ok(ArenaValue::Decimal(ArenaNumber("0.1000000000000000055"))));
+        assert_eq!(ArenaBuilder(&arena).decimal(1.1, "1.10"), ArenaValue::Number(1.1));
+    }
+
+    #[test]
+    fn test_batches_are_freed_by_resetting_the_arena() {
+        let mut arena = Bump::new();
+        for batch in 0..3 {
+            let documents = (0..100).map(|index| rust_type!({"batch": batch, "index": index})).collect::<Vec<_>>();
+            let arena_values = documents.iter().map(|document| ArenaValue::from_json_type(&arena, document).unwrap()).collect::<Vec<_>>();
+            assert_eq!(arena_values[42].get_attribute("index").and_then(JsonType::as_integer), Some(42));
+            drop(arena_values);
+            arena.reset();
+        }
+    }
+
+    #[cfg(feature = "big_integer")]
+    #[test]
+    fn test_big_integer() {
+        let value = RustType::from(num_bigint::BigInt::from(2).pow(128_u32));
+        let arena = Bump::new();
+        let arena_value = ArenaValue::from_json_type(&arena, &value).unwrap();
+        assert_eq!(arena_value, ArenaValue::BigInteger(ArenaNumber("340282366920938463463374607431768211456")));
+        assert_eq!(arena_value.to_rust_type(), value);
+    }
+
+    #[test]
+    fn test_to_json_string() {
+        let arena = Bump::new();
+        let value = ArenaValue::from_json_type(&arena, &rust_type!([{"a": [1]}, false])).unwrap();
+        assert_eq!(value.to_json_string(), r#"[{"a":[1]},false]"#);
+    }
+}
@@ -0,0 +1,447 @@
+// Layered configuration loader.
+//
+// `ConfigLoader` composes multiple layers (configuration files, environment variables, command line overrides or
+// any other `JsonType` value) into a single `RustType`. Layers are applied in the order they are added to the loader,
+// so later layers take precedence over earlier ones (ie. defaults file, environment specific file, environment
+// variables and finally command line overrides).
+//
+// Layers are deep merged: objects are merged key by key, while any other value (arrays included) replaces the value
+// set by lower precedence layers (replacing an object with a non object value discards the whole object).
+//
+// The loaded `Config` tracks the provenance of each value, so `Config::source` reports the layer that set the
+// value referenced by any JSON pointer (items of arrays are reported as set by the layer that set the array).
+//
+// Configuration files are supported according to the enabled backends (`trait_serde_json`, `trait_serde_yaml`
+// and `trait_toml` features).
+use crate::{
+    env_source::{ArgsSource, EnvSource},
+    error::Error,
+    fragment_helpers::fragment_components_from_fragment,
+    json_type::{get_fragment, JsonType, TryJsonType},
+    rust_type_impl::RustType,
+};
+use std::{
+    collections::{hash_map::Entry, BTreeMap, HashMap},
+    io,
+    path::PathBuf,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConfigFormat {
+    #[cfg(feature = "trait_serde_json")]
+    Json,
+    #[cfg(feature = "trait_toml")]
+    Toml,
+    #[cfg(feature = "trait_serde_yaml")]
+    Yaml,
+}
+
+impl ConfigFormat {
+    #[allow(unused_variables)]
+    fn parse(self, text: &str) -> Result<RustType, Error> {
+        match self {
+            #[cfg(feature = "trait_serde_json")]
+            Self::Json => serde_json::from_str::<serde_json::Value>(text)
+                .map_err(|error| Error::backend("serde_json", "", error))
+                .and_then(|value| value.try_to_rust_type()),
+            #[cfg(feature = "trait_toml")]
+            Self::Toml => toml::from_str::<toml::Table>(text)
+                .map_err(|error| Error::backend("toml", "", error))
+                .and_then(|table| toml::Value::Table(table).try_to_rust_type()),
+            #[cfg(feature = "trait_serde_yaml")]
+            Self::Yaml => serde_yaml::from_str::<serde_yaml::Value>(text)
+                .map_err(|error| Error::backend("serde_yaml", "", error))
+                .and_then(|value| value.try_to_rust_type()),
+        }
+    }
+}
+
+// Failure while loading a layer, reported as source of `Error::Backend` (`config` backend)
+#[derive(Debug, thiserror::Error)]
+#[error("unable to load layer `{layer}`: {source}")]
+pub struct LayerError {
+    pub layer: String,
+    #[source]
+    pub source: Error,
+}
+
+#[derive(Clone, Debug)]
+enum Layer {
+    Value { name: String, value: RustType },
+    File { path: PathBuf, format: ConfigFormat, required: bool },
+    Env(EnvSource),
+    Args { args_source: ArgsSource, args: Vec<String> },
+}
+
+impl Layer {
+    fn name(&self) -> String {
+        match self {
+            Self::Value { name, .. } => name.clone(),
+            Self::File { path, .. } => path.display().to_string(),
+            Self::Env(_) => "environment variables".to_string(),
+            Self::Args { .. } => "command line arguments".to_string(),
+        }
+    }
+
+    // Value of the layer, `None` if the layer has to be skipped (optional files not existing)
+    fn load(&self) -> Result<Option<RustType>, Error> {
+        match self {
+            Self::Value { value, .. } => Ok(Some(value.clone())),
+            Self::File { path, format, required } => match std::fs::read_to_string(path) {
+                Ok(text) => format.parse(&text).map(Some),
+                Err(error) if error.kind() == io::ErrorKind::NotFound && !required => Ok(None),
+                Err(error) => Err(Error::backend("filesystem", "", error)),
+            },
+            Self::Env(env_source) => env_source.from_env().map(Some),
+            Self::Args { args_source, args } => args_source.from_args(args).map(Some),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ConfigLoader {
+    layers: Vec<Layer>,
+}
+
+impl ConfigLoader {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Layer composed by `value`, reported as `name` by `Config::source`
+    /// # Errors
+    ///
+    /// Will return `Err` (`Error::Backend` with `LayerError` as source) if `value` is not a valid JSON value
+    /// (ie. an object with non string keys, see `TryJsonType::try_to_rust_type`)
+    pub fn value<T: JsonType>(mut self, name: &str, value: &T) -> Result<Self, Error> {
+        let value = value
+            .try_to_rust_type()
+            .map_err(|source| Error::backend("config", "", LayerError { layer: name.to_string(), source }))?;
+        self.layers.push(Layer::Value { name: name.to_string(), value });
+        Ok(self)
+    }
+
+    // Layer read from `path` while loading, the loading fails if the file does not exist
+    #[must_use]
+    pub fn file<P: Into<PathBuf>>(mut self, path: P, format: ConfigFormat) -> Self {
+        self.layers.push(Layer::File {
+            path: path.into(),
+            format,
+            required: true,
+        });
+        self
+    }
+
+    // Layer read from `path` while loading, skipped if the file does not exist
+    #[must_use]
+    pub fn optional_file<P: Into<PathBuf>>(mut self, path: P, format: ConfigFormat) -> Self {
+        self.layers.push(Layer::File {
+            path: path.into(),
+            format,
+            required: false,
+        });
+        self
+    }
+
+    // Layer composed by the environment variables of the process while loading
+    #[must_use]
+    pub fn env(mut self, env_source: EnvSource) -> Self {
+        self.layers.push(Layer::Env(env_source));
+        self
+    }
+
+    // Layer composed by `key=value` arguments (see `ArgsSource`)
+    #[must_use]
+    pub fn args<S: ToString, I: IntoIterator<Item = S>>(mut self, args_source: ArgsSource, args: I) -> Self {
+        self.layers.push(Layer::Args {
+            args_source,
+            args: args.into_iter().map(|arg| arg.to_string()).collect(),
+        });
+        self
+    }
+
+    // Load all the layers. The loader is not consumed, so it can be used to reload the configuration
+    /// # Errors
+    ///
+    /// Will return `Err` (`Error::Backend` with `LayerError` as source) if a layer cannot be loaded
+    /// (ie. a required file does not exist or it is not valid)
+    pub fn load(&self) -> Result<Config, Error> {
+        let mut config = Config {
+            value: RustType::Object(HashMap::new()),
+            layers: Vec::new(),
+            provenance: BTreeMap::new(),
+        };
+        for layer in &self.layers {
+            let value = layer.load().map_err(|source| Error::backend("config", "", LayerError { layer: layer.name(), source }))?;
+            if let Some(value) = value {
+                config.layers.push(layer.name());
+                let layer_index = config.layers.len() - 1;
+                merge(&mut config.value, value, &mut Vec::new(), layer_index, &mut config.provenance);
+            }
+        }
+        Ok(config)
+    }
+}
+
+// Layers (indexes in `Config::layers`) that set each value, keyed by the path of the value
+type Provenance = BTreeMap<Vec<String>, Vec<usize>>;
+
+fn record(provenance: &mut Provenance, path: &[String], layer_index: usize) {
+    let layer_indexes = provenance.entry(path.to_vec()).or_default();
+    if layer_indexes.last() != Some(&layer_index) {
+        layer_indexes.push(layer_index);
+    }
+}
+
+// Record `layer_index` as provenance of `value` and of all its nested object values
+fn record_all(provenance: &mut Provenance, value: &RustType, path: &mut Vec<String>, layer_index: usize) {
+    record(provenance, path, layer_index);
+    if let RustType::Object(object) = value {
+        for (key, value) in object {
+            path.push(key.clone());
+            record_all(provenance, value, path, layer_index);
+            let _ = path.pop();
+        }
+    }
+}
+
+fn merge(target: &mut RustType, value: RustType, path: &mut Vec<String>, layer_index: usize, provenance: &mut Provenance) {
+    match (target, value) {
+        (RustType::Object(target), RustType::Object(values)) => {
+            record(provenance, path, layer_index);
+            for (key, value) in values {
+                path.push(key.clone());
+                match target.entry(key) {
+                    Entry::Vacant(entry) => record_all(provenance, entry.insert(value), path, layer_index),
+                    Entry::Occupied(mut entry) => merge(entry.get_mut(), value, path, layer_index, provenance),
+                }
+                let _ = path.pop();
+            }
+        }
+        (target, value) => {
+            provenance.retain(|key, _| !key.starts_with(path));
+            record_all(provenance, &value, path, layer_index);
+            *target = value;
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Config {
+    value: RustType,
+    layers: Vec<String>,
+    provenance: Provenance,
+}
+
+impl Config {
+    #[must_use]
+    pub const fn value(&self) -> &RustType {
+        &self.value
+    }
+
+    #[must_use]
+    pub fn into_value(self) -> RustType {
+        self.value
+    }
+
+    // Names of the loaded layers, from the lowest to the highest precedence (optional files not existing are excluded)
+    #[must_use]
+    pub fn layers(&self) -> &[String] {
+        &self.layers
+    }
+
+    // Layers that set the value referenced by `pointer`, from the lowest to the highest precedence.
+    // Objects are reported as set by all the layers contributing to them, other values by a single layer.
+    // No layer is reported if `pointer` does not reference any value
+    #[must_use]
+    pub fn sources(&self, pointer: &str) -> Vec<&str> {
+        if get_fragment(&self.value, pointer).is_none() {
+            return Vec::new();
+        }
+        let mut path = fragment_components_from_fragment(pointer).collect::<Vec<_>>();
+        loop {
+            if let Some(layer_indexes) = self.provenance.get(&path) {
+                return layer_indexes.iter().map(|layer_index| self.layers[*layer_index].as_str()).collect();
+            }
+            if path.pop().is_none() {
+                return Vec::new();
+            }
+        }
+    }
+
+    // Layer with the highest precedence that set the value referenced by `pointer` (ie. the file that set `/db/port`)
+    #[must_use]
+    pub fn source(&self, pointer: &str) -> Option<&str> {
+        self.sources(pointer).pop()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ConfigLoader, LayerError};
+    use crate::{env_source::ArgsSource, error::Error};
+    use test_case::test_case;
+
+    lazy_static! {
+        static ref LOADER: ConfigLoader = ConfigLoader::new()
+            .value(
+                "defaults",
+                &rust_type!({"db": {"host": "localhost", "port": 5432, "options": {"ssl": false}}, "hosts": ["a", "b"], "name": "app"})
+            )
+            .unwrap()
+            .value("production", &rust_type!({"db": {"host": "db.example.com", "options": null}, "hosts": ["c"]}))
+            .unwrap()
+            .args(ArgsSource::default(), vec!["db.port=6543"]);
+    }
+
+    #[test]
+    fn test_deep_merge() {
+        assert_eq!(
+            LOADER.load().map(super::Config::into_value),
+            Ok(rust_type!({"db": {"host": "db.example.com", "port": 6543, "options": null}, "hosts": ["c"], "name": "app"}))
+        );
+    }
+
+    #[test_case("/db/port", Some("command line arguments") ; "overridden value")]
+    #[test_case("/db/host", Some("production") ; "overridden by intermediate layer")]
+    #[test_case("/name", Some("defaults") ; "not overridden value")]
+    #[test_case("/hosts/0", Some("production") ; "array item")]
+    #[test_case("/db/options", Some("production") ; "object replaced by value")]
+    #[test_case("/db/options/ssl", None ; "value of a replaced object")]
+    #[test_case("/missing", None ; "missing value")]
+    fn test_source(pointer: &str, expected_source: Option<&str>) {
+        assert_eq!(LOADER.load().unwrap().source(pointer), expected_source);
+    }
+
+    #[test_case("", &["defaults", "production", "command line arguments"] ; "root")]
+    #[test_case("/db", &["defaults", "production", "command line arguments"] ; "merged object")]
+    #[test_case("/db/host", &["production"] ; "value")]
+    fn test_sources(pointer: &str, expected_sources: &[&str]) {
+        assert_eq!(LOADER.load().unwrap().sources(pointer), expected_sources);
+    }
+
+    #[test]
+    fn test_layer_error() {
+        let error = ConfigLoader::new().args(ArgsSource::default(), vec!["db"]).load().unwrap_err();
+        if let Error::Backend { backend: "config", source, .. } = error {
+            let layer_error = source.get_ref().downcast_ref::<LayerError>().unwrap();
+            assert_eq!(layer_error.layer, "command line arguments");
+            assert!(matches!(layer_error.source, Error::InvalidSyntax { line: 1, .. }));
+        } else {
+            panic!("Unexpected error {:?}", error);
+        }
+    }
+
+    #[test]
+    fn test_provenance_of_replaced_root() {
+        let config = ConfigLoader::new()
+            .value("object", &rust_type!({"key": 1}))
+            .unwrap()
+            .value("scalar", &rust_type!(1))
+            .unwrap()
+            .load()
+            .unwrap();
+        assert_eq!(config.value(), &rust_type!(1));
+        assert_eq!(config.source("/key"), None);
+        assert_eq!(config.sources(""), vec!["scalar"]);
+    }
+}
+
+#[cfg(all(test, feature = "trait_serde_json", feature = "trait_serde_yaml", feature = "trait_toml"))]
+mod tests_files {
+    use super::{ConfigFormat, ConfigLoader, LayerError};
+    use crate::{
+        env_source::{ArgsSource, EnvSource},
+        error::Error,
+    };
+    use std::path::PathBuf;
+
+    fn layer_error(error: &Error) -> &LayerError {
+        match error {
+            Error::Backend { backend: "config", source, .. } => source.get_ref().downcast_ref::<LayerError>().unwrap(),
+            _ => panic!("Unexpected error {:?}", error),
+        }
+    }
+
+    // Write `content` into a file, unique per test, in the temporary directory
+    fn write_file(test_name: &str, file_name: &str, content: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!("json-trait-rs-{}-{}", std::process::id(), test_name));
+        std::fs::create_dir_all(&directory).unwrap();
+        let path = directory.join(file_name);
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_layered_files() {
+        let yaml_path = write_file("layered", "defaults.yaml", "db:\n  host: localhost\n  port: 5432\nlog: info\n");
+        let json_path = write_file("layered", "production.json", r#"{"db": {"host": "db.example.com"}}"#);
+        let toml_path = write_file("layered", "local.toml", "log = \"debug\"\n[db]\nuser = \"admin\"\n");
+        std::env::set_var("JSON_TRAIT_RS_CONFIG_TEST__DB__PORT", "6543");
+
+        let config = ConfigLoader::new()
+            .file(&yaml_path, ConfigFormat::Yaml)
+            .file(&json_path, ConfigFormat::Json)
+            .optional_file(yaml_path.with_file_name("missing.yaml"), ConfigFormat::Yaml)
+            .file(&toml_path, ConfigFormat::Toml)
+            .env(EnvSource::new("JSON_TRAIT_RS_CONFIG_TEST"))
+            .args(ArgsSource::default(), vec!["log=warning"])
+            .load()
+            .unwrap();
+
+        assert_eq!(
+            config.value(),
+            &rust_type!({"db": {"host": "db.example.com", "port": 6543, "user": "admin"}, "log": "warning"})
+        );
+        assert_eq!(config.source("/db/host"), Some(json_path.display().to_string().as_str()));
+        assert_eq!(config.source("/db/port"), Some("environment variables"));
+        assert_eq!(config.source("/db/user"), Some(toml_path.display().to_string().as_str()));
+        assert_eq!(config.sources("/log").len(), 1);
+        assert_eq!(config.layers().len(), 5);
+        std::fs::remove_dir_all(yaml_path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_file_errors() {
+        let invalid_path = write_file("errors", "invalid.json", "{");
+        let missing_path = invalid_path.with_file_name("missing.json");
+        for (path, backend) in [(invalid_path.clone(), "serde_json"), (missing_path, "filesystem")] {
+            let error = ConfigLoader::new().file(&path, ConfigFormat::Json).load().unwrap_err();
+            assert!(error.to_string().starts_with("config backend failure"), "{}", error);
+            let source = std::error::Error::source(&error).map(ToString::to_string).unwrap();
+            assert!(
+                source.starts_with(&format!("unable to load layer `{}`: {} backend failure", path.display(), backend)),
+                "{}",
+                source
+            );
+        }
+        std::fs::remove_dir_all(invalid_path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_non_string_keys() {
+        let path = write_file("non_string_keys", "config.yaml", "ports:\n  8080: web\n");
+        let expected_source = Error::backend("serde_yaml", "/ports", "Mapping key `8080` is not a string");
+
+        let error = ConfigLoader::new().file(&path, ConfigFormat::Yaml).load().unwrap_err();
+        assert_eq!(layer_error(&error).layer, path.display().to_string());
+        assert_eq!(layer_error(&error).source, expected_source);
+
+        let value = serde_yaml::from_str::<serde_yaml::Value>(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        let error = ConfigLoader::new().value("yaml", &value).unwrap_err();
+        assert_eq!(layer_error(&error).layer, "yaml");
+        assert_eq!(layer_error(&error).source, expected_source);
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_load_returns_the_current_content() {
+        let path = write_file("reload", "config.yaml", "key: 1");
+        let loader = ConfigLoader::new().file(&path, ConfigFormat::Yaml);
+        assert_eq!(loader.load().map(super::Config::into_value), Ok(rust_type!({"key": 1})));
+        std::fs::write(&path, "key: 2").unwrap();
+        assert_eq!(loader.load().map(super::Config::into_value), Ok(rust_type!({"key": 2})));
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,370 @@
+// Conversions from any `JsonType` into the values of the supported backends.
+// The target value is built (via `JsonBuilder`) while traversing the source object, so no intermediate
+// `RustType` is created.
+//
+// Integers that cannot be represented by the target backend are reported as `Error::NumericOverflow`, while
+// numbers that are not representable as exact decimals (because the backend does not preserve them) are
+// converted via their f64 value.
+use crate::{
+    error::Error,
+    json_builder::{JsonBuilder, JsonBuilderContext},
+    json_type::{JsonMapTrait, JsonType},
+    rust_type_impl::RustType,
+};
+use std::marker::PhantomData;
+
+// Collect the items produced by `results` via `collect`, stopping on the first error
+fn try_collect<V, R, I: Iterator<Item = Result<V, Error>>, C: FnOnce(&mut dyn Iterator<Item = V>) -> R>(results: I, collect: C) -> Result<R, Error> {
+    let mut error = None;
+    let collected = collect(&mut results.map_while(|result| result.map_err(|err| error = Some(err)).ok()));
+    error.map_or(Ok(collected), Err)
+}
+
+// Build any `JsonBuilder` backend value from any `JsonType`
+/// # Errors
+///
+/// Will return `Err` if `json_object` contains a value not representable by `B`
+pub fn from_json_type<B: JsonBuilder, T: JsonType>(json_object: &T) -> Result<B, Error> {
+    build(&PhantomData::<B>, json_object)
+}
+
+// Build the value of `context` from any `JsonType`
+pub(crate) fn build<C: JsonBuilderContext, T: JsonType>(context: &C, json_object: &T) -> Result<C::Value, Error> {
+    #[cfg(feature = "big_integer")]
+    {
+        if let (None, Some(big_integer)) = (json_object.as_integer(), json_object.as_big_integer()) {
+            return context.big_integer(&big_integer);
+        }
+    }
+
+    if let Some(array) = json_object.as_array() {
+        try_collect(
+            array
+                .enumerate()
+                .map(|(index, item)| build(context, item).map_err(|error| error.prefix_path(&index.to_string()))),
+            |items| context.array(items),
+        )
+    } else if let Some(bool) = json_object.as_boolean() {
+        Ok(context.bool(bool))
+    } else if let Some(integer) = json_object.as_integer() {
+        context.integer(integer)
+    } else if json_object.is_null() {
+        Ok(context.null())
+    } else if let Some(number) = json_object.as_number() {
+        Ok(json_object
+            .as_number_str()
+            .map_or_else(|| context.number(number), |number_str| context.decimal(number, &number_str)))
+    } else if let Some(object) = json_object.as_object() {
+        try_collect(
+            object
+                .items()
+                .map(|(key, value)| build(context, value).map(|value| (key, value)).map_err(|error| error.prefix_path(key))),
+            |items| context.object(items),
+        )
+    } else if let Some(string) = json_object.as_string_cow() {
+        Ok(context.string(&string))
+    } else {
+        Err(inconsistent_value(json_object))
+    }
+}
+
+// Error reported for values not exposed by any `JsonType` accessor (ie. a backend reporting a string while
+// `JsonType::as_string` returns `None`), so that such backend bugs do not turn into panics.
+// `JsonType::primitive_type` is not usable here, as it relies on the very same accessors.
+pub(crate) fn inconsistent_value<T: JsonType>(_json_object: &T) -> Error {
+    Error::backend(std::any::type_name::<T>(), "", "Value is not exposed by any JsonType accessor")
+}
+
+// `ToRustType::to_rust_type` already builds the `RustType` while traversing the object
+pub fn to_rust_type<T: JsonType>(json_object: &T) -> RustType {
+    json_object.to_rust_type()
+}
+
+// Integers are supported up to u64 (any integer with `serde_json_arbitrary_precision` feature)
+/// # Errors
+///
+/// Will return `Err` if `json_object` contains an integer not representable by `serde_json`
+#[cfg(feature = "trait_serde_json")]
+pub fn to_serde_json<T: JsonType>(json_object: &T) -> Result<serde_json::Value, Error> {
+    from_json_type(json_object)
+}
+
+// Integers are supported up to u64
+/// # Errors
+///
+/// Will return `Err` if `json_object` contains an integer not representable by `serde_yaml`
+#[cfg(feature = "trait_serde_yaml")]
+pub fn to_serde_yaml<T: JsonType>(json_object: &T) -> Result<serde_yaml::Value, Error> {
+    from_json_type(json_object)
+}
+
+// Integers are supported up to u64
+/// # Errors
+///
+/// Will return `Err` if `json_object` contains an integer not representable by `json`
+#[cfg(feature = "trait_json")]
+pub fn to_json_value<T: JsonType>(json_object: &T) -> Result<json::JsonValue, Error> {
+    from_json_type(json_object)
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "trait_json")]
+    use super::to_json_value;
+    use super::to_rust_type;
+    #[cfg(feature = "trait_serde_json")]
+    use super::to_serde_json;
+    #[cfg(feature = "trait_serde_yaml")]
+    use super::to_serde_yaml;
+    #[cfg(any(feature = "trait_json", feature = "trait_serde_json", feature = "trait_serde_yaml"))]
+    use crate::error::Error;
+    use crate::rust_type_impl::{Decimal, RustType};
+
+    lazy_static! {
+        static ref TESTING_VALUE: RustType = rust_type!({"array": [1, -2, 2.5, null, true, "string"], "object": {"key": {}}});
+    }
+
+    #[test]
+    fn test_to_rust_type() {
+        assert_eq!(to_rust_type(&*TESTING_VALUE), *TESTING_VALUE);
+    }
+
+    #[cfg(feature = "trait_serde_json")]
+    #[test]
+    fn test_to_serde_json() {
+        assert_eq!(
+            to_serde_json(&*TESTING_VALUE),
+            Ok(json!({"array": [1, -2, 2.5, null, true, "string"], "object": {"key": {}}}))
+        );
+    }
+
+    #[cfg(all(feature = "trait_serde_json", not(feature = "serde_json_arbitrary_precision")))]
+    #[test]
+    fn test_to_serde_json_integer_overflow() {
+        assert_eq!(
+            to_serde_json(&rust_type!({"key": [i128::max_value()]})),
+            Err(Error::NumericOverflow {
+                path: "/key/0".to_string(),
+                value: i128::max_value().to_string(),
+                target: "serde_json number",
+            })
+        );
+    }
+
+    #[cfg(feature = "serde_json_arbitrary_precision")]
+    #[test]
+    fn test_to_serde_json_preserves_exact_numbers() {
+        let value = rust_type!([i128::max_value(), (RustType::Decimal(Decimal::new("0.1000000000000000055511151231257827").unwrap()))]);
+        assert_eq!(
+            to_serde_json(&value).map(|value| value.to_string()),
+            Ok(format!("[{},0.1000000000000000055511151231257827]", i128::max_value()))
+        );
+    }
+
+    #[cfg(all(feature = "trait_serde_json", feature = "trait_serde_yaml"))]
+    #[test]
+    fn test_between_backends() {
+        let yaml: serde_yaml::Value = serde_yaml::from_str("key: [1, 2.5, ~, text]").unwrap();
+        assert_eq!(to_serde_json(&yaml), Ok(json!({"key": [1, 2.5, null, "text"]})));
+        assert_eq!(to_serde_yaml(&to_serde_json(&yaml).unwrap()), Ok(yaml));
+    }
+
+    #[cfg(feature = "trait_serde_yaml")]
+    #[test]
+    fn test_to_serde_yaml() {
+        let expected_value: serde_yaml::Value = serde_yaml::from_str(
+            r#"
+            array: [1, -2, 2.5, null, true, "string"]
+            object: {"key": {}}
+            "#,
+        )
+        .unwrap();
+        assert_eq!(to_serde_yaml(&*TESTING_VALUE), Ok(expected_value));
+        assert_eq!(
+            to_serde_yaml(&rust_type!(i128::min_value())),
+            Err(Error::NumericOverflow {
+                path: String::new(),
+                value: i128::min_value().to_string(),
+                target: "serde_yaml number",
+            })
+        );
+    }
+
+    #[cfg(feature = "trait_json")]
+    #[test]
+    fn test_to_json_value() {
+        assert_eq!(
+            to_json_value(&*TESTING_VALUE),
+            Ok(json::object! {"array" => json::array![1, -2, 2.5, json::Null, true, "string"], "object" => json::object!{"key" => json::object!{}}})
+        );
+        assert_eq!(
+            to_json_value(&RustType::Decimal(Decimal::new("0.1000000000000000055511151231257827").unwrap())).map(|value| value.to_string()),
+            Ok("1.0000000000000000555e-1".to_string())
+        );
+        assert_eq!(
+            to_json_value(&rust_type!([i128::from(u64::max_value()) + 1])),
+            Err(Error::NumericOverflow {
+                path: "/0".to_string(),
+                value: (u128::from(u64::max_value()) + 1).to_string(),
+                target: "json number",
+            })
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests_from_json_type {
+    use super::from_json_type;
+    use crate::{
+        error::Error,
+        json_builder::JsonBuilder,
+        json_type::{JsonMap, JsonType, ToRustType},
+        rust_type_impl::{Decimal, RustType},
+    };
+    use test_case::test_case;
+
+    lazy_static! {
+        static ref TESTING_VALUE: RustType = rust_type!({"array": [1, -2, 2.5, null, true, "string"], "object": {"key": {"nested": []}}});
+    }
+
+    // Output of generic code that does not know the backend chosen by the caller
+    fn wrapped<B: JsonBuilder>(value: B) -> B {
+        B::object(vec![("wrapped", B::array(vec![value, B::null()]))])
+    }
+
+    fn assert_round_trip<B: JsonBuilder + JsonType>() {
+        assert_eq!(from_json_type::<B, _>(&*TESTING_VALUE).map(|value| value.to_rust_type()), Ok(TESTING_VALUE.clone()));
+        assert_eq!(wrapped(B::string("value")).to_rust_type(), rust_type!({"wrapped": ["value", null]}));
+    }
+
+    #[test]
+    fn test_rust_type() {
+        assert_round_trip::<RustType>();
+    }
+
+    #[test]
+    fn test_rust_type_decimal() {
+        let decimal = RustType::Decimal(Decimal::new("0.1000000000000000055511151231257827").unwrap());
+        assert_eq!(RustType::decimal(0.1, "0.1"), RustType::Number(0.1));
+        assert_eq!(from_json_type::<RustType, _>(&decimal), Ok(decimal));
+    }
+
+    #[cfg(feature = "trait_bson")]
+    #[test]
+    fn test_bson() {
+        assert_round_trip::<bson::Bson>();
+    }
+
+    #[cfg(feature = "trait_cbor")]
+    #[test]
+    fn test_cbor() {
+        assert_round_trip::<ciborium::value::Value>();
+    }
+
+    #[cfg(feature = "trait_json")]
+    #[test]
+    fn test_json() {
+        assert_round_trip::<json::JsonValue>();
+    }
+
+    #[cfg(feature = "trait_rmpv")]
+    #[test]
+    fn test_rmpv() {
+        assert_round_trip::<rmpv::Value>();
+    }
+
+    #[cfg(feature = "trait_ron")]
+    #[test]
+    fn test_ron() {
+        assert_round_trip::<ron::Value>();
+    }
+
+    #[cfg(feature = "trait_serde_json")]
+    #[test]
+    fn test_serde_json() {
+        assert_round_trip::<serde_json::Value>();
+    }
+
+    #[cfg(feature = "trait_serde_yaml")]
+    #[test]
+    fn test_serde_yaml() {
+        assert_round_trip::<serde_yaml::Value>();
+    }
+
+    #[cfg(feature = "trait_simd_json")]
+    #[test]
+    fn test_simd_json() {
+        assert_round_trip::<simd_json::OwnedValue>();
+    }
+
+    #[cfg(feature = "shared")]
+    #[test]
+    fn test_shared() {
+        assert_round_trip::<crate::shared::SharedValue>();
+    }
+
+    // Value not exposed by any accessor, as a backend with inconsistent accessors
+    struct InconsistentValue;
+
+    impl ToRustType for InconsistentValue {}
+
+    impl JsonType for InconsistentValue {
+        fn as_array<'json>(&'json self) -> Option<Box<dyn ExactSizeIterator<Item = &Self> + 'json>> {
+            None
+        }
+
+        fn as_boolean(&self) -> Option<bool> {
+            None
+        }
+
+        fn as_integer(&self) -> Option<i128> {
+            None
+        }
+
+        fn as_null(&self) -> Option<()> {
+            None
+        }
+
+        fn as_number(&self) -> Option<f64> {
+            None
+        }
+
+        fn as_object(&self) -> Option<JsonMap<Self>> {
+            None
+        }
+
+        fn as_string(&self) -> Option<&str> {
+            None
+        }
+
+        fn get_attribute(&self, _attribute_name: &str) -> Option<&Self> {
+            None
+        }
+
+        fn get_index(&self, _index: usize) -> Option<&Self> {
+            None
+        }
+    }
+
+    #[test]
+    fn test_inconsistent_value() {
+        assert_eq!(
+            from_json_type::<RustType, _>(&InconsistentValue),
+            Err(Error::backend(
+                std::any::type_name::<InconsistentValue>(),
+                "",
+                "Value is not exposed by any JsonType accessor"
+            ))
+        );
+    }
+
+    #[test_case(i128::from(i32::max_value()) + 1, Ok(rust_type!(i128::from(i32::max_value()) + 1)) ; "above int32")]
+    #[test_case(i128::from(u64::max_value()), Err(Error::NumericOverflow { path: "/0".to_string(), value: u64::max_value().to_string(), target: "BSON integer" }) ; "above int64")]
+    #[cfg(feature = "trait_bson")]
+    fn test_bson_integers(integer: i128, expected_value: Result<RustType, Error>) {
+        assert_eq!(
+            from_json_type::<bson::Bson, _>(&rust_type!([integer])).map(|value| value.to_rust_type()),
+            expected_value.map(|value| rust_type!([value]))
+        );
+    }
+}
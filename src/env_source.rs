@@ -0,0 +1,250 @@
+// Environment variables and command line arguments exposed as nested JSON objects (ie. twelve-factor configurations).
+//
+// `EnvSource` maps variables as `APP__DB__HOST=x` (prefix `APP`, separator `__`) into `{"db": {"host": "x"}}`, while
+// `ArgsSource` maps `key=value` arguments as `db.host=x` (separator `.`), as collected by repeated `--set` options
+// of command line parsers. The output is a `RustType`, so it can be merged with the content of configuration files
+// via the same tooling.
+//
+// Values are parsed according to `ValueParsing`. Objects set by multiple entries are merged (ie. `APP__DB={"port": 1}`
+// and `APP__DB__HOST=x`), while setting both an object and another value at the same path is reported as
+// `Error::TypeMismatch`. Other values set multiple times are replaced: arguments are applied in order, while
+// environment variables are applied in lexicographic order of their names (the environment has no defined order).
+//
+// Arrays are not created from keys, use JSON literals instead (ie. `APP__HOSTS=["a", "b"]`).
+use crate::{
+    error::Error,
+    json_type::{JsonType, PrimitiveType, ToRustType},
+    lazy_json::LazyDocument,
+    rust_type_impl::RustType,
+};
+use std::collections::{hash_map::Entry, HashMap};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ValueParsing {
+    // All the values are reported as strings
+    Strings,
+    // Values that are valid JSON documents (ie. `true`, `null`, `5432`, `"quoted"`, `[1, 2]` or `{"key": 1}`) are
+    // reported as the value they represent, other values are reported as strings
+    Literals,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct EnvSource {
+    // Variables not starting with `prefix` followed by `separator` are ignored (all the variables are considered if empty)
+    pub prefix: String,
+    pub separator: String,
+    pub value_parsing: ValueParsing,
+    // Keys are lowercased (ie. `APP__DB__HOST` is mapped to `/db/host`)
+    pub lowercase_keys: bool,
+}
+
+impl Default for EnvSource {
+    #[must_use]
+    fn default() -> Self {
+        Self {
+            prefix: String::new(),
+            separator: "__".to_string(),
+            value_parsing: ValueParsing::Literals,
+            lowercase_keys: true,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ArgsSource {
+    pub separator: String,
+    pub value_parsing: ValueParsing,
+}
+
+impl Default for ArgsSource {
+    #[must_use]
+    fn default() -> Self {
+        Self {
+            separator: ".".to_string(),
+            value_parsing: ValueParsing::Literals,
+        }
+    }
+}
+
+fn parse_value(value: &str, value_parsing: ValueParsing) -> RustType {
+    match value_parsing {
+        ValueParsing::Strings => RustType::String(value.to_string()),
+        ValueParsing::Literals => LazyDocument::parse(value).map_or_else(|_| RustType::String(value.to_string()), |document| document.root().to_rust_type()),
+    }
+}
+
+const fn type_mismatch(found: PrimitiveType) -> Error {
+    Error::TypeMismatch {
+        path: String::new(),
+        expected: PrimitiveType::Object,
+        found,
+    }
+}
+
+// Value created by setting `value` at `path` of an empty object
+fn nested(path: &[String], value: RustType) -> RustType {
+    path.iter().rev().fold(value, |value, key| {
+        let mut object = HashMap::new();
+        let _ = object.insert(key.clone(), value);
+        RustType::Object(object)
+    })
+}
+
+// Set `value` at `path` of `target`, merging objects
+fn set(target: &mut RustType, path: &[String], value: RustType) -> Result<(), Error> {
+    match (path.split_first(), target) {
+        (None, target) => merge(target, value),
+        (Some((key, path)), RustType::Object(object)) => match object.entry(key.clone()) {
+            Entry::Vacant(entry) => {
+                let _ = entry.insert(nested(path, value));
+                Ok(())
+            }
+            Entry::Occupied(mut entry) => set(entry.get_mut(), path, value).map_err(|error| error.prefix_path(key)),
+        },
+        (Some(_), target) => Err(type_mismatch(target.primitive_type())),
+    }
+}
+
+fn merge(target: &mut RustType, value: RustType) -> Result<(), Error> {
+    match (target.is_object(), value) {
+        (true, RustType::Object(values)) => values.into_iter().try_for_each(|(key, value)| set(target, std::slice::from_ref(&key), value)),
+        (true, value) => Err(type_mismatch(value.primitive_type())),
+        (false, value) if value.is_object() => Err(type_mismatch(target.primitive_type())),
+        (false, value) => {
+            *target = value;
+            Ok(())
+        }
+    }
+}
+
+impl EnvSource {
+    #[must_use]
+    pub fn new(prefix: &str) -> Self {
+        Self {
+            prefix: prefix.to_string(),
+            ..Self::default()
+        }
+    }
+
+    // Path of the variable, `None` if the variable has to be ignored (not matching the prefix or with empty components)
+    fn path(&self, name: &str) -> Option<Vec<String>> {
+        let name = if self.prefix.is_empty() {
+            name
+        } else {
+            name.strip_prefix(self.prefix.as_str())?.strip_prefix(self.separator.as_str())?
+        };
+        name.split(self.separator.as_str())
+            .map(|component| match component {
+                "" => None,
+                component if self.lowercase_keys => Some(component.to_lowercase()),
+                component => Some(component.to_string()),
+            })
+            .collect()
+    }
+
+    // Object represented by the variables of the current process. Variables with non UTF-8 names or values are ignored
+    /// # Errors
+    ///
+    /// Will return `Err` if a path is set to both an object and another value
+    pub fn from_env(&self) -> Result<RustType, Error> {
+        self.from_vars(std::env::vars_os().filter_map(|(name, value)| Some((name.into_string().ok()?, value.into_string().ok()?))))
+    }
+
+    /// # Errors
+    ///
+    /// Will return `Err` if a path is set to both an object and another value
+    pub fn from_vars<K: AsRef<str>, V: AsRef<str>, I: IntoIterator<Item = (K, V)>>(&self, vars: I) -> Result<RustType, Error> {
+        let mut vars = vars
+            .into_iter()
+            .filter_map(|(name, value)| self.path(name.as_ref()).map(|path| (name, path, value)))
+            .collect::<Vec<_>>();
+        vars.sort_by(|(name, ..), (other_name, ..)| name.as_ref().cmp(other_name.as_ref()));
+
+        let mut object = RustType::Object(HashMap::new());
+        for (_, path, value) in vars {
+            set(&mut object, &path, parse_value(value.as_ref(), self.value_parsing))?;
+        }
+        Ok(object)
+    }
+}
+
+impl ArgsSource {
+    // Object represented by `key=value` arguments (the position of the argument is reported as line on errors)
+    /// # Errors
+    ///
+    /// Will return `Err` if an argument is not in the `key=value` form, if a key contains empty components
+    /// or if a path is set to both an object and another value
+    pub fn from_args<S: AsRef<str>, I: IntoIterator<Item = S>>(&self, args: I) -> Result<RustType, Error> {
+        let mut object = RustType::Object(HashMap::new());
+        for (index, arg) in args.into_iter().enumerate() {
+            let arg = arg.as_ref();
+            let invalid_syntax = |column: usize, message: String| Error::InvalidSyntax { line: index + 1, column, message };
+            let (key, value) = arg
+                .split_once('=')
+                .ok_or_else(|| invalid_syntax(arg.chars().count() + 1, format!("expected `key=value`, found `{}`", arg)))?;
+
+            let mut path = Vec::new();
+            let mut column = 1;
+            for component in key.split(self.separator.as_str()) {
+                if component.is_empty() {
+                    return Err(invalid_syntax(column, format!("empty key component in `{}`", key)));
+                }
+                column += component.chars().count() + self.separator.chars().count();
+                path.push(component.to_string());
+            }
+            set(&mut object, &path, parse_value(value, self.value_parsing))?;
+        }
+        Ok(object)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ArgsSource, EnvSource, ValueParsing};
+    use crate::{
+        error::Error,
+        json_type::PrimitiveType,
+        rust_type_impl::{Decimal, RustType},
+    };
+    use test_case::test_case;
+
+    #[test_case(&[("APP__DB__HOST", "localhost"), ("APP__DB__PORT", "5432")], Ok(rust_type!({"db": {"host": "localhost", "port": 5432}})) ; "nested keys")]
+    #[test_case(&[("APP__DEBUG", "true"), ("APP__RATIO", "0.5"), ("APP__NAME", "null"), ("APP__TEXT", "1.0.0")], Ok(rust_type!({"debug": true, "ratio": 0.5, "name": null, "text": "1.0.0"})) ; "literals")]
+    #[test_case(&[("APP__PRECISE", "0.1000000000000000055511151231257827")], Ok(rust_type!({"precise": (RustType::Decimal(Decimal::new("0.1000000000000000055511151231257827").unwrap()))})) ; "inexact numbers")]
+    #[test_case(&[("APP__HOSTS", r#"["a", "b"]"#), ("APP__QUOTED", r#""5432""#)], Ok(rust_type!({"hosts": ["a", "b"], "quoted": "5432"})) ; "json literals")]
+    #[test_case(&[("APP__DB", r#"{"port": 1, "user": "x"}"#), ("APP__DB__PORT", "2")], Ok(rust_type!({"db": {"port": 2, "user": "x"}})) ; "objects are merged")]
+    #[test_case(&[("OTHER__KEY", "1"), ("APPLICATION__KEY", "1"), ("APP", "1"), ("APP____KEY", "1"), ("APP__KEY__", "1")], Ok(rust_type!({})) ; "ignored variables")]
+    #[test_case(&[("APP__DB__HOST", "x"), ("APP__DB", "y")], Err(Error::TypeMismatch { path: "/db".to_string(), expected: PrimitiveType::Object, found: PrimitiveType::String }) ; "object and value")]
+    #[test_case(&[("APP__DB__HOST__NAME", "x"), ("APP__DB__HOST", "1")], Err(Error::TypeMismatch { path: "/db/host".to_string(), expected: PrimitiveType::Object, found: PrimitiveType::Integer }) ; "nested object and value")]
+    fn test_env_source_from_vars(vars: &[(&str, &str)], expected_value: Result<RustType, Error>) {
+        assert_eq!(EnvSource::new("APP").from_vars(vars.iter().copied()), expected_value);
+    }
+
+    #[test]
+    fn test_env_source_options() {
+        let vars = [("CFG-Db-Port", "5432"), ("PATH", "/bin")];
+        let env_source = EnvSource {
+            prefix: "CFG".to_string(),
+            separator: "-".to_string(),
+            value_parsing: ValueParsing::Strings,
+            lowercase_keys: false,
+        };
+        assert_eq!(env_source.from_vars(vars.iter().copied()), Ok(rust_type!({"Db": {"Port": "5432"}})));
+        assert_eq!(EnvSource::default().from_vars(vars.iter().copied()), Ok(rust_type!({"cfg-db-port": 5432, "path": "/bin"})));
+    }
+
+    #[test]
+    fn test_env_source_from_env() {
+        std::env::set_var("JSON_TRAIT_RS_TEST__KEY__NESTED", "[1]");
+        assert_eq!(EnvSource::new("JSON_TRAIT_RS_TEST").from_env(), Ok(rust_type!({"key": {"nested": [1]}})));
+    }
+
+    #[test_case(&["db.host=localhost", "db.port=5432"], Ok(rust_type!({"db": {"host": "localhost", "port": 5432}})) ; "nested keys")]
+    #[test_case(&["key=1", "key=2", "other=a=b", "empty="], Ok(rust_type!({"key": 2, "other": "a=b", "empty": ""})) ; "last value wins")]
+    #[test_case(&["key=1", "db"], Err(Error::InvalidSyntax { line: 2, column: 3, message: "expected `key=value`, found `db`".to_string() }) ; "missing value")]
+    #[test_case(&["db..port=1"], Err(Error::InvalidSyntax { line: 1, column: 4, message: "empty key component in `db..port`".to_string() }) ; "empty component")]
+    #[test_case(&["db=1", "db.port=1"], Err(Error::TypeMismatch { path: "/db".to_string(), expected: PrimitiveType::Object, found: PrimitiveType::Integer }) ; "object and value")]
+    fn test_args_source(args: &[&str], expected_value: Result<RustType, Error>) {
+        assert_eq!(ArgsSource::default().from_args(args), expected_value);
+    }
+}
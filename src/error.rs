@@ -1,6 +1,108 @@
+use crate::{fragment_helpers::fragment_from_fragment_components, json_type::PrimitiveType};
+use std::fmt;
 use thiserror::Error;
+
 #[derive(Debug, Error, PartialEq)]
+#[non_exhaustive]
 pub enum Error {
     #[error("Unsupported primitive type `{type_str}`. Available types are defined by `json_trait_rs::PrimitiveType::VARIANTS`")]
     UnsupportedPrimitiveType { type_str: String },
+    #[error("Invalid JSON pointer `{pointer}`: {reason}")]
+    InvalidPointer { pointer: String, reason: String },
+    #[error("Missing key `{key}` at `{path}`")]
+    MissingKey { path: String, key: String },
+    #[error("Type mismatch at `{path}`: expected {expected}, found {found}")]
+    TypeMismatch { path: String, expected: PrimitiveType, found: PrimitiveType },
+    #[error("Numeric overflow at `{path}`: `{value}` does not fit into {target}")]
+    NumericOverflow { path: String, value: String, target: &'static str },
+    #[error("Invalid syntax at line {line}, column {column}: {message}")]
+    InvalidSyntax { line: usize, column: usize, message: String },
+    #[error("{backend} backend failure at `{path}`")]
+    Backend {
+        backend: &'static str,
+        path: String,
+        #[source]
+        source: BackendError,
+    },
+}
+
+impl Error {
+    pub fn backend<E: Into<Box<dyn std::error::Error + Send + Sync>>>(backend: &'static str, path: &str, error: E) -> Self {
+        Self::Backend {
+            backend,
+            path: path.to_string(),
+            source: BackendError(error.into()),
+        }
+    }
+
+    // Prepend `fragment_component` to the path carried by the error, if any.
+    // This allows nested traversals to report the path from the root object.
+    pub(crate) fn prefix_path(mut self, fragment_component: &str) -> Self {
+        match &mut self {
+            Self::MissingKey { path, .. } | Self::TypeMismatch { path, .. } | Self::NumericOverflow { path, .. } | Self::Backend { path, .. } => {
+                path.insert_str(0, &fragment_from_fragment_components(&[fragment_component]));
+            }
+            Self::UnsupportedPrimitiveType { .. } | Self::InvalidPointer { .. } | Self::InvalidSyntax { .. } => {}
+        }
+        self
+    }
+}
+
+// Opaque wrapper around the error reported by the underlying library (ie. a Python exception)
+// It allows `Error` to be `PartialEq` (errors are compared via their string representation)
+// while still exposing the original error via `std::error::Error::source`.
+#[derive(Debug)]
+pub struct BackendError(Box<dyn std::error::Error + Send + Sync>);
+
+impl BackendError {
+    // Error reported by the underlying library, it allows downcasting to the concrete error type
+    #[must_use]
+    pub fn get_ref(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+        self.0.as_ref()
+    }
+}
+
+impl fmt::Display for BackendError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.0, formatter)
+    }
+}
+
+impl std::error::Error for BackendError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.0.source()
+    }
+}
+
+impl PartialEq for BackendError {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_string() == other.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Error;
+    use std::error::Error as _;
+
+    #[test]
+    fn test_backend_error_exposes_source() {
+        let error = Error::backend("pyo3", "/key/0", "ValueError: boom");
+        assert_eq!(error.to_string(), "pyo3 backend failure at `/key/0`");
+        assert_eq!(error.source().map(ToString::to_string), Some("ValueError: boom".to_string()));
+    }
+
+    #[test]
+    fn test_prefix_path() {
+        assert_eq!(
+            Error::backend("pyo3", "/0", "boom").prefix_path("k/y").prefix_path("root"),
+            Error::backend("pyo3", "/root/k~1y/0", "boom")
+        );
+    }
+
+    #[test]
+    fn test_backend_errors_are_compared_by_message() {
+        assert_eq!(Error::backend("pyo3", "", "boom"), Error::backend("pyo3", "", "boom"));
+        assert_ne!(Error::backend("pyo3", "", "boom"), Error::backend("pyo3", "", "bang"));
+    }
 }
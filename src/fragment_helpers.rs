@@ -1,3 +1,5 @@
+use crate::error::Error;
+
 pub fn fragment_components_from_fragment(fragment: &str) -> impl Iterator<Item = String> {
     let fragment = fragment.trim_start_matches('/');
     if fragment.is_empty() {
@@ -11,6 +13,47 @@ pub fn fragment_components_from_fragment(fragment: &str) -> impl Iterator<Item =
     }
 }
 
+/// Strict version of `fragment_components_from_fragment` that follows [RFC 6901](https://tools.ietf.org/html/rfc6901).
+///
+/// # Errors
+/// `Error::InvalidPointer` is returned if a non empty fragment does not start with `/` or
+/// if `~` is not part of the `~0` and `~1` escape sequences.
+pub fn try_fragment_components_from_fragment(fragment: &str) -> Result<Vec<String>, Error> {
+    if fragment.is_empty() {
+        return Ok(Vec::with_capacity(0));
+    }
+    if !fragment.starts_with('/') {
+        return Err(Error::InvalidPointer {
+            pointer: fragment.to_string(),
+            reason: "a non empty pointer has to start with `/`".to_string(),
+        });
+    }
+    fragment[1..]
+        .split('/')
+        .map(|fragment_part| {
+            let mut component = String::with_capacity(fragment_part.len());
+            let mut chars = fragment_part.chars();
+            while let Some(c) = chars.next() {
+                if c == '~' {
+                    match chars.next() {
+                        Some('0') => component.push('~'),
+                        Some('1') => component.push('/'),
+                        _ => {
+                            return Err(Error::InvalidPointer {
+                                pointer: fragment.to_string(),
+                                reason: "`~` has to be followed by `0` or `1`".to_string(),
+                            })
+                        }
+                    }
+                } else {
+                    component.push(c);
+                }
+            }
+            Ok(component)
+        })
+        .collect()
+}
+
 pub fn fragment_from_fragment_components<I: IntoIterator<Item = T>, T: ToString>(fragment_components: I) -> String {
     fragment_components
         .into_iter()
@@ -24,7 +67,8 @@ pub fn fragment_from_fragment_components<I: IntoIterator<Item = T>, T: ToString>
 
 #[cfg(test)]
 mod tests {
-    use super::{fragment_components_from_fragment, fragment_from_fragment_components};
+    use super::{fragment_components_from_fragment, fragment_from_fragment_components, try_fragment_components_from_fragment};
+    use crate::error::Error;
     use test_case::test_case;
 
     #[test_case(""       => Vec::<String>::new() ; "empty")]
@@ -37,6 +81,17 @@ mod tests {
         fragment_components_from_fragment(fragment).collect::<Vec<_>>()
     }
 
+    #[test_case(""        => Ok(Vec::<String>::new())       ; "empty")]
+    #[test_case("/"       => Ok(vec!["".to_string()])       ; "empty key")]
+    #[test_case("/p1/p2"  => Ok(vec!["p1".to_string(), "p2".to_string()]) ; "two levels")]
+    #[test_case("/~01"    => Ok(vec!["~1".to_string()])     ; "escape sequences are decoded once")]
+    #[test_case("p1"      => Err(Error::InvalidPointer { pointer: "p1".to_string(), reason: "a non empty pointer has to start with `/`".to_string() }) ; "missing leading slash")]
+    #[test_case("/p1/~2"  => Err(Error::InvalidPointer { pointer: "/p1/~2".to_string(), reason: "`~` has to be followed by `0` or `1`".to_string() }) ; "invalid escape sequence")]
+    #[test_case("/p1~"    => Err(Error::InvalidPointer { pointer: "/p1~".to_string(), reason: "`~` has to be followed by `0` or `1`".to_string() }) ; "trailing tilde")]
+    fn test_try_fragment_components_from_fragment(fragment: &str) -> Result<Vec<String>, Error> {
+        try_fragment_components_from_fragment(fragment)
+    }
+
     #[test_case(vec![]           => ""       ; "empty")]
     #[test_case(vec!["p1"]       => "/p1"    ; "one level")]
     #[test_case(vec!["p2", "p3"] => "/p2/p3" ; "two levels")]
@@ -0,0 +1,559 @@
+// JSON5 (https://spec.json5.org/) parser producing `RustType` instances.
+// JSON5 is a superset of JSON and of JSON with comments (JSONC), so the parser can be used for all those formats.
+//
+// Strings and object keys without escape sequences are borrowed from the input (see `from_str_borrowed`).
+//
+// Numeric values are mapped as follows
+// * integers (decimal or hexadecimal) are reported as `RustType::Integer`. Integers not fitting into i128 are reported
+//   as `RustType::BigInteger` if `big_integer` feature is enabled, as (lossy) `RustType::Number` otherwise
+// * `Infinity`, `-Infinity`, `NaN` and all the other numbers are reported as `RustType::Number`
+use crate::{error::Error, rust_type_impl::RustType, rust_type_ref_impl::RustTypeRef};
+#[cfg(feature = "big_integer")]
+use num_bigint::BigInt;
+use std::{borrow::Cow, collections::HashMap};
+
+// Maximum nesting of arrays and objects, this prevents stack overflows on malicious inputs
+const MAX_DEPTH: usize = 128;
+
+/// # Errors
+///
+/// Will return `Err` if `text` is not a valid JSON5 document. The error reports the position of the failure
+pub fn from_str(text: &str) -> Result<RustType, Error> {
+    from_str_borrowed(text).map(RustTypeRef::into_owned)
+}
+
+/// # Errors
+///
+/// Will return `Err` if `text` is not a valid JSON5 document. The error reports the position of the failure
+pub fn from_str_borrowed(text: &str) -> Result<RustTypeRef, Error> {
+    let mut parser = Parser { text, position: 0 };
+    parser.skip_whitespaces_and_comments()?;
+    let value = parser.parse_value(0)?;
+    parser.skip_whitespaces_and_comments()?;
+    match parser.peek() {
+        Some(character) => Err(parser.error(format!("unexpected character `{}` after the document", character))),
+        None => Ok(value),
+    }
+}
+
+// One-based line and column (in characters) of the byte offset `position`
+fn line_column(text: &str, position: usize) -> (usize, usize) {
+    let mut line = 1;
+    let mut column = 1;
+    let mut previous_character = None;
+    for character in text[..position].chars() {
+        match character {
+            '\n' if previous_character == Some('\r') => {}
+            '\n' | '\r' | '\u{2028}' | '\u{2029}' => {
+                line += 1;
+                column = 1;
+            }
+            _ => column += 1,
+        }
+        previous_character = Some(character);
+    }
+    (line, column)
+}
+
+fn is_whitespace(character: char) -> bool {
+    character.is_whitespace() || character == '\u{feff}'
+}
+
+fn is_identifier_start(character: char) -> bool {
+    character == '$' || character == '_' || character.is_alphabetic()
+}
+
+fn is_identifier_part(character: char) -> bool {
+    is_identifier_start(character) || character.is_alphanumeric() || character == '\u{200c}' || character == '\u{200d}'
+}
+
+fn integer<'a>(negative: bool, digits: &str, radix: u32) -> RustTypeRef<'a> {
+    let signed_digits = if negative { format!("-{}", digits) } else { digits.to_string() };
+    if let Ok(value) = i128::from_str_radix(&signed_digits, radix) {
+        return RustTypeRef::from(value);
+    }
+
+    #[cfg(feature = "big_integer")]
+    {
+        if let Some(value) = BigInt::parse_bytes(signed_digits.as_bytes(), radix) {
+            return RustTypeRef::from(value);
+        }
+    }
+
+    let magnitude = digits
+        .chars()
+        .filter_map(|digit| digit.to_digit(radix))
+        .fold(0_f64, |acc, digit| acc.mul_add(f64::from(radix), f64::from(digit)));
+    RustTypeRef::from(if negative { -magnitude } else { magnitude })
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    // Byte offset of the next character to parse
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<char> {
+        self.text[self.position..].chars().next()
+    }
+
+    fn peek_second(&self) -> Option<char> {
+        self.text[self.position..].chars().nth(1)
+    }
+
+    fn next_character(&mut self) -> Option<char> {
+        let character = self.peek()?;
+        self.position += character.len_utf8();
+        Some(character)
+    }
+
+    fn error_at(&self, position: usize, message: String) -> Error {
+        let (line, column) = line_column(self.text, position);
+        Error::InvalidSyntax { line, column, message }
+    }
+
+    fn error(&self, message: String) -> Error {
+        self.error_at(self.position, message)
+    }
+
+    fn unexpected_character(&self) -> Error {
+        self.peek().map_or_else(
+            || self.error("unexpected end of input".to_string()),
+            |character| self.error(format!("unexpected character `{}`", character)),
+        )
+    }
+
+    // Consume `keyword` only if it is not followed by other identifier characters
+    fn consume_keyword(&mut self, keyword: &str) -> bool {
+        let rest = &self.text[self.position..];
+        if rest.starts_with(keyword) && !rest[keyword.len()..].chars().next().map_or(false, is_identifier_part) {
+            self.position += keyword.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn consume_while(&mut self, predicate: impl Fn(char) -> bool) -> &'a str {
+        let start = self.position;
+        while self.peek().map_or(false, &predicate) {
+            let _ = self.next_character();
+        }
+        &self.text[start..self.position]
+    }
+
+    fn skip_whitespaces_and_comments(&mut self) -> Result<(), Error> {
+        loop {
+            match (self.peek(), self.peek_second()) {
+                (Some(character), _) if is_whitespace(character) => {
+                    let _ = self.next_character();
+                }
+                (Some('/'), Some('/')) => {
+                    let _ = self.consume_while(|character| !matches!(character, '\n' | '\r' | '\u{2028}' | '\u{2029}'));
+                }
+                (Some('/'), Some('*')) => {
+                    let start = self.position;
+                    match self.text[self.position + 2..].find("*/") {
+                        Some(offset) => self.position += 2 + offset + 2,
+                        None => return Err(self.error_at(start, "unterminated block comment".to_string())),
+                    }
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    fn parse_value(&mut self, depth: usize) -> Result<RustTypeRef<'a>, Error> {
+        match self.peek() {
+            Some('{') => self.parse_object(depth),
+            Some('[') => self.parse_array(depth),
+            Some('"') | Some('\'') => self.parse_string().map(RustTypeRef::from),
+            Some(character) if matches!(character, '+' | '-' | '.' | '0'..='9' | 'I' | 'N') => self.parse_number(),
+            _ => {
+                if self.consume_keyword("null") {
+                    Ok(RustTypeRef::Null)
+                } else if self.consume_keyword("true") {
+                    Ok(RustTypeRef::from(true))
+                } else if self.consume_keyword("false") {
+                    Ok(RustTypeRef::from(false))
+                } else {
+                    Err(self.unexpected_character())
+                }
+            }
+        }
+    }
+
+    fn enter_container(&self, depth: usize) -> Result<(), Error> {
+        if depth >= MAX_DEPTH {
+            Err(self.error(format!("maximum nesting depth ({}) exceeded", MAX_DEPTH)))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn parse_array(&mut self, depth: usize) -> Result<RustTypeRef<'a>, Error> {
+        self.enter_container(depth)?;
+        let _ = self.next_character(); // `[`
+        let mut items = Vec::new();
+        loop {
+            self.skip_whitespaces_and_comments()?;
+            if self.peek() == Some(']') {
+                let _ = self.next_character();
+                return Ok(RustTypeRef::from(items));
+            }
+            items.push(self.parse_value(depth + 1)?);
+            self.skip_whitespaces_and_comments()?;
+            match self.peek() {
+                Some(',') => {
+                    let _ = self.next_character();
+                }
+                Some(']') => {}
+                _ => return Err(self.error("expected `,` or `]`".to_string())),
+            }
+        }
+    }
+
+    fn parse_object(&mut self, depth: usize) -> Result<RustTypeRef<'a>, Error> {
+        self.enter_container(depth)?;
+        let _ = self.next_character(); // `{`
+        let mut object = HashMap::new();
+        loop {
+            self.skip_whitespaces_and_comments()?;
+            let key = match self.peek() {
+                Some('}') => {
+                    let _ = self.next_character();
+                    return Ok(RustTypeRef::from(object));
+                }
+                Some('"') | Some('\'') => self.parse_string()?,
+                _ => self.parse_identifier()?,
+            };
+            self.skip_whitespaces_and_comments()?;
+            if self.peek() != Some(':') {
+                return Err(self.error("expected `:`".to_string()));
+            }
+            let _ = self.next_character();
+            self.skip_whitespaces_and_comments()?;
+            let _ = object.insert(key, self.parse_value(depth + 1)?);
+            self.skip_whitespaces_and_comments()?;
+            match self.peek() {
+                Some(',') => {
+                    let _ = self.next_character();
+                }
+                Some('}') => {}
+                _ => return Err(self.error("expected `,` or `}`".to_string())),
+            }
+        }
+    }
+
+    fn parse_identifier(&mut self) -> Result<Cow<'a, str>, Error> {
+        let text = self.text;
+        let start = self.position;
+        // Allocated only if the identifier contains escape sequences
+        let mut identifier: Option<String> = None;
+        loop {
+            let character_position = self.position;
+            let character = match self.peek() {
+                Some('\\') => {
+                    let _ = self.next_character();
+                    if self.next_character() != Some('u') {
+                        return Err(self.error_at(character_position, "invalid escape sequence in identifier".to_string()));
+                    }
+                    let character = self.parse_unicode_escape(character_position)?;
+                    identifier.get_or_insert_with(|| text[start..character_position].to_string()).push(character);
+                    character
+                }
+                Some(character) if is_identifier_part(character) => {
+                    let _ = self.next_character();
+                    if let Some(identifier) = &mut identifier {
+                        identifier.push(character);
+                    }
+                    character
+                }
+                _ => break,
+            };
+            if character_position == start && !is_identifier_start(character) {
+                return Err(self.error_at(character_position, format!("unexpected character `{}`", character)));
+            }
+        }
+        if self.position == start {
+            Err(self.unexpected_character())
+        } else {
+            Ok(identifier.map_or_else(|| Cow::Borrowed(&text[start..self.position]), Cow::Owned))
+        }
+    }
+
+    fn parse_hex_digits(&mut self, count: usize, escape_position: usize) -> Result<u32, Error> {
+        let mut value = 0;
+        for _ in 0..count {
+            match self.peek().and_then(|character| character.to_digit(16)) {
+                Some(digit) => {
+                    let _ = self.next_character();
+                    value = value * 16 + digit;
+                }
+                None => return Err(self.error_at(escape_position, "invalid hexadecimal escape sequence".to_string())),
+            }
+        }
+        Ok(value)
+    }
+
+    // Parse the 4 hexadecimal digits following `\u` (including a trailing low surrogate escape, if needed)
+    fn parse_unicode_escape(&mut self, escape_position: usize) -> Result<char, Error> {
+        let code_unit = self.parse_hex_digits(4, escape_position)?;
+        let code_point = if (0xd800..0xdc00).contains(&code_unit) && self.text[self.position..].starts_with("\\u") {
+            let low_surrogate_position = self.position;
+            self.position += 2;
+            let low_surrogate = self.parse_hex_digits(4, low_surrogate_position)?;
+            if (0xdc00..0xe000).contains(&low_surrogate) {
+                0x10000 + ((code_unit - 0xd800) << 10) + (low_surrogate - 0xdc00)
+            } else {
+                return Err(self.error_at(escape_position, "invalid unicode surrogate pair".to_string()));
+            }
+        } else {
+            code_unit
+        };
+        std::char::from_u32(code_point).ok_or_else(|| self.error_at(escape_position, "invalid unicode escape sequence".to_string()))
+    }
+
+    fn parse_string(&mut self) -> Result<Cow<'a, str>, Error> {
+        let text = self.text;
+        let start = self.position;
+        let quote = self.next_character();
+        let content_start = self.position;
+        // Allocated only if the string contains escape sequences
+        let mut owned_string: Option<String> = None;
+        loop {
+            let character_position = self.position;
+            match self.next_character() {
+                None => return Err(self.error_at(start, "unterminated string".to_string())),
+                Some(character) if Some(character) == quote => {
+                    return Ok(owned_string.map_or_else(|| Cow::Borrowed(&text[content_start..character_position]), Cow::Owned));
+                }
+                Some('\n') | Some('\r') => return Err(self.error_at(character_position, "unescaped line terminator in string".to_string())),
+                Some('\\') => {
+                    let string = owned_string.get_or_insert_with(|| text[content_start..character_position].to_string());
+                    match self.next_character() {
+                        None => return Err(self.error_at(start, "unterminated string".to_string())),
+                        Some('b') => string.push('\u{8}'),
+                        Some('f') => string.push('\u{c}'),
+                        Some('n') => string.push('\n'),
+                        Some('r') => string.push('\r'),
+                        Some('t') => string.push('\t'),
+                        Some('v') => string.push('\u{b}'),
+                        Some('0') if !self.peek().map_or(false, |character| character.is_ascii_digit()) => string.push('\0'),
+                        Some('x') => {
+                            let code_point = self.parse_hex_digits(2, character_position)?;
+                            string.push(std::char::from_u32(code_point).unwrap_or_default());
+                        }
+                        Some('u') => string.push(self.parse_unicode_escape(character_position)?),
+                        // Line continuations
+                        Some('\r') => {
+                            if self.peek() == Some('\n') {
+                                let _ = self.next_character();
+                            }
+                        }
+                        Some('\n') | Some('\u{2028}') | Some('\u{2029}') => {}
+                        Some('0'..='9') => return Err(self.error_at(character_position, "invalid escape sequence".to_string())),
+                        Some(character) => string.push(character),
+                    }
+                }
+                Some(character) => {
+                    if let Some(string) = &mut owned_string {
+                        string.push(character);
+                    }
+                }
+            }
+        }
+    }
+
+    fn parse_number(&mut self) -> Result<RustTypeRef<'a>, Error> {
+        let start = self.position;
+        let negative = match self.peek() {
+            Some('-') => {
+                let _ = self.next_character();
+                true
+            }
+            Some('+') => {
+                let _ = self.next_character();
+                false
+            }
+            _ => false,
+        };
+
+        let value = if self.consume_keyword("Infinity") {
+            RustTypeRef::from(if negative { f64::NEG_INFINITY } else { f64::INFINITY })
+        } else if self.consume_keyword("NaN") {
+            RustTypeRef::from(f64::NAN)
+        } else if self.peek() == Some('0') && matches!(self.peek_second(), Some('x') | Some('X')) {
+            self.position += 2;
+            let digits = self.consume_while(|character| character.is_ascii_hexdigit());
+            if digits.is_empty() {
+                return Err(self.error("expected hexadecimal digit".to_string()));
+            }
+            integer(negative, digits, 16)
+        } else {
+            let integer_digits = self.consume_while(|character| character.is_ascii_digit());
+            if integer_digits.len() > 1 && integer_digits.starts_with('0') {
+                return Err(self.error_at(start, "leading zeros are not allowed".to_string()));
+            }
+            let mut is_integer = true;
+            let mut has_digits = !integer_digits.is_empty();
+            if self.peek() == Some('.') {
+                let _ = self.next_character();
+                is_integer = false;
+                has_digits |= !self.consume_while(|character| character.is_ascii_digit()).is_empty();
+            }
+            if !has_digits {
+                return Err(self.error_at(start, "invalid number".to_string()));
+            }
+            if let Some('e') | Some('E') = self.peek() {
+                let _ = self.next_character();
+                is_integer = false;
+                if let Some('+') | Some('-') = self.peek() {
+                    let _ = self.next_character();
+                }
+                if self.consume_while(|character| character.is_ascii_digit()).is_empty() {
+                    return Err(self.error("expected exponent digits".to_string()));
+                }
+            }
+
+            if is_integer {
+                integer(negative, integer_digits, 10)
+            } else {
+                let number: f64 = self.text[start..self.position]
+                    .trim_start_matches('+')
+                    .parse()
+                    .map_err(|_| self.error_at(start, "invalid number".to_string()))?;
+                RustTypeRef::from(number)
+            }
+        };
+
+        if self.peek().map_or(false, is_identifier_part) {
+            Err(self.unexpected_character())
+        } else {
+            Ok(value)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{from_str, from_str_borrowed};
+    use crate::{error::Error, rust_type_impl::RustType, rust_type_ref_impl::RustTypeRef};
+    use std::borrow::Cow;
+    use test_case::test_case;
+
+    #[test_case("null", rust_type!(null))]
+    #[test_case("true", rust_type!(true))]
+    #[test_case("[1, 2.5, 'a', \"b\"]", rust_type!([1, 2.5, "a", "b"]) ; "array")]
+    #[test_case("{a: 1, $b_2: 2, 'c': 3, \"d\": 4}", rust_type!({"a": 1, "$b_2": 2, "c": 3, "d": 4}) ; "object keys")]
+    #[test_case(r"{\u0061b: 1, c\u0064: 2}", rust_type!({"ab": 1, "cd": 2}) ; "escaped object keys")]
+    #[test_case("[1, 2,]", rust_type!([1, 2]) ; "array trailing comma")]
+    #[test_case("{a: 1,}", rust_type!({"a": 1}) ; "object trailing comma")]
+    #[test_case("// comment\n{\n  /* block\n comment */ a: 1, // trailing\n}", rust_type!({"a": 1}) ; "comments")]
+    #[test_case("0x1F", rust_type!(31) ; "hexadecimal")]
+    #[test_case("-0XfF", rust_type!(-255) ; "negative hexadecimal")]
+    #[test_case("+1", rust_type!(1) ; "explicit positive sign")]
+    #[test_case(".5", rust_type!(0.5) ; "leading decimal point")]
+    #[test_case("5.", rust_type!(5.0) ; "trailing decimal point")]
+    #[test_case("1e3", rust_type!(1000.0) ; "exponent")]
+    #[test_case("Infinity", RustType::from(f64::INFINITY) ; "infinity")]
+    #[test_case("-Infinity", RustType::from(f64::NEG_INFINITY) ; "negative infinity")]
+    #[test_case(r"'\'\n\x41è😀\0'", rust_type!("'\nAè😀\0") ; "escapes")]
+    #[test_case("'line \\\ncontinuation'", rust_type!("line continuation") ; "line continuation")]
+    #[test_case("'\"quoted\"'", rust_type!("\"quoted\"") ; "double quotes in single quoted string")]
+    #[test_case("\u{feff} \u{a0}1\u{2028}", rust_type!(1) ; "unicode whitespaces")]
+    fn test_from_str(text: &str, expected_value: RustType) {
+        assert_eq!(from_str(text), Ok(expected_value));
+    }
+
+    #[test]
+    fn test_from_str_borrowed() {
+        let text = r"{key: 'value', 'escaped\tkey': ['escaped\tvalue', 1]}";
+        match from_str_borrowed(text) {
+            Ok(RustTypeRef::Object(object)) => {
+                let mut entries = object.iter().collect::<Vec<_>>();
+                entries.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+                match entries.as_slice() {
+                    [(Cow::Owned(escaped_key), RustTypeRef::List(list)), (Cow::Borrowed(key), RustTypeRef::String(Cow::Borrowed(value)))] => {
+                        assert_eq!(escaped_key, "escaped\tkey");
+                        assert_eq!(list, &vec![RustTypeRef::String(Cow::Owned("escaped\tvalue".to_string())), RustTypeRef::from(1)]);
+                        assert_eq!((*key, *value), ("key", "value"));
+                        // Borrowed strings reference the input buffer
+                        assert!(text.as_bytes().as_ptr_range().contains(&value.as_ptr()));
+                    }
+                    _ => panic!("Unexpected entries {:?}", entries),
+                }
+            }
+            value => panic!("Unexpected value {:?}", value),
+        }
+    }
+
+    #[test]
+    fn test_nan() {
+        match from_str("NaN") {
+            Ok(RustType::Number(value)) => assert!(value.is_nan()),
+            value => panic!("Unexpected value {:?}", value),
+        }
+    }
+
+    #[cfg(not(feature = "big_integer"))]
+    #[test]
+    fn test_integer_above_i128_max_is_a_number() {
+        assert_eq!(from_str("0x100000000000000000000000000000000"), Ok(RustType::from(2_f64.powi(128))));
+    }
+
+    #[cfg(feature = "big_integer")]
+    #[test]
+    fn test_integer_above_i128_max_is_a_big_integer() {
+        assert_eq!(
+            from_str("-340282366920938463463374607431768211456"),
+            Ok(RustType::from(num_bigint::BigInt::from(2).pow(128_u32) * num_bigint::BigInt::from(-1)))
+        );
+    }
+
+    #[test_case("", 1, 1, "unexpected end of input" ; "empty document")]
+    #[test_case("{a: 1}}", 1, 7, "unexpected character `}` after the document" ; "trailing characters")]
+    #[test_case("{\n  a: 1\n  b: 2\n}", 3, 3, "expected `,` or `}`" ; "missing comma")]
+    #[test_case("[1 2]", 1, 4, "expected `,` or `]`" ; "missing array comma")]
+    #[test_case("[1,,]", 1, 4, "unexpected character `,`" ; "double comma")]
+    #[test_case("{1: 2}", 1, 2, "unexpected character `1`" ; "invalid key")]
+    #[test_case("{a 1}", 1, 4, "expected `:`" ; "missing colon")]
+    #[test_case("'abc", 1, 1, "unterminated string" ; "unterminated string")]
+    #[test_case("'a\nb'", 1, 3, "unescaped line terminator in string" ; "line terminator in string")]
+    #[test_case(r"'\1'", 1, 2, "invalid escape sequence" ; "invalid escape")]
+    #[test_case(r"'\u12'", 1, 2, "invalid hexadecimal escape sequence" ; "short unicode escape")]
+    #[test_case(r"'\ud83d\u0041'", 1, 2, "invalid unicode surrogate pair" ; "invalid surrogate pair")]
+    #[test_case(r"'\ud83d'", 1, 2, "invalid unicode escape sequence" ; "lone surrogate")]
+    #[test_case("/* unterminated", 1, 1, "unterminated block comment" ; "unterminated comment")]
+    #[test_case("\r\n[01]", 2, 2, "leading zeros are not allowed" ; "leading zeros")]
+    #[test_case("0x", 1, 3, "expected hexadecimal digit" ; "empty hexadecimal")]
+    #[test_case("1e", 1, 3, "expected exponent digits" ; "empty exponent")]
+    #[test_case("-", 1, 1, "invalid number" ; "sign only")]
+    #[test_case("nul", 1, 1, "unexpected character `n`" ; "invalid literal")]
+    #[test_case("[12a]", 1, 4, "unexpected character `a`" ; "invalid number suffix")]
+    #[test_case("[\"è\", x]", 1, 7, "unexpected character `x`" ; "column counts characters")]
+    fn test_from_str_errors(text: &str, line: usize, column: usize, message: &str) {
+        assert_eq!(
+            from_str(text),
+            Err(Error::InvalidSyntax {
+                line,
+                column,
+                message: message.to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_maximum_depth() {
+        let text = format!("{}{}", "[".repeat(200), "]".repeat(200));
+        assert_eq!(
+            from_str(&text),
+            Err(Error::InvalidSyntax {
+                line: 1,
+                column: 129,
+                message: "maximum nesting depth (128) exceeded".to_string()
+            })
+        );
+    }
+}
@@ -0,0 +1,100 @@
+use crate::error::Error;
+#[cfg(feature = "big_integer")]
+use num_bigint::BigInt;
+use std::marker::PhantomData;
+
+// Construction of backend values, the counterpart of `JsonType`.
+// It allows generic code to produce its output in the backend chosen by the caller (see `convert::from_json_type`).
+//
+// Values that are not representable by the backend (ie. integers out of the supported range) are reported
+// via `Error::NumericOverflow` with an empty path, the path is filled by the caller while traversing the input.
+// Backends that are not able to represent all the JSON values (ie. TOML, as it has no null) do not implement it.
+#[allow(clippy::module_name_repetitions)]
+pub trait JsonBuilder: Sized {
+    fn null() -> Self;
+    fn bool(value: bool) -> Self;
+    /// # Errors
+    ///
+    /// Will return `Err` if `value` is not representable by the backend
+    fn integer(value: i128) -> Result<Self, Error>;
+    /// # Errors
+    ///
+    /// Will return `Err` if `value` is not representable by the backend
+    #[cfg(feature = "big_integer")]
+    fn big_integer(value: &BigInt) -> Result<Self, Error>;
+    fn number(value: f64) -> Self;
+    // Number with its exact textual representation (see `JsonType::as_number_str`).
+    // Backends not preserving the textual representation of numbers fall back to `value`
+    fn decimal(value: f64, _number_str: &str) -> Self {
+        Self::number(value)
+    }
+    fn string(value: &str) -> Self;
+    fn array<I: IntoIterator<Item = Self>>(items: I) -> Self;
+    fn object<'key, I: IntoIterator<Item = (&'key str, Self)>>(items: I) -> Self;
+}
+
+// `JsonBuilder` for backends whose values are built within a context (ie. `arena::ArenaValue`, allocated into an arena).
+// `PhantomData<B>` is the context of any `JsonBuilder` `B`, so `convert::from_json_type` is shared by both.
+pub(crate) trait JsonBuilderContext {
+    type Value;
+    fn null(&self) -> Self::Value;
+    fn bool(&self, value: bool) -> Self::Value;
+    fn integer(&self, value: i128) -> Result<Self::Value, Error>;
+    #[cfg(feature = "big_integer")]
+    fn big_integer(&self, value: &BigInt) -> Result<Self::Value, Error>;
+    fn number(&self, value: f64) -> Self::Value;
+    fn decimal(&self, value: f64, number_str: &str) -> Self::Value;
+    fn string(&self, value: &str) -> Self::Value;
+    fn array<I: IntoIterator<Item = Self::Value>>(&self, items: I) -> Self::Value;
+    fn object<'key, I: IntoIterator<Item = (&'key str, Self::Value)>>(&self, items: I) -> Self::Value;
+}
+
+impl<B: JsonBuilder> JsonBuilderContext for PhantomData<B> {
+    type Value = B;
+
+    fn null(&self) -> B {
+        B::null()
+    }
+
+    fn bool(&self, value: bool) -> B {
+        B::bool(value)
+    }
+
+    fn integer(&self, value: i128) -> Result<B, Error> {
+        B::integer(value)
+    }
+
+    #[cfg(feature = "big_integer")]
+    fn big_integer(&self, value: &BigInt) -> Result<B, Error> {
+        B::big_integer(value)
+    }
+
+    fn number(&self, value: f64) -> B {
+        B::number(value)
+    }
+
+    fn decimal(&self, value: f64, number_str: &str) -> B {
+        B::decimal(value, number_str)
+    }
+
+    fn string(&self, value: &str) -> B {
+        B::string(value)
+    }
+
+    fn array<I: IntoIterator<Item = B>>(&self, items: I) -> B {
+        B::array(items)
+    }
+
+    fn object<'key, I: IntoIterator<Item = (&'key str, B)>>(&self, items: I) -> B {
+        B::object(items)
+    }
+}
+
+// Helper for `JsonBuilder` implementations reporting values that do not fit into `target`
+pub(crate) fn numeric_overflow<V: ToString>(value: &V, target: &'static str) -> Error {
+    Error::NumericOverflow {
+        path: String::new(),
+        value: value.to_string(),
+        target,
+    }
+}
@@ -1,5 +1,12 @@
-use crate::{error::Error, fragment_helpers::fragment_components_from_fragment, rust_type_impl::RustType};
-use std::{collections::HashMap, convert::TryFrom, fmt::Debug, ops::Deref};
+use crate::{
+    error::Error,
+    fragment_helpers::{fragment_components_from_fragment, fragment_from_fragment_components, try_fragment_components_from_fragment},
+    json_builder::JsonBuilder,
+    rust_type_impl::RustType,
+};
+#[cfg(feature = "big_integer")]
+use num_bigint::BigInt;
+use std::{borrow::Cow, collections::HashMap, convert::TryFrom, fmt::Debug, ops::Deref};
 
 #[allow(clippy::module_name_repetitions)]
 #[derive(Clone, Copy, EnumIter, EnumVariantNames, Eq, Hash, Debug, Display, PartialEq)]
@@ -56,11 +63,82 @@ pub trait JsonMapTrait<'json, T: 'json + JsonType> {
     fn items(&'json self) -> Box<dyn Iterator<Item = (&str, &T)> + 'json>;
 }
 
+// Fallible counterpart of `JsonMapTrait`.
+// Backends that could fail while iterating over an object (ie. Python dictionaries with non string keys)
+// report the failure instead of silently skipping the problematic entries
+#[allow(clippy::module_name_repetitions)]
+pub trait TryJsonMapTrait<'json, T: 'json + JsonType> {
+    /// # Errors
+    /// Returns an error if the backend fails to iterate over the object keys
+    fn try_keys(&'json self) -> Result<Box<dyn Iterator<Item = &str> + 'json>, Error>;
+
+    /// # Errors
+    /// Returns an error if the backend fails to iterate over the object values
+    fn try_values(&'json self) -> Result<Box<dyn Iterator<Item = &T> + 'json>, Error>;
+
+    /// # Errors
+    /// Returns an error if the backend fails to iterate over the object items
+    fn try_items(&'json self) -> Result<Box<dyn Iterator<Item = (&str, &T)> + 'json>, Error>;
+}
+
+// Sign, significant digits (without leading and trailing zeros) and exponent of a decimal number (`None` if `number_str`
+// is not a decimal number)
+pub(crate) fn normalized_decimal(number_str: &str) -> Option<(bool, String, i64)> {
+    let (negative, number_str) = number_str.strip_prefix('-').map_or((false, number_str), |number_str| (true, number_str));
+    let (mantissa, exponent) = number_str.split_once(|c| c == 'e' || c == 'E').unwrap_or((number_str, "0"));
+    let (integer_part, fractional_part) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    if integer_part.is_empty() && fractional_part.is_empty() || !integer_part.chars().chain(fractional_part.chars()).all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let mut exponent = exponent.strip_prefix('+').unwrap_or(exponent).parse::<i64>().ok()?;
+    exponent = exponent.checked_sub(i64::try_from(fractional_part.len()).ok()?)?;
+
+    let digits = format!("{}{}", integer_part, fractional_part);
+    let digits = digits.trim_start_matches('0');
+    let significant_digits = digits.trim_end_matches('0');
+    exponent = exponent.checked_add(i64::try_from(digits.len() - significant_digits.len()).ok()?)?;
+    if significant_digits.is_empty() {
+        // All the zeros are equal, except for their sign
+        exponent = 0;
+    }
+    Some((negative, significant_digits.to_string(), exponent))
+}
+
+// Whether `number_str` follows the JSON number grammar (ie. no leading zeros, `+` sign, nor missing digits)
+pub(crate) fn is_json_number_str(number_str: &str) -> bool {
+    let is_digits = |digits: &str| !digits.is_empty() && digits.bytes().all(|byte| byte.is_ascii_digit());
+    let number_str = number_str.strip_prefix('-').unwrap_or(number_str);
+    let (mantissa, exponent) = number_str
+        .split_once(|c| c == 'e' || c == 'E')
+        .map_or((number_str, None), |(mantissa, exponent)| (mantissa, Some(exponent)));
+    let (integer_part, fractional_part) = mantissa
+        .split_once('.')
+        .map_or((mantissa, None), |(integer_part, fractional_part)| (integer_part, Some(fractional_part)));
+    is_digits(integer_part)
+        && (integer_part == "0" || !integer_part.starts_with('0'))
+        && fractional_part.map_or(true, is_digits)
+        && exponent.map_or(true, |exponent| is_digits(exponent.strip_prefix(['+', '-']).unwrap_or(exponent)))
+}
+
+// Whether `number_str` represents exactly `number`, which is the case if it has the same value as the shortest
+// representation of `number` (ie. `1.10`, `1e2` and `-0.0` are exact while `0.1000000000000000055` and `1e400` are not).
+// Strings that are not decimal numbers are considered exact, as there is no representation to preserve.
+pub(crate) fn is_exact_number_str(number: f64, number_str: &str) -> bool {
+    normalized_decimal(number_str).map_or(true, |decimal| normalized_decimal(&format!("{:e}", number)) == Some(decimal))
+}
+
 pub trait ToRustType {
     fn to_rust_type(&self) -> RustType
     where
         Self: Sized + JsonType,
     {
+        #[cfg(feature = "big_integer")]
+        {
+            if let Some(big_integer) = self.as_big_integer() {
+                return RustType::from(big_integer);
+            }
+        }
+
         #[allow(clippy::option_if_let_else)]
         if let Some(array) = self.as_array() {
             RustType::from(array.map(|item| item.to_rust_type()).collect::<Vec<_>>())
@@ -71,16 +149,17 @@ pub trait ToRustType {
         } else if self.is_null() {
             RustType::from(())
         } else if let Some(number) = self.as_number() {
-            RustType::from(number)
+            match self.as_number_str() {
+                // The textual representation is preserved only if it cannot be losslessly represented by a f64
+                Some(number_str) => RustType::decimal(number, &number_str),
+                None => RustType::from(number),
+            }
         } else if let Some(object) = self.as_object() {
             RustType::from(object.items().map(|(k, v)| (k.into(), v.to_rust_type())).collect::<HashMap<_, _>>())
-        } else if let Some(string) = self.as_string() {
-            RustType::from(string)
+        } else if let Some(string) = self.as_string_cow() {
+            RustType::from(string.as_ref())
         } else {
-            #[allow(unsafe_code)]
-            unsafe {
-                std::hint::unreachable_unchecked()
-            }
+            panic!("{} value has no JSON primitive type", std::any::type_name::<Self>())
         }
     }
 }
@@ -99,12 +178,27 @@ pub trait JsonType: JsonTypeToString + ToRustType {
         Self: Sized;
     fn as_boolean(&self) -> Option<bool>;
     fn as_integer(&self) -> Option<i128>;
+    // Integers that do not fit into a i128 are reported only by `JsonType::as_big_integer`
+    #[cfg(feature = "big_integer")]
+    fn as_big_integer(&self) -> Option<BigInt> {
+        self.as_integer().map(BigInt::from)
+    }
     fn as_null(&self) -> Option<()>;
     fn as_number(&self) -> Option<f64>;
+    // Exact textual representation of the number, available only if the backend preserves it
+    // (ie. `serde_json` with `arbitrary_precision`, `json::JsonValue` or Python's `decimal.Decimal`)
+    fn as_number_str(&self) -> Option<Cow<str>> {
+        None
+    }
     fn as_object(&self) -> Option<JsonMap<Self>>
     where
         Self: Sized;
     fn as_string(&self) -> Option<&str>;
+    // Strings that are not stored by the backend (ie. TOML datetimes) have no `&str` to borrow, so they are exposed
+    // (formatted on each call) only via this accessor while `JsonType::as_string` returns `None` for them
+    fn as_string_cow(&self) -> Option<Cow<str>> {
+        self.as_string().map(Cow::Borrowed)
+    }
 
     fn get_attribute(&self, attribute_name: &str) -> Option<&Self>
     where
@@ -125,7 +219,14 @@ pub trait JsonType: JsonTypeToString + ToRustType {
     }
 
     fn is_integer(&self) -> bool {
-        self.as_integer().is_some()
+        #[cfg(feature = "big_integer")]
+        {
+            self.as_big_integer().is_some()
+        }
+        #[cfg(not(feature = "big_integer"))]
+        {
+            self.as_integer().is_some()
+        }
     }
 
     fn is_null(&self) -> bool {
@@ -144,7 +245,7 @@ pub trait JsonType: JsonTypeToString + ToRustType {
     }
 
     fn is_string(&self) -> bool {
-        self.as_string().is_some()
+        self.as_string_cow().is_some()
     }
 
     fn has_attribute(&self, attribute_name: &str) -> bool
@@ -174,10 +275,55 @@ pub trait JsonType: JsonTypeToString + ToRustType {
         } else if self.is_string() {
             PrimitiveType::String
         } else {
-            #[allow(unsafe_code)]
-            unsafe {
-                std::hint::unreachable_unchecked()
-            }
+            panic!("{} value has no JSON primitive type", std::any::type_name::<Self>())
+        }
+    }
+}
+
+// Fallible counterpart of `JsonType`.
+// All the `JsonType` types implement it and the default implementation never fails, but backends
+// that could fail while accessing the data (ie. pyo3) report the failure instead of swallowing it
+#[allow(clippy::module_name_repetitions)]
+pub trait TryJsonType: JsonType {
+    /// # Errors
+    /// Returns an error if the backend fails to iterate over the array items
+    fn try_as_array<'json>(&'json self) -> Result<Option<Box<dyn ExactSizeIterator<Item = &Self> + 'json>>, Error>
+    where
+        Self: Sized;
+
+    /// # Errors
+    /// Returns an error if the backend fails to extract the attribute
+    fn try_get_attribute(&self, attribute_name: &str) -> Result<Option<&Self>, Error>
+    where
+        Self: Sized;
+
+    /// # Errors
+    /// Returns an error if the backend fails to extract the index
+    fn try_get_index(&self, index: usize) -> Result<Option<&Self>, Error>
+    where
+        Self: Sized;
+
+    /// # Errors
+    /// Returns the first error reported by the backend while traversing the object.
+    /// The error path is relative to `self`.
+    fn try_to_rust_type(&self) -> Result<RustType, Error>
+    where
+        Self: Sized,
+    {
+        if let Some(array) = self.try_as_array()? {
+            array
+                .enumerate()
+                .map(|(index, item)| item.try_to_rust_type().map_err(|error| error.prefix_path(&index.to_string())))
+                .collect::<Result<Vec<_>, _>>()
+                .map(RustType::from)
+        } else if let Some(object) = self.as_object() {
+            object
+                .try_items()?
+                .map(|(key, value)| value.try_to_rust_type().map(|value| (key.to_string(), value)).map_err(|error| error.prefix_path(key)))
+                .collect::<Result<HashMap<_, _>, _>>()
+                .map(RustType::from)
+        } else {
+            Ok(self.to_rust_type())
         }
     }
 }
@@ -220,12 +366,49 @@ impl<'json, T: JsonType> JsonMapTrait<'json, T> for JsonMap<'json, T> {
     }
 }
 
+impl<'json, T: JsonType> TryJsonMapTrait<'json, T> for JsonMap<'json, T> {
+    default fn try_keys(&'json self) -> Result<Box<dyn Iterator<Item = &str> + 'json>, Error> {
+        Ok(self.keys())
+    }
+
+    default fn try_values(&'json self) -> Result<Box<dyn Iterator<Item = &T> + 'json>, Error> {
+        Ok(self.values())
+    }
+
+    default fn try_items(&'json self) -> Result<Box<dyn Iterator<Item = (&str, &T)> + 'json>, Error> {
+        Ok(self.items())
+    }
+}
+
+impl<T: JsonType> TryJsonType for T {
+    default fn try_as_array<'json>(&'json self) -> Result<Option<Box<dyn ExactSizeIterator<Item = &Self> + 'json>>, Error> {
+        Ok(self.as_array())
+    }
+
+    default fn try_get_attribute(&self, attribute_name: &str) -> Result<Option<&Self>, Error> {
+        Ok(self.get_attribute(attribute_name))
+    }
+
+    default fn try_get_index(&self, index: usize) -> Result<Option<&Self>, Error> {
+        Ok(self.get_index(index))
+    }
+}
+
 impl<T: JsonType> JsonTypeToString for T {
     default fn to_json_string(&self) -> String {
         self.to_rust_type().to_json_string()
     }
 }
 
+// Index referenced by an array reference token of a JSON pointer, which is `0` or digits without leading zeros (RFC 6901)
+fn array_index(fragment_part: &str) -> Option<usize> {
+    if fragment_part == "0" || !fragment_part.starts_with('0') && fragment_part.bytes().all(|byte| byte.is_ascii_digit()) {
+        fragment_part.parse().ok()
+    } else {
+        None
+    }
+}
+
 #[allow(clippy::module_name_repetitions)]
 pub fn get_fragment<'json, T: JsonType>(json_object: &'json T, fragment: &str) -> Option<&'json T> {
     let mut result = Some(json_object);
@@ -233,7 +416,7 @@ pub fn get_fragment<'json, T: JsonType>(json_object: &'json T, fragment: &str) -
         if let Some(value) = result {
             result = match value.primitive_type() {
                 PrimitiveType::Object => value.get_attribute(fragment_part.as_str()),
-                PrimitiveType::Array => fragment_part.parse::<usize>().map(|index| value.get_index(index)).ok().unwrap_or(None),
+                PrimitiveType::Array => array_index(&fragment_part).and_then(|index| value.get_index(index)),
                 _ => None,
             };
         }
@@ -241,9 +424,53 @@ pub fn get_fragment<'json, T: JsonType>(json_object: &'json T, fragment: &str) -
     result
 }
 
+/// Fallible version of `get_fragment` that reports why the fragment could not be resolved.
+///
+/// # Errors
+/// * `Error::InvalidPointer` if the fragment is not a valid JSON pointer
+/// * `Error::MissingKey` if an attribute, or an index, referenced by the fragment does not exist
+/// * `Error::TypeMismatch` if the fragment traverses a value that is neither an object nor an array
+#[allow(clippy::module_name_repetitions)]
+pub fn try_get_fragment<'json, T: JsonType>(json_object: &'json T, fragment: &str) -> Result<&'json T, Error> {
+    let fragment_components = try_fragment_components_from_fragment(fragment)?;
+    let mut result = json_object;
+    for (depth, fragment_part) in fragment_components.iter().enumerate() {
+        let path = || fragment_from_fragment_components(&fragment_components[..depth]);
+        // Backend errors carry the path relative to the value being traversed
+        let prefix_path = |error: Error| fragment_components[..depth].iter().rev().fold(error, |error, component| error.prefix_path(component));
+        result = match result.primitive_type() {
+            PrimitiveType::Object => result.try_get_attribute(fragment_part).map_err(prefix_path)?.ok_or_else(|| Error::MissingKey {
+                path: path(),
+                key: fragment_part.clone(),
+            })?,
+            PrimitiveType::Array => {
+                let index = array_index(fragment_part).ok_or_else(|| Error::InvalidPointer {
+                    pointer: fragment.to_string(),
+                    reason: format!("`{}` is not a valid array index", fragment_part),
+                })?;
+                result.try_get_index(index).map_err(prefix_path)?.ok_or_else(|| Error::MissingKey {
+                    path: path(),
+                    key: fragment_part.clone(),
+                })?
+            }
+            primitive_type => {
+                // Values that the backend fails to read are reported as null by `JsonType::primitive_type`, so the
+                // read failure is reported instead of the type mismatch
+                let _ = result.try_as_array().map_err(prefix_path)?;
+                return Err(Error::TypeMismatch {
+                    path: path(),
+                    expected: PrimitiveType::Object,
+                    found: primitive_type,
+                });
+            }
+        };
+    }
+    Ok(result)
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{get_fragment, Error, JsonType, PrimitiveType};
+    use super::{get_fragment, is_exact_number_str, is_json_number_str, try_get_fragment, Error, JsonMapTrait, JsonType, PrimitiveType, TryJsonMapTrait, TryJsonType};
     use crate::rust_type_impl::RustType;
     use std::convert::TryFrom;
     use test_case::test_case;
@@ -258,6 +485,34 @@ mod tests {
         check(&RustType::default())
     }
 
+    #[test_case(1.1, "1.1", true)]
+    #[test_case(1.1, "1.10", true ; "trailing zeros")]
+    #[test_case(100.0, "1e2", true ; "exponent")]
+    #[test_case(100.0, "1.00E+2", true ; "exponent with trailing zeros")]
+    #[test_case(0.012, "12e-3", true ; "negative exponent")]
+    #[test_case(-0.0, "-0.0", true ; "negative zero")]
+    #[test_case(0.0, "0e10", true ; "zero with exponent")]
+    #[test_case(0.1, "0.1000000000000000055", false ; "not representable digits")]
+    #[test_case(f64::INFINITY, "1e400", false ; "overflow")]
+    #[test_case(0.0, "1e-400", false ; "underflow")]
+    #[test_case(f64::NAN, "NaN", true ; "not a decimal number")]
+    fn test_is_exact_number_str(number: f64, number_str: &str, expected_value: bool) {
+        assert_eq!(is_exact_number_str(number, number_str), expected_value);
+    }
+
+    #[test_case("0", true)]
+    #[test_case("-1.5e+10", true)]
+    #[test_case("1.0E-2", true)]
+    #[test_case("01", false ; "leading zero")]
+    #[test_case("+1", false ; "plus sign")]
+    #[test_case(".5", false ; "missing integer part")]
+    #[test_case("1.", false ; "missing fractional part")]
+    #[test_case("1e", false ; "missing exponent")]
+    #[test_case("NaN", false ; "not a number")]
+    fn test_is_json_number_str(number_str: &str, expected_value: bool) {
+        assert_eq!(is_json_number_str(number_str), expected_value);
+    }
+
     #[test_case("array", &Ok(PrimitiveType::Array))]
     #[test_case("integer", &Ok(PrimitiveType::Integer))]
     #[test_case("number", &Ok(PrimitiveType::Number))]
@@ -292,9 +547,41 @@ mod tests {
     #[test_case("/key/inner_key/1", &Some(RustType::from("2")))]
     #[test_case("/not_present", &None)]
     #[test_case("/key/inner_key/a", &None)]
+    #[test_case("/key/inner_key/01", &None ; "index with leading zero")]
     #[test_case("/key/inner_key/2", &None)]
     fn test_get_fragment(fragment: &str, expected_value: &Option<RustType>) {
         let external_map = rust_type!({"key": {"inner_key": [1, "2"]}});
         assert_eq!(get_fragment(&external_map, fragment), expected_value.as_ref());
     }
+
+    #[test_case("", &Ok(rust_type!({"key": {"inner_key": [1, "2"]}})))]
+    #[test_case("/key/inner_key/1", &Ok(RustType::from("2")))]
+    #[test_case("/not_present", &Err(Error::MissingKey { path: "".to_string(), key: "not_present".to_string() }))]
+    #[test_case("/key/inner_key/2", &Err(Error::MissingKey { path: "/key/inner_key".to_string(), key: "2".to_string() }))]
+    #[test_case("/key/inner_key/0/a", &Err(Error::TypeMismatch { path: "/key/inner_key/0".to_string(), expected: PrimitiveType::Object, found: PrimitiveType::Integer }))]
+    #[test_case("/key/inner_key/a", &Err(Error::InvalidPointer { pointer: "/key/inner_key/a".to_string(), reason: "`a` is not a valid array index".to_string() }))]
+    #[test_case("/key/inner_key/+1", &Err(Error::InvalidPointer { pointer: "/key/inner_key/+1".to_string(), reason: "`+1` is not a valid array index".to_string() }) ; "index with sign")]
+    #[test_case("/key/inner_key/01", &Err(Error::InvalidPointer { pointer: "/key/inner_key/01".to_string(), reason: "`01` is not a valid array index".to_string() }) ; "index with leading zero")]
+    #[test_case("key", &Err(Error::InvalidPointer { pointer: "key".to_string(), reason: "a non empty pointer has to start with `/`".to_string() }))]
+    fn test_try_get_fragment(fragment: &str, expected_value: &Result<RustType, Error>) {
+        let external_map = rust_type!({"key": {"inner_key": [1, "2"]}});
+        assert_eq!(&try_get_fragment(&external_map, fragment).map(Clone::clone), expected_value);
+    }
+
+    #[test]
+    fn test_try_json_type_default_implementation_never_fails() {
+        let value = rust_type!({"key": [1, "2", null]});
+        assert_eq!(value.try_to_rust_type(), Ok(value.clone()));
+        assert_eq!(value.try_get_attribute("key"), Ok(value.get_attribute("key")));
+        assert_eq!(value.try_get_attribute("not_present"), Ok(None));
+        assert_eq!(value.try_as_array().map(|array| array.is_some()), Ok(false));
+
+        let inner_array = value.get_attribute("key").unwrap();
+        assert_eq!(inner_array.try_get_index(1), Ok(Some(&RustType::from("2"))));
+        assert_eq!(inner_array.try_as_array().map(|array| array.map(Iterator::count)), Ok(Some(3)));
+        assert_eq!(
+            value.as_object().unwrap().try_items().map(Iterator::collect::<Vec<_>>),
+            Ok(value.as_object().unwrap().items().collect::<Vec<_>>())
+        );
+    }
 }
@@ -0,0 +1,836 @@
+// Lazy, on-demand JSON documents.
+//
+// `LazyDocument::parse` validates the input and indexes it into a flat structural tape (one entry per value, each
+// entry knowing the byte range of the value and where its subtree ends), without building any tree.
+// `LazyValue`s are created on demand: accessing an array or an object materializes only its direct children (skipping
+// over nested values via the tape), while strings with escape sequences are decoded only once they are read.
+//
+// Integers not fitting into i128 are reported via `JsonType::as_big_integer` if `big_integer` feature is enabled,
+// as numbers otherwise. The original text of numbers not exactly representable by a f64 is available via
+// `JsonType::as_number_str`.
+use crate::{
+    error::Error,
+    json_builder::JsonBuilder,
+    json_type::{is_exact_number_str, JsonMap, JsonMapTrait, JsonType, ThreadSafeJsonType, ToRustType},
+    rust_type_impl::RustType,
+};
+#[cfg(feature = "big_integer")]
+use num_bigint::BigInt;
+use std::{borrow::Cow, collections::HashMap, sync::OnceLock};
+
+// Maximum nesting of arrays and objects, this prevents stack overflows on malicious inputs
+const MAX_DEPTH: usize = 128;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Kind {
+    Null,
+    Boolean(bool),
+    Integer,
+    Number,
+    String { escaped: bool },
+    Array { len: usize },
+    Object { len: usize },
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Entry {
+    kind: Kind,
+    // Byte range of the value (strings exclude the quotes)
+    start: usize,
+    end: usize,
+    // Index of the first entry after the subtree of this entry
+    next: usize,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Tape<'doc> {
+    text: &'doc str,
+    entries: &'doc [Entry],
+}
+
+impl<'doc> Tape<'doc> {
+    fn raw(self, index: usize) -> &'doc str {
+        let entry = self.entries[index];
+        &self.text[entry.start..entry.end]
+    }
+
+    // Indexes of the direct children of the container at `index`. Object children alternate keys and values
+    fn children(self, index: usize) -> impl Iterator<Item = usize> + 'doc {
+        let entries = self.entries;
+        let end = entries[index].next;
+        std::iter::successors(Some(index + 1).filter(|first| *first < end), move |child| {
+            Some(entries[*child].next).filter(|next| *next < end)
+        })
+    }
+
+    fn string(self, index: usize) -> Cow<'doc, str> {
+        match self.entries[index].kind {
+            Kind::String { escaped: true } => Cow::Owned(unescape(self.raw(index))),
+            _ => Cow::Borrowed(self.raw(index)),
+        }
+    }
+
+    fn integer(self, index: usize) -> Option<i128> {
+        self.raw(index).parse().ok()
+    }
+
+    fn number(self, index: usize) -> f64 {
+        // The syntax has been validated while building the tape
+        self.raw(index).parse().unwrap_or_default()
+    }
+
+    // Text of the number at `index`, only if it is not exactly representable by a f64
+    fn number_str(self, index: usize) -> Option<&'doc str> {
+        Some(self.raw(index)).filter(|number_str| !is_exact_number_str(self.number(index), number_str))
+    }
+
+    fn to_rust_type(self, index: usize) -> RustType {
+        match self.entries[index].kind {
+            Kind::Null => RustType::Null,
+            Kind::Boolean(value) => RustType::from(value),
+            Kind::Integer => {
+                if let Some(value) = self.integer(index) {
+                    return RustType::from(value);
+                }
+                #[cfg(feature = "big_integer")]
+                {
+                    if let Ok(value) = self.raw(index).parse::<BigInt>() {
+                        return RustType::from(value);
+                    }
+                }
+                RustType::decimal(self.number(index), self.raw(index))
+            }
+            Kind::Number => RustType::decimal(self.number(index), self.raw(index)),
+            Kind::String { .. } => RustType::from(self.string(index).into_owned()),
+            Kind::Array { .. } => RustType::from(self.children(index).map(|child| self.to_rust_type(child)).collect::<Vec<_>>()),
+            Kind::Object { .. } => {
+                let mut children = self.children(index);
+                let mut object = HashMap::new();
+                while let (Some(key), Some(value)) = (children.next(), children.next()) {
+                    let _ = object.insert(self.string(key).into_owned(), self.to_rust_type(value));
+                }
+                RustType::from(object)
+            }
+        }
+    }
+}
+
+// Decode the escape sequences of an already validated JSON string
+fn unescape(raw: &str) -> String {
+    let mut string = String::with_capacity(raw.len());
+    let mut characters = raw.chars();
+    while let Some(character) = characters.next() {
+        if character != '\\' {
+            string.push(character);
+            continue;
+        }
+        match characters.next() {
+            Some('b') => string.push('\u{8}'),
+            Some('f') => string.push('\u{c}'),
+            Some('n') => string.push('\n'),
+            Some('r') => string.push('\r'),
+            Some('t') => string.push('\t'),
+            Some('u') => {
+                let mut code_point = hex_code_unit(&mut characters);
+                if (0xd800..0xdc00).contains(&code_point) {
+                    let _ = characters.nth(1); // `\u`
+                    code_point = 0x10000 + ((code_point - 0xd800) << 10) + (hex_code_unit(&mut characters) - 0xdc00);
+                }
+                string.push(std::char::from_u32(code_point).unwrap_or(std::char::REPLACEMENT_CHARACTER));
+            }
+            Some(character) => string.push(character),
+            None => {}
+        }
+    }
+    string
+}
+
+fn hex_code_unit(characters: &mut std::str::Chars) -> u32 {
+    characters.take(4).filter_map(|digit| digit.to_digit(16)).fold(0, |acc, digit| acc * 16 + digit)
+}
+
+// One-based line and column (in characters) of the byte offset `position`
+fn line_column(text: &str, position: usize) -> (usize, usize) {
+    let prefix = &text[..position];
+    let line_start = prefix.rfind('\n').map_or(0, |offset| offset + 1);
+    (prefix.matches('\n').count() + 1, prefix[line_start..].chars().count() + 1)
+}
+
+struct TapeBuilder<'a> {
+    text: &'a str,
+    bytes: &'a [u8],
+    // Byte offset of the next character to parse
+    position: usize,
+    entries: Vec<Entry>,
+}
+
+impl<'a> TapeBuilder<'a> {
+    fn error_at(&self, position: usize, message: String) -> Error {
+        let (line, column) = line_column(self.text, position);
+        Error::InvalidSyntax { line, column, message }
+    }
+
+    fn unexpected_character(&self) -> Error {
+        self.text[self.position..].chars().next().map_or_else(
+            || self.error_at(self.position, "unexpected end of input".to_string()),
+            |character| self.error_at(self.position, format!("unexpected character `{}`", character)),
+        )
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.position).copied()
+    }
+
+    fn skip_whitespaces(&mut self) {
+        while let Some(b' ') | Some(b'\t') | Some(b'\n') | Some(b'\r') = self.peek() {
+            self.position += 1;
+        }
+    }
+
+    fn skip_digits(&mut self) -> usize {
+        let start = self.position;
+        while self.peek().map_or(false, |byte| byte.is_ascii_digit()) {
+            self.position += 1;
+        }
+        self.position - start
+    }
+
+    fn push(&mut self, kind: Kind, start: usize, end: usize) -> usize {
+        self.entries.push(Entry {
+            kind,
+            start,
+            end,
+            next: self.entries.len() + 1,
+        });
+        self.entries.len() - 1
+    }
+
+    fn parse_value(&mut self, depth: usize) -> Result<(), Error> {
+        match self.peek() {
+            Some(b'{') | Some(b'[') => self.parse_container(depth),
+            Some(b'"') => self.parse_string(),
+            Some(b'-') | Some(b'0'..=b'9') => self.parse_number(),
+            _ => {
+                let start = self.position;
+                let rest = &self.text[start..];
+                let (kind, keyword) = if rest.starts_with("null") {
+                    (Kind::Null, "null")
+                } else if rest.starts_with("true") {
+                    (Kind::Boolean(true), "true")
+                } else if rest.starts_with("false") {
+                    (Kind::Boolean(false), "false")
+                } else {
+                    return Err(self.unexpected_character());
+                };
+                self.position += keyword.len();
+                let _ = self.push(kind, start, self.position);
+                Ok(())
+            }
+        }
+    }
+
+    fn parse_container(&mut self, depth: usize) -> Result<(), Error> {
+        if depth >= MAX_DEPTH {
+            return Err(self.error_at(self.position, format!("maximum nesting depth ({}) exceeded", MAX_DEPTH)));
+        }
+        let start = self.position;
+        let is_object = self.peek() == Some(b'{');
+        let (closing, expected_separator) = if is_object { (b'}', "expected `,` or `}`") } else { (b']', "expected `,` or `]`") };
+        let index = self.push(Kind::Null, start, start);
+        self.position += 1;
+
+        let mut len = 0;
+        self.skip_whitespaces();
+        if self.peek() == Some(closing) {
+            self.position += 1;
+        } else {
+            loop {
+                self.skip_whitespaces();
+                if is_object {
+                    if self.peek() != Some(b'"') {
+                        return Err(self.error_at(self.position, "expected object key".to_string()));
+                    }
+                    self.parse_string()?;
+                    self.skip_whitespaces();
+                    if self.peek() != Some(b':') {
+                        return Err(self.error_at(self.position, "expected `:`".to_string()));
+                    }
+                    self.position += 1;
+                    self.skip_whitespaces();
+                }
+                self.parse_value(depth + 1)?;
+                len += 1;
+                self.skip_whitespaces();
+                match self.peek() {
+                    Some(b',') => self.position += 1,
+                    Some(byte) if byte == closing => {
+                        self.position += 1;
+                        break;
+                    }
+                    _ => return Err(self.error_at(self.position, expected_separator.to_string())),
+                }
+            }
+        }
+
+        let next = self.entries.len();
+        self.entries[index] = Entry {
+            kind: if is_object { Kind::Object { len } } else { Kind::Array { len } },
+            start,
+            end: self.position,
+            next,
+        };
+        Ok(())
+    }
+
+    fn parse_hex_code_unit(&mut self, escape_position: usize) -> Result<u32, Error> {
+        let digits = self
+            .text
+            .get(self.position..self.position + 4)
+            .filter(|digits| digits.bytes().all(|byte| byte.is_ascii_hexdigit()));
+        match digits.map(|digits| u32::from_str_radix(digits, 16)) {
+            Some(Ok(code_unit)) => {
+                self.position += 4;
+                Ok(code_unit)
+            }
+            _ => Err(self.error_at(escape_position, "invalid unicode escape sequence".to_string())),
+        }
+    }
+
+    fn parse_string(&mut self) -> Result<(), Error> {
+        let start = self.position;
+        self.position += 1; // `"`
+        let mut escaped = false;
+        loop {
+            let character_position = self.position;
+            match self.peek() {
+                None => return Err(self.error_at(start, "unterminated string".to_string())),
+                Some(b'"') => break,
+                Some(b'\\') => {
+                    escaped = true;
+                    self.position += 1;
+                    match self.peek() {
+                        Some(b'"') | Some(b'\\') | Some(b'/') | Some(b'b') | Some(b'f') | Some(b'n') | Some(b'r') | Some(b't') => self.position += 1,
+                        Some(b'u') => {
+                            self.position += 1;
+                            let code_unit = self.parse_hex_code_unit(character_position)?;
+                            if (0xd800..0xdc00).contains(&code_unit) {
+                                let low_surrogate = if self.text[self.position..].starts_with("\\u") {
+                                    self.position += 2;
+                                    self.parse_hex_code_unit(self.position - 2)?
+                                } else {
+                                    0
+                                };
+                                if !(0xdc00..0xe000).contains(&low_surrogate) {
+                                    return Err(self.error_at(character_position, "invalid unicode surrogate pair".to_string()));
+                                }
+                            } else if (0xdc00..0xe000).contains(&code_unit) {
+                                return Err(self.error_at(character_position, "invalid unicode surrogate pair".to_string()));
+                            }
+                        }
+                        _ => return Err(self.error_at(character_position, "invalid escape sequence".to_string())),
+                    }
+                }
+                Some(byte) if byte < 0x20 => return Err(self.error_at(character_position, "unescaped control character in string".to_string())),
+                Some(_) => self.position += 1,
+            }
+        }
+        let _ = self.push(Kind::String { escaped }, start + 1, self.position);
+        self.position += 1; // `"`
+        Ok(())
+    }
+
+    fn parse_number(&mut self) -> Result<(), Error> {
+        let start = self.position;
+        if self.peek() == Some(b'-') {
+            self.position += 1;
+        }
+        let integer_start = self.position;
+        match self.skip_digits() {
+            0 => return Err(self.error_at(start, "invalid number".to_string())),
+            digits if digits > 1 && self.bytes[integer_start] == b'0' => return Err(self.error_at(start, "leading zeros are not allowed".to_string())),
+            _ => {}
+        }
+        let mut kind = Kind::Integer;
+        if self.peek() == Some(b'.') {
+            self.position += 1;
+            kind = Kind::Number;
+            if self.skip_digits() == 0 {
+                return Err(self.error_at(self.position, "expected fraction digits".to_string()));
+            }
+        }
+        if let Some(b'e') | Some(b'E') = self.peek() {
+            self.position += 1;
+            kind = Kind::Number;
+            if let Some(b'+') | Some(b'-') = self.peek() {
+                self.position += 1;
+            }
+            if self.skip_digits() == 0 {
+                return Err(self.error_at(self.position, "expected exponent digits".to_string()));
+            }
+        }
+        let _ = self.push(kind, start, self.position);
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct LazyDocument<'a> {
+    text: &'a str,
+    entries: Vec<Entry>,
+}
+
+impl<'a> LazyDocument<'a> {
+    /// # Errors
+    ///
+    /// Will return `Err` if `text` is not a valid JSON document. The error reports the position of the failure
+    pub fn parse(text: &'a str) -> Result<Self, Error> {
+        let mut builder = TapeBuilder {
+            text,
+            bytes: text.as_bytes(),
+            position: 0,
+            entries: Vec::new(),
+        };
+        builder.skip_whitespaces();
+        builder.parse_value(0)?;
+        builder.skip_whitespaces();
+        if builder.position < text.len() {
+            return Err(builder.error_at(builder.position, "unexpected characters after the document".to_string()));
+        }
+        Ok(Self { text, entries: builder.entries })
+    }
+
+    // Root of the document. Values materialized while traversing it are cached by the returned instance
+    #[must_use]
+    pub fn root(&self) -> LazyValue {
+        LazyValue::new(
+            Tape {
+                text: self.text,
+                entries: &self.entries,
+            },
+            0,
+        )
+    }
+}
+
+#[derive(Debug)]
+enum Children<'doc> {
+    Array(Vec<LazyValue<'doc>>),
+    Object {
+        entries: Vec<(Cow<'doc, str>, LazyValue<'doc>)>,
+        // Position in `entries` of each key (duplicated keys refer to their last occurrence)
+        positions: HashMap<Cow<'doc, str>, usize>,
+    },
+}
+
+#[derive(Debug)]
+pub struct LazyValue<'doc> {
+    tape: Tape<'doc>,
+    index: usize,
+    children: OnceLock<Children<'doc>>,
+    // Decoded content of strings with escape sequences
+    unescaped: OnceLock<String>,
+}
+
+impl<'doc> LazyValue<'doc> {
+    fn new(tape: Tape<'doc>, index: usize) -> Self {
+        Self {
+            tape,
+            index,
+            children: OnceLock::new(),
+            unescaped: OnceLock::new(),
+        }
+    }
+
+    fn kind(&self) -> Kind {
+        self.tape.entries[self.index].kind
+    }
+
+    // Raw JSON text of the value
+    #[must_use]
+    pub fn raw_json(&self) -> &'doc str {
+        let entry = self.tape.entries[self.index];
+        match entry.kind {
+            Kind::String { .. } => &self.tape.text[entry.start - 1..=entry.end],
+            _ => &self.tape.text[entry.start..entry.end],
+        }
+    }
+
+    fn children(&self) -> Option<&Children<'doc>> {
+        let tape = self.tape;
+        match self.kind() {
+            Kind::Array { len } => Some(self.children.get_or_init(|| {
+                let mut items = Vec::with_capacity(len);
+                items.extend(tape.children(self.index).map(|child| Self::new(tape, child)));
+                Children::Array(items)
+            })),
+            Kind::Object { len } => Some(self.children.get_or_init(|| {
+                let mut entries = Vec::with_capacity(len);
+                let mut positions = HashMap::with_capacity(len);
+                let mut children = tape.children(self.index);
+                while let (Some(key), Some(value)) = (children.next(), children.next()) {
+                    let key = tape.string(key);
+                    let _ = positions.insert(key.clone(), entries.len());
+                    entries.push((key, Self::new(tape, value)));
+                }
+                Children::Object { entries, positions }
+            })),
+            _ => None,
+        }
+    }
+
+    fn object_entries(&self) -> &[(Cow<'doc, str>, LazyValue<'doc>)] {
+        match self.children() {
+            Some(Children::Object { entries, .. }) => entries,
+            _ => &[],
+        }
+    }
+}
+
+impl<'doc> ToRustType for LazyValue<'doc> {
+    fn to_rust_type(&self) -> RustType {
+        // Values are read directly from the tape, without materializing the intermediate `LazyValue`s
+        self.tape.to_rust_type(self.index)
+    }
+}
+
+impl<'json, 'doc> JsonMapTrait<'json, LazyValue<'doc>> for JsonMap<'json, LazyValue<'doc>> {
+    #[must_use]
+    fn items(&'json self) -> Box<dyn Iterator<Item = (&str, &LazyValue<'doc>)> + 'json> {
+        Box::new(self.object_entries().iter().map(|(key, value)| (key.as_ref(), value)))
+    }
+}
+
+impl<'doc> JsonType for LazyValue<'doc> {
+    #[must_use]
+    fn as_array<'json>(&'json self) -> Option<Box<dyn ExactSizeIterator<Item = &Self> + 'json>> {
+        if let Some(Children::Array(items)) = self.children() {
+            Some(Box::new(items.iter()))
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_boolean(&self) -> Option<bool> {
+        if let Kind::Boolean(value) = self.kind() {
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_integer(&self) -> Option<i128> {
+        if let Kind::Integer = self.kind() {
+            self.tape.integer(self.index)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "big_integer")]
+    #[must_use]
+    fn as_big_integer(&self) -> Option<BigInt> {
+        if let Kind::Integer = self.kind() {
+            self.tape.raw(self.index).parse().ok()
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_null(&self) -> Option<()> {
+        if let Kind::Null = self.kind() {
+            Some(())
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_number(&self) -> Option<f64> {
+        if let Kind::Integer | Kind::Number = self.kind() {
+            Some(self.tape.number(self.index))
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_number_str(&self) -> Option<Cow<str>> {
+        if let Kind::Integer | Kind::Number = self.kind() {
+            self.tape.number_str(self.index).map(Cow::Borrowed)
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_object(&self) -> Option<JsonMap<Self>> {
+        if let Kind::Object { .. } = self.kind() {
+            Some(JsonMap::new(self))
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_string(&self) -> Option<&str> {
+        match self.kind() {
+            Kind::String { escaped: false } => Some(self.tape.raw(self.index)),
+            Kind::String { escaped: true } => Some(self.unescaped.get_or_init(|| unescape(self.tape.raw(self.index)))),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    fn get_attribute(&self, attribute_name: &str) -> Option<&Self> {
+        if let Some(Children::Object { entries, positions }) = self.children() {
+            positions.get(attribute_name).map(|position| &entries[*position].1)
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn get_index(&self, index: usize) -> Option<&Self> {
+        if let Some(Children::Array(items)) = self.children() {
+            items.get(index)
+        } else {
+            None
+        }
+    }
+}
+
+impl<'doc> ThreadSafeJsonType for LazyValue<'doc> {}
+
+#[cfg(test)]
+mod tests_lazy_json_map_trait {
+    use super::{LazyDocument, LazyValue};
+    use crate::json_type::{JsonMap, JsonMapTrait, JsonType};
+
+    const TESTING_MAP: &str = r#"{"k1": "v1", "k2": {"nested": []}}"#;
+
+    #[test]
+    fn keys() {
+        let document = LazyDocument::parse(TESTING_MAP).unwrap();
+        let root = document.root();
+        assert_eq!(JsonMap::new(&root).keys().collect::<Vec<_>>(), vec!["k1", "k2"]);
+    }
+
+    #[test]
+    fn values() {
+        let document = LazyDocument::parse(TESTING_MAP).unwrap();
+        let root = document.root();
+        assert_eq!(
+            JsonMap::new(&root).values().map(LazyValue::raw_json).collect::<Vec<_>>(),
+            vec![r#""v1""#, r#"{"nested": []}"#]
+        );
+    }
+
+    #[test]
+    fn items() {
+        let document = LazyDocument::parse(TESTING_MAP).unwrap();
+        let root = document.root();
+        assert_eq!(
+            JsonMap::new(&root).items().map(|(key, value)| (key, value.as_string())).collect::<Vec<_>>(),
+            vec![("k1", Some("v1")), ("k2", None)]
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests_primitive_type_trait {
+    use super::{LazyDocument, LazyValue};
+    use crate::json_type::{JsonType, PrimitiveType};
+    use test_case::test_case;
+
+    #[test_case("[]", PrimitiveType::Array)]
+    #[test_case("true", PrimitiveType::Boolean)]
+    #[test_case("1", PrimitiveType::Integer)]
+    #[test_case("null", PrimitiveType::Null)]
+    #[test_case("1.2", PrimitiveType::Number)]
+    #[test_case("1e2", PrimitiveType::Number ; "exponent")]
+    #[test_case(r#"{"prop": "value"}"#, PrimitiveType::Object)]
+    #[test_case(r#""string""#, PrimitiveType::String)]
+    fn test_primitive_type(json_string: &str, expected_value: PrimitiveType) {
+        assert_eq!(LazyDocument::parse(json_string).unwrap().root().primitive_type(), expected_value);
+    }
+
+    #[test_case(r#"{"present": 1}"#, "present", Some("1"))]
+    #[test_case(r#"{"present": 1}"#, "not-present", None)]
+    #[test_case(r#"{"dup": 1, "dup": 2}"#, "dup", Some("2") ; "duplicated key")]
+    #[test_case(r#"{"esc\u0061ped": true}"#, "escaped", Some("true") ; "escaped key")]
+    #[test_case("[1]", "0", None)]
+    fn test_get_attribute(json_string: &str, attribute_name: &str, expected_value: Option<&str>) {
+        let document = LazyDocument::parse(json_string).unwrap();
+        assert_eq!(document.root().get_attribute(attribute_name).map(LazyValue::raw_json), expected_value);
+    }
+
+    #[test_case("[0, [1], 2]", 1, Some("[1]"))]
+    #[test_case("[0, 1, 2]", 4, None)]
+    #[test_case(r#"{"0": 1}"#, 0, None)]
+    fn test_get_index(json_string: &str, index: usize, expected_value: Option<&str>) {
+        let document = LazyDocument::parse(json_string).unwrap();
+        assert_eq!(document.root().get_index(index).map(LazyValue::raw_json), expected_value);
+    }
+
+    #[test_case("true", Some(true))]
+    #[test_case("false", Some(false))]
+    #[test_case("1", None)]
+    fn test_as_boolean(json_string: &str, expected_value: Option<bool>) {
+        assert_eq!(LazyDocument::parse(json_string).unwrap().root().as_boolean(), expected_value);
+    }
+
+    #[test_case("1", Some(1))]
+    #[test_case("-1", Some(-1) ; "negative integer")]
+    #[test_case("340282366920938463463374607431768211456", None ; "integer above i128 max")]
+    #[test_case("1.0", None)]
+    #[test_case(r#""1""#, None)]
+    fn test_as_integer(json_string: &str, expected_value: Option<i128>) {
+        assert_eq!(LazyDocument::parse(json_string).unwrap().root().as_integer(), expected_value);
+    }
+
+    #[test_case("null", Some(()))]
+    #[test_case(r#""1""#, None)]
+    fn test_as_null(json_string: &str, expected_value: Option<()>) {
+        assert_eq!(LazyDocument::parse(json_string).unwrap().root().as_null(), expected_value);
+    }
+
+    #[test_case("1", Some(1_f64))]
+    #[test_case("-1.2e1", Some(-12_f64) ; "negative with exponent")]
+    #[test_case(r#""1""#, None)]
+    fn test_as_number(json_string: &str, expected_value: Option<f64>) {
+        assert_eq!(LazyDocument::parse(json_string).unwrap().root().as_number(), expected_value);
+    }
+
+    #[test_case("0.1000000000000000055511151231257827", Some("0.1000000000000000055511151231257827") ; "inexact number")]
+    #[test_case("340282366920938463463374607431768211457", Some("340282366920938463463374607431768211457") ; "inexact integer")]
+    #[test_case("1.10", None ; "exact number")]
+    #[test_case("1e2", None ; "exact exponent")]
+    #[test_case(r#""1""#, None)]
+    fn test_as_number_str(json_string: &str, expected_value: Option<&str>) {
+        assert_eq!(LazyDocument::parse(json_string).unwrap().root().as_number_str().as_deref(), expected_value);
+    }
+
+    #[test_case(r#""plain""#, Some("plain"))]
+    #[test_case(r#""tab\tquote\"slash\/è😀""#, Some("tab\tquote\"slash/è😀") ; "escapes")]
+    #[test_case("1", None)]
+    fn test_as_string(json_string: &str, expected_value: Option<&str>) {
+        assert_eq!(LazyDocument::parse(json_string).unwrap().root().as_string(), expected_value);
+    }
+}
+
+#[cfg(test)]
+mod tests_lazy_document {
+    use super::{Children, LazyDocument};
+    use crate::{
+        error::Error,
+        json_type::{get_fragment, JsonType, ToRustType},
+    };
+    use test_case::test_case;
+
+    #[test]
+    fn test_only_touched_values_are_materialized() {
+        let document = LazyDocument::parse(r#"{"a": [{"b": 1}, {"c": [2]}], "d": {"e": [3]}}"#).unwrap();
+        let root = document.root();
+        assert_eq!(get_fragment(&root, "/a/1/c/0").and_then(JsonType::as_integer), Some(2));
+
+        assert!(root.children.get().is_some());
+        let a = root.get_attribute("a").unwrap();
+        assert!(a.children.get().is_some());
+        // Siblings of the traversed path are not materialized
+        assert!(root.get_attribute("d").unwrap().children.get().is_none());
+        assert!(a.get_index(0).unwrap().children.get().is_none());
+        match a.children.get() {
+            Some(Children::Array(items)) => assert_eq!(items.len(), 2),
+            children => panic!("Unexpected children {:?}", children),
+        }
+    }
+
+    #[test]
+    fn test_to_rust_type() {
+        let document = LazyDocument::parse(r#" {"a": [1, 2.5, "s\n", null, true, {}], "b": {"c": false}} "#).unwrap();
+        assert_eq!(document.root().to_rust_type(), rust_type!({"a": [1, 2.5, "s\n", null, true, {}], "b": {"c": false}}));
+    }
+
+    #[test]
+    fn test_to_rust_type_preserves_inexact_numbers() {
+        let document = LazyDocument::parse("[0.1000000000000000055511151231257827, 1.10]").unwrap();
+        let decimal = crate::Decimal::new("0.1000000000000000055511151231257827").unwrap();
+        assert_eq!(document.root().to_rust_type(), rust_type!([(crate::RustType::Decimal(decimal)), 1.1]));
+    }
+
+    #[cfg(feature = "big_integer")]
+    #[test]
+    fn test_big_integer() {
+        let document = LazyDocument::parse("-340282366920938463463374607431768211456").unwrap();
+        let expected_value = num_bigint::BigInt::from(2).pow(128_u32) * num_bigint::BigInt::from(-1);
+        assert_eq!(document.root().as_big_integer(), Some(expected_value.clone()));
+        assert_eq!(document.root().to_rust_type(), crate::rust_type_impl::RustType::from(expected_value));
+    }
+
+    #[test]
+    fn test_values_are_shareable_across_threads() {
+        let document = LazyDocument::parse(r#"{"key": [1, 2, 3]}"#).unwrap();
+        let root = document.root();
+        let sums = sum_in_threads(&root);
+        assert_eq!(sums, vec![6, 6]);
+    }
+
+    fn sum_in_threads(root: &super::LazyValue) -> Vec<i128> {
+        std::thread::scope(|scope| {
+            let handles = (0..2)
+                .map(|_| {
+                    scope.spawn(|| {
+                        root.get_attribute("key")
+                            .and_then(JsonType::as_array)
+                            .map_or(0, |items| items.filter_map(JsonType::as_integer).sum())
+                    })
+                })
+                .collect::<Vec<_>>();
+            handles.into_iter().map(|handle| handle.join().unwrap()).collect()
+        })
+    }
+
+    #[test_case("", 1, 1, "unexpected end of input" ; "empty document")]
+    #[test_case("[1] 2", 1, 5, "unexpected characters after the document" ; "trailing characters")]
+    #[test_case("{\n  \"a\": 1\n  \"b\": 2\n}", 3, 3, "expected `,` or `}`" ; "missing comma")]
+    #[test_case("[1,]", 1, 4, "unexpected character `]`" ; "trailing comma")]
+    #[test_case("{a: 1}", 1, 2, "expected object key" ; "unquoted key")]
+    #[test_case(r#"{"a" 1}"#, 1, 6, "expected `:`" ; "missing colon")]
+    #[test_case(r#""abc"#, 1, 1, "unterminated string" ; "unterminated string")]
+    #[test_case("\"a\tb\"", 1, 3, "unescaped control character in string" ; "control character")]
+    #[test_case(r#""\x41""#, 1, 2, "invalid escape sequence" ; "invalid escape")]
+    #[test_case(r#""\u12""#, 1, 2, "invalid unicode escape sequence" ; "short unicode escape")]
+    #[test_case(r#""\ud83d""#, 1, 2, "invalid unicode surrogate pair" ; "lone surrogate")]
+    #[test_case("01", 1, 1, "leading zeros are not allowed" ; "leading zeros")]
+    #[test_case("1.", 1, 3, "expected fraction digits" ; "empty fraction")]
+    #[test_case("1e", 1, 3, "expected exponent digits" ; "empty exponent")]
+    #[test_case("-", 1, 1, "invalid number" ; "sign only")]
+    #[test_case("nul", 1, 1, "unexpected character `n`" ; "invalid literal")]
+    #[test_case("[\"è\", x]", 1, 7, "unexpected character `x`" ; "column counts characters")]
+    fn test_parse_errors(text: &str, line: usize, column: usize, message: &str) {
+        assert_eq!(
+            LazyDocument::parse(text).map(|_| ()),
+            Err(Error::InvalidSyntax {
+                line,
+                column,
+                message: message.to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_maximum_depth() {
+        let text = format!("{}{}", "[".repeat(200), "]".repeat(200));
+        assert_eq!(
+            LazyDocument::parse(&text).map(|_| ()),
+            Err(Error::InvalidSyntax {
+                line: 1,
+                column: 129,
+                message: "maximum nesting depth (128) exceeded".to_string()
+            })
+        );
+    }
+}
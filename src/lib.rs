@@ -47,7 +47,16 @@ extern crate strum_macros;
 #[cfg(test)]
 #[macro_use]
 extern crate lazy_static;
-#[cfg(all(test, any(feature = "trait_serde_json", feature = "trait_serde_yaml", feature = "trait_json")))]
+#[cfg(all(
+    test,
+    any(
+        feature = "trait_bson",
+        feature = "trait_serde_json",
+        feature = "trait_serde_yaml",
+        feature = "trait_json",
+        feature = "trait_toml"
+    )
+))]
 #[macro_use]
 extern crate serde_json;
 
@@ -55,14 +64,33 @@ extern crate serde_json;
 #[macro_use]
 pub mod macros;
 
+#[cfg(feature = "arena")]
+pub mod arena;
+#[cfg(feature = "config")]
+pub mod config;
+pub mod convert;
+#[cfg(feature = "env_source")]
+pub mod env_source;
 mod error;
 pub mod fragment_helpers;
+#[cfg(feature = "json5")]
+pub mod json5;
+mod json_builder;
 mod json_type;
+#[cfg(feature = "lazy_json")]
+pub mod lazy_json;
+#[cfg(feature = "python_module")]
+pub mod python_module;
 mod rust_type_impl;
+mod rust_type_ref_impl;
+#[cfg(feature = "shared")]
+pub mod shared;
 pub mod traits;
 
 pub use crate::{
-    error::Error,
-    json_type::{get_fragment, JsonMap, JsonMapTrait, JsonType, JsonTypeToString, PrimitiveType, ThreadSafeJsonType, ToRustType},
-    rust_type_impl::RustType,
+    error::{BackendError, Error},
+    json_builder::JsonBuilder,
+    json_type::{get_fragment, try_get_fragment, JsonMap, JsonMapTrait, JsonType, JsonTypeToString, PrimitiveType, ThreadSafeJsonType, ToRustType, TryJsonMapTrait, TryJsonType},
+    rust_type_impl::{Decimal, RustType},
+    rust_type_ref_impl::RustTypeRef,
 };
@@ -0,0 +1,280 @@
+// Python extension module exposing the crate tools to Python callers.
+// Python objects are read via the `trait_pyo3` backend, so the module accepts any JSON-like Python object
+// (ie. `dict`, `list`, `tuple`, `int`, `float`, `decimal.Decimal`, `str`, `bool` and `None`).
+//
+// The module is not built by default, as Python extension modules have to be compiled as `cdylib` without
+// linking the Python interpreter. Use `make python-module` to build it.
+use crate::{
+    error::Error,
+    fragment_helpers::fragment_from_fragment_components,
+    json_type::{normalized_decimal, try_get_fragment, TryJsonType},
+    rust_type_impl::{Decimal, RustType},
+    traits::_pyo3::to_pyobject,
+};
+use pyo3::{
+    exceptions::{KeyError, TypeError, ValueError},
+    prelude::*,
+    types::PyAny,
+    wrap_pyfunction,
+};
+use std::{convert::TryFrom, fmt::Write};
+
+// Python refuses to convert integers with more digits into strings (`sys.get_int_max_str_digits`), so longer integral
+// decimals are not expanded into digits
+const MAX_INTEGER_DIGITS: usize = 4300;
+
+// Python exception matching `error`, backend failures carry the original exception representation
+fn error_to_py_err(error: &Error) -> PyErr {
+    match error {
+        Error::MissingKey { .. } => KeyError::py_err(error.to_string()),
+        Error::TypeMismatch { .. } => TypeError::py_err(error.to_string()),
+        Error::Backend { source, .. } => ValueError::py_err(format!("{}: {}", error, source)),
+        _ => ValueError::py_err(error.to_string()),
+    }
+}
+
+// Path of the first non finite number (NaN or infinity) contained by `value`
+fn non_finite_number_path(value: &RustType) -> Option<Vec<String>> {
+    match value {
+        RustType::Number(number) if !number.is_finite() => Some(Vec::new()),
+        RustType::List(items) => items.iter().enumerate().find_map(|(index, item)| {
+            non_finite_number_path(item).map(|mut path| {
+                path.insert(0, index.to_string());
+                path
+            })
+        }),
+        RustType::Object(object) => object.iter().find_map(|(key, value)| {
+            non_finite_number_path(value).map(|mut path| {
+                path.insert(0, key.clone());
+                path
+            })
+        }),
+        _ => None,
+    }
+}
+
+// JSON representation of `value`.
+// `ValueError` is raised if the object has non string keys, non finite numbers (JSON has no representation for them)
+// or if any of its attributes can not be read
+fn to_json_rust_type(value: &PyAny) -> PyResult<RustType> {
+    let rust_type = value.try_to_rust_type().map_err(|error| error_to_py_err(&error))?;
+    non_finite_number_path(&rust_type).map_or(Ok(rust_type), |path| {
+        Err(ValueError::py_err(format!(
+            "Non finite number at `{}` has no JSON representation",
+            fragment_from_fragment_components(&path)
+        )))
+    })
+}
+
+fn write_canonical_string(output: &mut String, value: &str) {
+    output.push('"');
+    for character in value.chars() {
+        match character {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            '\u{8}' => output.push_str("\\b"),
+            '\u{c}' => output.push_str("\\f"),
+            character if character.is_control() && u32::from(character) < 0x20 => {
+                let _ = write!(output, "\\u{:04x}", u32::from(character));
+            }
+            character => output.push(character),
+        }
+    }
+    output.push('"');
+}
+
+// Numbers exactly representable by a f64 are written as their shortest representation in exponent notation
+// (`-0.0` as `0e0`), so that `1`, `1.0` and `-0.0 == 0` share the same representation
+fn write_canonical_float(output: &mut String, value: f64) {
+    let _ = write!(output, "{:e}", if value == 0.0 { 0.0 } else { value });
+}
+
+// Integers not exactly representable by a f64 are written as plain digits, which never match the exponent notation
+// of floats (ie. `10**300` differs from `1e300`)
+fn write_canonical_integer(output: &mut String, digits: &str) {
+    match digits.parse::<f64>() {
+        Ok(value) if value.is_finite() && format!("{:.0}", value) == digits => write_canonical_float(output, value),
+        _ => output.push_str(digits),
+    }
+}
+
+// Decimals have no exact f64 representation (see `Decimal::new`): integral ones are written as integers while the
+// others are written as the exponent notation of their exact value
+fn write_canonical_decimal(output: &mut String, value: &Decimal) {
+    // Decimals are valid (non zero) JSON numbers, so they always have a normalised representation
+    if let Some((negative, digits, exponent)) = normalized_decimal(value.as_str()) {
+        let sign = if negative { "-" } else { "" };
+        match usize::try_from(exponent) {
+            Ok(zeros) if digits.len().saturating_add(zeros) <= MAX_INTEGER_DIGITS => {
+                write_canonical_integer(output, &format!("{}{}{}", sign, digits, "0".repeat(zeros)));
+            }
+            _ => {
+                let (first_digit, other_digits) = digits.split_at(1);
+                let separator = if other_digits.is_empty() { "" } else { "." };
+                let exponent = i128::from(exponent) + i128::try_from(other_digits.len()).unwrap_or_default();
+                let _ = write!(output, "{}{}{}{}e{}", sign, first_digit, separator, other_digits, exponent);
+            }
+        }
+    } else {
+        output.push_str(value.as_str());
+    }
+}
+
+// Compact JSON representation with object keys sorted by their code points
+fn write_canonical(output: &mut String, value: &RustType) {
+    match value {
+        RustType::Null => output.push_str("null"),
+        RustType::Boolean(value) => output.push_str(if *value { "true" } else { "false" }),
+        RustType::String(value) => write_canonical_string(output, value),
+        RustType::Integer(value) => write_canonical_integer(output, &value.to_string()),
+        #[cfg(feature = "big_integer")]
+        RustType::BigInteger(value) => write_canonical_integer(output, &value.to_string()),
+        RustType::Number(value) => write_canonical_float(output, *value),
+        RustType::Decimal(value) => write_canonical_decimal(output, value),
+        RustType::List(items) => {
+            output.push('[');
+            for (index, item) in items.iter().enumerate() {
+                if index > 0 {
+                    output.push(',');
+                }
+                write_canonical(output, item);
+            }
+            output.push(']');
+        }
+        RustType::Object(object) => {
+            let mut items = object.iter().collect::<Vec<_>>();
+            items.sort_unstable_by_key(|(key, _)| key.as_str());
+            output.push('{');
+            for (index, (key, value)) in items.into_iter().enumerate() {
+                if index > 0 {
+                    output.push(',');
+                }
+                write_canonical_string(output, key);
+                output.push(':');
+                write_canonical(output, value);
+            }
+            output.push('}');
+        }
+    }
+}
+
+fn canonical_json_string(value: &RustType) -> String {
+    let mut output = String::new();
+    write_canonical(&mut output, value);
+    output
+}
+
+// Object referenced by `fragment` (a JSON pointer), `None` if the object does not exist.
+// `ValueError` is raised if `fragment` is not a valid JSON pointer and `TypeError` if `fragment`
+// traverses a value that is neither an object nor an array
+#[pyfunction]
+fn get_fragment(py: Python, value: &PyAny, fragment: &str) -> PyResult<PyObject> {
+    match try_get_fragment(value, fragment) {
+        Ok(fragment_value) => Ok(fragment_value.to_object(py)),
+        Err(Error::MissingKey { .. }) => Ok(py.None()),
+        Err(error) => Err(error_to_py_err(&error)),
+    }
+}
+
+// Two values are equal if they have the same canonical JSON representation (ie. `1 == 1.0` while `(1,) == [1]`)
+#[pyfunction]
+fn json_equal(left: &PyAny, right: &PyAny) -> PyResult<bool> {
+    Ok(canonical_json_string(&to_json_rust_type(left)?) == canonical_json_string(&to_json_rust_type(right)?))
+}
+
+#[pyfunction]
+fn canonical_json(value: &PyAny) -> PyResult<String> {
+    to_json_rust_type(value).map(|rust_type| canonical_json_string(&rust_type))
+}
+
+// Equivalent object composed only by `dict`, `list`, `int`, `float`, `decimal.Decimal`, `str`, `bool` and `None`.
+// Values without JSON counterpart are reported as `None`
+#[pyfunction]
+fn to_json_compatible(py: Python, value: &PyAny) -> PyResult<PyObject> {
+    to_pyobject(py, &to_json_rust_type(value)?).map_err(|error| error_to_py_err(&error))
+}
+
+#[pymodule]
+fn json_trait_rs(_py: Python, module: &PyModule) -> PyResult<()> {
+    module.add_wrapped(wrap_pyfunction!(get_fragment))?;
+    module.add_wrapped(wrap_pyfunction!(json_equal))?;
+    module.add_wrapped(wrap_pyfunction!(canonical_json))?;
+    module.add_wrapped(wrap_pyfunction!(to_json_compatible))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PyInit_json_trait_rs;
+    use pyo3::{types::PyDict, wrap_pymodule, Python};
+    use test_case::test_case;
+
+    // Run `python_code` via the embedded interpreter, with the module available as `json_trait_rs`
+    fn run_python(python_code: &str) {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let locals = PyDict::new(py);
+        locals.set_item("json_trait_rs", wrap_pymodule!(json_trait_rs)(py)).unwrap();
+        if let Err(py_err) = py.run(python_code, None, Some(locals)) {
+            py_err.print(py);
+            panic!("{} failed", python_code);
+        }
+    }
+
+    #[test_case("assert json_trait_rs.get_fragment({'key': [1, {'nested': 'value'}]}, '/key/1/nested') == 'value'" ; "existing fragment")]
+    #[test_case("assert json_trait_rs.get_fragment({'key': (1, 2)}, '/key') == (1, 2)" ; "original object is returned")]
+    #[test_case("assert json_trait_rs.get_fragment({'key': [1]}, '/key/2') is None" ; "missing fragment")]
+    #[test_case("assert json_trait_rs.get_fragment({'key': [1]}, '/key/1/other') is None" ; "fragment below a missing fragment")]
+    #[test_case("try:\n    json_trait_rs.get_fragment({'key': 1}, '/key/other')\n    assert False\nexcept TypeError as error:\n    assert 'Type mismatch at `/key`' in str(error)" ; "fragment below a scalar")]
+    #[test_case("try:\n    json_trait_rs.get_fragment({}, '/~2')\n    assert False\nexcept ValueError as error:\n    assert 'Invalid JSON pointer' in str(error)" ; "invalid pointer")]
+    #[test_case("try:\n    json_trait_rs.get_fragment({'key': type('Failing', (list,), {'__getitem__': lambda self, index: 1 // 0})([1])}, '/key/0')\n    assert False\nexcept ValueError as error:\n    assert 'ZeroDivisionError' in str(error)" ; "failing lookup")]
+    fn test_get_fragment(python_code: &str) {
+        run_python(python_code);
+    }
+
+    #[test_case("assert json_trait_rs.json_equal({'a': 1, 'b': [True]}, {'b': [True], 'a': 1})" ; "key order is ignored")]
+    #[test_case("assert json_trait_rs.json_equal((1, 'a'), [1, 'a'])" ; "tuples are arrays")]
+    #[test_case("assert json_trait_rs.json_equal(1, 1.0)" ; "integral numbers")]
+    #[test_case("assert not json_trait_rs.json_equal({'a': 1}, {'a': '1'})" ; "different types")]
+    #[test_case("assert not json_trait_rs.json_equal(True, 1)" ; "booleans are not integers")]
+    #[test_case("assert json_trait_rs.json_equal(0.0, -0.0)" ; "signed zeros")]
+    #[test_case("assert json_trait_rs.json_equal(2 ** 100, float(2 ** 100))" ; "large integral numbers")]
+    #[test_case("assert json_trait_rs.json_equal(__import__('decimal').Decimal('9007199254740993'), 9007199254740993)" ; "integral decimals")]
+    #[test_case("assert json_trait_rs.json_equal(__import__('decimal').Decimal('1267650600228229401496703205376'), 2 ** 100)" ; "integral decimals exactly representable")]
+    #[test_case("assert not json_trait_rs.json_equal(__import__('decimal').Decimal('0.1000000000000000055'), 0.1)" ; "inexact decimals")]
+    #[test_case("try:\n    json_trait_rs.json_equal(float('nan'), None)\n    assert False\nexcept ValueError as error:\n    assert 'Non finite number at ``' in str(error)" ; "non finite numbers")]
+    #[test_case("try:\n    json_trait_rs.json_equal({1: 'a'}, {'1': 'a'})\n    assert False\nexcept ValueError as error:\n    assert 'pyo3 backend failure' in str(error)" ; "non string keys")]
+    fn test_json_equal(python_code: &str) {
+        run_python(python_code);
+    }
+
+    // Integers not fitting into i128 are reported as (lossy) numbers without `big_integer` feature
+    #[cfg(feature = "big_integer")]
+    #[test]
+    fn test_json_equal_big_integers() {
+        run_python("assert not json_trait_rs.json_equal(1e300, 10 ** 300)");
+        run_python("assert json_trait_rs.json_equal(2 ** 200, float(2 ** 200))");
+    }
+
+    #[test_case(r#"assert json_trait_rs.canonical_json({'b': [1, 2.5, None], 'a': {'d': True, 'c': 'string'}}) == '{"a":{"c":"string","d":true},"b":[1e0,2.5e0,null]}'"# ; "sorted keys")]
+    #[test_case(r#"assert json_trait_rs.canonical_json('quote" backslash\\ newline\n bell\x07 unicodeè') == r'"quote\" backslash\\ newline\n bell\u0007 unicode' 'è"'"# ; "escaped strings")]
+    #[test_case("assert json_trait_rs.canonical_json(__import__('decimal').Decimal('0.1000000000000000055')) == '1.000000000000000055e-1'" ; "decimals")]
+    #[test_case("assert json_trait_rs.canonical_json([-0.0, 1e300, 10 ** 30, 9007199254740993]) == '[0e0,1e300,1000000000000000000000000000000,9007199254740993]'" ; "numbers")]
+    #[test_case("assert json_trait_rs.canonical_json(__import__('decimal').Decimal('-1.5e-400')) == '-1.5e-400'" ; "decimals out of the f64 range")]
+    #[test_case("assert json_trait_rs.canonical_json(__import__('decimal').Decimal('1.10')) == '1.1e0'" ; "exact decimals")]
+    #[test_case("try:\n    json_trait_rs.canonical_json({'key': [1, float('inf')]})\n    assert False\nexcept ValueError as error:\n    assert 'Non finite number at `/key/1`' in str(error)" ; "non finite numbers")]
+    fn test_canonical_json(python_code: &str) {
+        run_python(python_code);
+    }
+
+    #[test_case("assert json_trait_rs.to_json_compatible({'key': (1, {'nested': (None,)})}) == {'key': [1, {'nested': [None]}]}" ; "tuples")]
+    #[test_case("assert type(json_trait_rs.to_json_compatible([(1,)])[0]) is list" ; "nested tuples")]
+    #[test_case("try:\n    json_trait_rs.to_json_compatible({'key': 1, 2: 'not a string key'})\n    assert False\nexcept ValueError as error:\n    assert 'pyo3 backend failure' in str(error)" ; "non string keys")]
+    #[test_case("try:\n    json_trait_rs.to_json_compatible([float('-inf')])\n    assert False\nexcept ValueError as error:\n    assert 'Non finite number at `/0`' in str(error)" ; "non finite numbers")]
+    fn test_to_json_compatible(python_code: &str) {
+        run_python(python_code);
+    }
+}
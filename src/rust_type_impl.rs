@@ -1,9 +1,62 @@
 use crate::{
-    json_type::{JsonMap, JsonMapTrait, JsonType, JsonTypeToString, ToRustType},
+    error::Error,
+    json_builder::JsonBuilder,
+    json_type::{is_exact_number_str, is_json_number_str, JsonMap, JsonMapTrait, JsonType, JsonTypeToString, ToRustType},
     ThreadSafeJsonType,
 };
 use join_lazy_fmt::Join;
-use std::{collections::hash_map::HashMap, fmt, ops::Deref};
+#[cfg(feature = "big_integer")]
+use num_bigint::BigInt;
+use std::{
+    borrow::Cow,
+    collections::hash_map::HashMap,
+    convert::{TryFrom, TryInto},
+    fmt,
+    ops::Deref,
+};
+
+// JSON textual representation of a number that cannot be losslessly represented by a f64.
+// The representation is validated on creation, so it is always a JSON number (`as_f64` is its closest f64).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Decimal {
+    number_str: String,
+    number: f64,
+}
+
+impl Decimal {
+    // `None` if `number_str` is not a JSON number or if it is exactly represented by a f64 (ie. `1.10`)
+    #[must_use]
+    pub fn new(number_str: &str) -> Option<Self> {
+        if !is_json_number_str(number_str) {
+            return None;
+        }
+        let number = number_str.parse().ok()?;
+        if is_exact_number_str(number, number_str) {
+            None
+        } else {
+            Some(Self {
+                number_str: number_str.to_string(),
+                number,
+            })
+        }
+    }
+
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.number_str
+    }
+
+    #[must_use]
+    pub const fn as_f64(&self) -> f64 {
+        self.number
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(formatter, "{}", self.number_str)
+    }
+}
 
 #[allow(clippy::module_name_repetitions)]
 #[derive(Clone, Debug, PartialEq)]
@@ -12,7 +65,13 @@ pub enum RustType {
     Boolean(bool),
     String(String),
     Integer(i128),
+    // Integers that do not fit into a i128. Use `RustType::from(BigInt)` to ensure that
+    // `RustType::Integer` is used whenever possible.
+    #[cfg(feature = "big_integer")]
+    BigInteger(BigInt),
     Number(f64),
+    // Numbers that cannot be losslessly represented by a f64, stored via their JSON textual representation
+    Decimal(Decimal),
     List(Vec<RustType>),
     Object(HashMap<String, RustType>),
 }
@@ -24,7 +83,10 @@ impl fmt::Display for RustType {
             Self::Boolean(value) => write!(formatter, "{}", value),
             Self::String(value) => write!(formatter, r#""{}""#, value),
             Self::Integer(value) => write!(formatter, "{}", value),
+            #[cfg(feature = "big_integer")]
+            Self::BigInteger(value) => write!(formatter, "{}", value),
             Self::Number(value) => write!(formatter, "{}", value),
+            Self::Decimal(value) => write!(formatter, "{}", value),
             Self::List(value) => {
                 write!(formatter, "[")?;
                 write!(formatter, "{}", ",".join(value))?;
@@ -101,6 +163,51 @@ impl From<i128> for RustType {
     }
 }
 
+impl From<u32> for RustType {
+    #[must_use]
+    fn from(value: u32) -> Self {
+        Self::Integer(value.into())
+    }
+}
+
+impl From<u64> for RustType {
+    #[must_use]
+    fn from(value: u64) -> Self {
+        Self::Integer(value.into())
+    }
+}
+
+// Not all the u128 values fit into a i128. Enabling the `big_integer` feature makes the conversion infallible.
+impl TryFrom<u128> for RustType {
+    type Error = Error;
+
+    fn try_from(value: u128) -> Result<Self, Self::Error> {
+        #[cfg(feature = "big_integer")]
+        {
+            Ok(Self::from(BigInt::from(value)))
+        }
+        #[cfg(not(feature = "big_integer"))]
+        {
+            value.try_into().map(Self::Integer).map_err(|_| Error::NumericOverflow {
+                path: String::new(),
+                value: value.to_string(),
+                target: "i128",
+            })
+        }
+    }
+}
+
+#[cfg(feature = "big_integer")]
+impl From<BigInt> for RustType {
+    #[must_use]
+    fn from(value: BigInt) -> Self {
+        match (&value).try_into() {
+            Ok(integer) => Self::Integer(integer),
+            Err(_) => Self::BigInteger(value),
+        }
+    }
+}
+
 impl From<f32> for RustType {
     #[must_use]
     fn from(value: f32) -> Self {
@@ -156,10 +263,21 @@ impl JsonType for RustType {
 
     #[must_use]
     fn as_integer(&self) -> Option<i128> {
-        if let Self::Integer(v) = self {
-            Some(*v)
-        } else {
-            None
+        match self {
+            Self::Integer(v) => Some(*v),
+            #[cfg(feature = "big_integer")]
+            Self::BigInteger(v) => v.try_into().ok(),
+            _ => None,
+        }
+    }
+
+    #[cfg(feature = "big_integer")]
+    #[must_use]
+    fn as_big_integer(&self) -> Option<BigInt> {
+        match self {
+            Self::Integer(v) => Some(BigInt::from(*v)),
+            Self::BigInteger(v) => Some(v.clone()),
+            _ => None,
         }
     }
 
@@ -174,8 +292,17 @@ impl JsonType for RustType {
 
     #[must_use]
     fn as_number(&self) -> Option<f64> {
-        if let Self::Number(v) = self {
-            Some(*v)
+        match self {
+            Self::Number(v) => Some(*v),
+            Self::Decimal(v) => Some(v.as_f64()),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    fn as_number_str(&self) -> Option<Cow<str>> {
+        if let Self::Decimal(v) = self {
+            Some(Cow::Borrowed(v.as_str()))
         } else {
             None
         }
@@ -220,6 +347,46 @@ impl JsonType for RustType {
 
 impl ThreadSafeJsonType for RustType {}
 
+impl JsonBuilder for RustType {
+    fn null() -> Self {
+        Self::Null
+    }
+
+    fn bool(value: bool) -> Self {
+        Self::Boolean(value)
+    }
+
+    fn integer(value: i128) -> Result<Self, Error> {
+        Ok(Self::Integer(value))
+    }
+
+    #[cfg(feature = "big_integer")]
+    fn big_integer(value: &BigInt) -> Result<Self, Error> {
+        Ok(Self::from(value.clone()))
+    }
+
+    fn number(value: f64) -> Self {
+        Self::Number(value)
+    }
+
+    // The textual representation is preserved only if it cannot be losslessly represented by a f64
+    fn decimal(value: f64, number_str: &str) -> Self {
+        Decimal::new(number_str).map_or(Self::Number(value), Self::Decimal)
+    }
+
+    fn string(value: &str) -> Self {
+        Self::String(value.to_string())
+    }
+
+    fn array<I: IntoIterator<Item = Self>>(items: I) -> Self {
+        Self::List(items.into_iter().collect())
+    }
+
+    fn object<'key, I: IntoIterator<Item = (&'key str, Self)>>(items: I) -> Self {
+        Self::Object(items.into_iter().map(|(key, value)| (key.to_string(), value)).collect())
+    }
+}
+
 impl<'json> JsonMapTrait<'json, RustType> for JsonMap<'json, RustType> {
     #[must_use]
     fn items(&'json self) -> Box<dyn Iterator<Item = (&str, &RustType)> + 'json> {
@@ -239,11 +406,15 @@ impl<'json> JsonMapTrait<'json, RustType> for JsonMap<'json, RustType> {
 
 #[cfg(test)]
 mod smoke_test {
+    #[cfg(not(feature = "big_integer"))]
+    use crate::error::Error;
     use crate::{
-        json_type::{JsonMapTrait, JsonType},
-        rust_type_impl::RustType,
+        json_type::{JsonMapTrait, JsonType, PrimitiveType, ToRustType},
+        rust_type_impl::{Decimal, RustType},
     };
-    use std::collections::hash_map::HashMap;
+    #[cfg(feature = "big_integer")]
+    use num_bigint::BigInt;
+    use std::{collections::hash_map::HashMap, convert::TryFrom};
     use test_case::test_case;
 
     #[test_case(&rust_type!(()) => "null")]
@@ -286,6 +457,64 @@ mod smoke_test {
         assert_eq!(testing_type_instance.is_string(), false);
     }
 
+    #[test]
+    fn test_unsigned_integers() {
+        assert_eq!(RustType::from(u32::max_value()), RustType::Integer(i128::from(u32::max_value())));
+        assert_eq!(RustType::from(u64::max_value()), RustType::Integer(i128::from(u64::max_value())));
+        assert_eq!(RustType::try_from(42_u128), Ok(RustType::Integer(42)));
+    }
+
+    #[cfg(not(feature = "big_integer"))]
+    #[test]
+    fn test_u128_overflow() {
+        assert_eq!(
+            RustType::try_from(u128::max_value()),
+            Err(Error::NumericOverflow {
+                path: String::new(),
+                value: u128::max_value().to_string(),
+                target: "i128",
+            })
+        );
+    }
+
+    #[cfg(feature = "big_integer")]
+    #[test]
+    fn test_big_integer() {
+        let big_integer = RustType::try_from(u128::max_value()).unwrap();
+        assert_eq!(big_integer, RustType::BigInteger(BigInt::from(u128::max_value())));
+        assert_eq!(big_integer.primitive_type(), PrimitiveType::Integer);
+        assert_eq!(big_integer.as_integer(), None);
+        assert_eq!(big_integer.as_big_integer(), Some(BigInt::from(u128::max_value())));
+        assert_eq!(big_integer.to_string(), u128::max_value().to_string());
+
+        // Integers fitting into a i128 are always represented as RustType::Integer
+        assert_eq!(RustType::from(BigInt::from(1)), RustType::Integer(1));
+        assert_eq!(RustType::BigInteger(BigInt::from(1)).as_integer(), Some(1));
+    }
+
+    #[test]
+    fn test_decimal() {
+        let decimal = RustType::Decimal(Decimal::new("0.1000000000000000055511151231257827").unwrap());
+        assert_eq!(decimal.primitive_type(), PrimitiveType::Number);
+        assert_eq!(decimal.as_number(), Some(0.1));
+        assert_eq!(decimal.as_number_str().as_deref(), Some("0.1000000000000000055511151231257827"));
+        assert_eq!(decimal.to_string(), "0.1000000000000000055511151231257827");
+        assert_eq!(decimal.to_rust_type(), decimal);
+        assert_eq!(RustType::from(0.1).as_number_str(), None);
+    }
+
+    #[test_case("0.1000000000000000055", true ; "not representable digits")]
+    #[test_case("1e400", true ; "overflow")]
+    #[test_case("1.10", false ; "exact")]
+    #[test_case("abc", false ; "not a number")]
+    #[test_case("01.00000000000000000001", false ; "leading zero")]
+    fn test_decimal_new(number_str: &str, expected_is_decimal: bool) {
+        assert_eq!(
+            Decimal::new(number_str).map(|decimal| decimal.to_string()),
+            if expected_is_decimal { Some(number_str.to_string()) } else { None }
+        );
+    }
+
     #[test]
     fn test_testing_type_instance_list() {
         let array = vec![RustType::from(1), RustType::from(2)];
@@ -0,0 +1,399 @@
+// Borrowed counterpart of `RustType`.
+// Strings (and object keys) are `Cow<'a, str>` so that parsers can reference the input buffer instead of allocating
+// a `String` for each of them. `RustTypeRef::into_owned` converts the instance into a `RustType`.
+use crate::{
+    json_type::{JsonMap, JsonMapTrait, JsonType, ToRustType},
+    rust_type_impl::{Decimal, RustType},
+    ThreadSafeJsonType,
+};
+#[cfg(feature = "big_integer")]
+use num_bigint::BigInt;
+#[cfg(feature = "big_integer")]
+use std::convert::TryInto;
+use std::{borrow::Cow, collections::hash_map::HashMap, ops::Deref};
+
+#[allow(clippy::module_name_repetitions)]
+#[derive(Clone, Debug, PartialEq)]
+pub enum RustTypeRef<'a> {
+    Null,
+    Boolean(bool),
+    String(Cow<'a, str>),
+    Integer(i128),
+    #[cfg(feature = "big_integer")]
+    BigInteger(BigInt),
+    Number(f64),
+    Decimal(Cow<'a, Decimal>),
+    List(Vec<RustTypeRef<'a>>),
+    Object(HashMap<Cow<'a, str>, RustTypeRef<'a>>),
+}
+
+impl<'a> RustTypeRef<'a> {
+    // Already owned strings are moved, so no additional allocation is performed for them
+    #[must_use]
+    pub fn into_owned(self) -> RustType {
+        match self {
+            Self::Null => RustType::Null,
+            Self::Boolean(value) => RustType::Boolean(value),
+            Self::String(value) => RustType::String(value.into_owned()),
+            Self::Integer(value) => RustType::Integer(value),
+            #[cfg(feature = "big_integer")]
+            Self::BigInteger(value) => RustType::BigInteger(value),
+            Self::Number(value) => RustType::Number(value),
+            Self::Decimal(value) => RustType::Decimal(value.into_owned()),
+            Self::List(value) => RustType::List(value.into_iter().map(Self::into_owned).collect()),
+            Self::Object(value) => RustType::Object(value.into_iter().map(|(key, value)| (key.into_owned(), value.into_owned())).collect()),
+        }
+    }
+}
+
+impl<'a> Default for RustTypeRef<'a> {
+    #[must_use]
+    fn default() -> Self {
+        Self::Null
+    }
+}
+
+impl<'a> From<()> for RustTypeRef<'a> {
+    #[must_use]
+    fn from(_: ()) -> Self {
+        Self::Null
+    }
+}
+
+impl<'a> From<bool> for RustTypeRef<'a> {
+    #[must_use]
+    fn from(value: bool) -> Self {
+        Self::Boolean(value)
+    }
+}
+
+impl<'a> From<&'a str> for RustTypeRef<'a> {
+    #[must_use]
+    fn from(value: &'a str) -> Self {
+        Self::String(Cow::Borrowed(value))
+    }
+}
+
+impl<'a> From<String> for RustTypeRef<'a> {
+    #[must_use]
+    fn from(value: String) -> Self {
+        Self::String(Cow::Owned(value))
+    }
+}
+
+impl<'a> From<Cow<'a, str>> for RustTypeRef<'a> {
+    #[must_use]
+    fn from(value: Cow<'a, str>) -> Self {
+        Self::String(value)
+    }
+}
+
+impl<'a> From<i32> for RustTypeRef<'a> {
+    #[must_use]
+    fn from(value: i32) -> Self {
+        Self::Integer(value.into())
+    }
+}
+
+impl<'a> From<i64> for RustTypeRef<'a> {
+    #[must_use]
+    fn from(value: i64) -> Self {
+        Self::Integer(value.into())
+    }
+}
+
+impl<'a> From<i128> for RustTypeRef<'a> {
+    #[must_use]
+    fn from(value: i128) -> Self {
+        Self::Integer(value)
+    }
+}
+
+impl<'a> From<u32> for RustTypeRef<'a> {
+    #[must_use]
+    fn from(value: u32) -> Self {
+        Self::Integer(value.into())
+    }
+}
+
+impl<'a> From<u64> for RustTypeRef<'a> {
+    #[must_use]
+    fn from(value: u64) -> Self {
+        Self::Integer(value.into())
+    }
+}
+
+#[cfg(feature = "big_integer")]
+impl<'a> From<BigInt> for RustTypeRef<'a> {
+    #[must_use]
+    fn from(value: BigInt) -> Self {
+        match (&value).try_into() {
+            Ok(integer) => Self::Integer(integer),
+            Err(_) => Self::BigInteger(value),
+        }
+    }
+}
+
+impl<'a> From<f32> for RustTypeRef<'a> {
+    #[must_use]
+    fn from(value: f32) -> Self {
+        Self::Number(value.into())
+    }
+}
+
+impl<'a> From<f64> for RustTypeRef<'a> {
+    #[must_use]
+    fn from(value: f64) -> Self {
+        Self::Number(value)
+    }
+}
+
+impl<'a> From<HashMap<Cow<'a, str>, RustTypeRef<'a>>> for RustTypeRef<'a> {
+    #[must_use]
+    fn from(value: HashMap<Cow<'a, str>, Self>) -> Self {
+        Self::Object(value)
+    }
+}
+
+impl<'a> From<Vec<RustTypeRef<'a>>> for RustTypeRef<'a> {
+    #[must_use]
+    fn from(value: Vec<Self>) -> Self {
+        Self::List(value)
+    }
+}
+
+// Borrow all the strings of an existing `RustType`
+impl<'a> From<&'a RustType> for RustTypeRef<'a> {
+    #[must_use]
+    fn from(value: &'a RustType) -> Self {
+        match value {
+            RustType::Null => Self::Null,
+            RustType::Boolean(value) => Self::Boolean(*value),
+            RustType::String(value) => Self::String(Cow::Borrowed(value)),
+            RustType::Integer(value) => Self::Integer(*value),
+            #[cfg(feature = "big_integer")]
+            RustType::BigInteger(value) => Self::BigInteger(value.clone()),
+            RustType::Number(value) => Self::Number(*value),
+            RustType::Decimal(value) => Self::Decimal(Cow::Borrowed(value)),
+            RustType::List(value) => Self::List(value.iter().map(Self::from).collect()),
+            RustType::Object(value) => Self::Object(value.iter().map(|(key, value)| (Cow::Borrowed(key.as_str()), Self::from(value))).collect()),
+        }
+    }
+}
+
+impl<'a> From<RustTypeRef<'a>> for RustType {
+    #[must_use]
+    fn from(value: RustTypeRef<'a>) -> Self {
+        value.into_owned()
+    }
+}
+
+impl<'a> ToRustType for RustTypeRef<'a> {
+    fn to_rust_type(&self) -> RustType {
+        match self {
+            Self::Null => RustType::Null,
+            Self::Boolean(value) => RustType::Boolean(*value),
+            Self::String(value) => RustType::String(value.to_string()),
+            Self::Integer(value) => RustType::Integer(*value),
+            #[cfg(feature = "big_integer")]
+            Self::BigInteger(value) => RustType::BigInteger(value.clone()),
+            Self::Number(value) => RustType::Number(*value),
+            Self::Decimal(value) => RustType::Decimal(Decimal::clone(value)),
+            Self::List(value) => RustType::List(value.iter().map(ToRustType::to_rust_type).collect()),
+            Self::Object(value) => RustType::Object(value.iter().map(|(key, value)| (key.to_string(), value.to_rust_type())).collect()),
+        }
+    }
+}
+
+impl<'a> JsonType for RustTypeRef<'a> {
+    #[must_use]
+    fn as_array<'json>(&'json self) -> Option<Box<dyn ExactSizeIterator<Item = &Self> + 'json>> {
+        if let Self::List(v) = self {
+            Some(Box::new(v.iter()))
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_boolean(&self) -> Option<bool> {
+        if let Self::Boolean(v) = self {
+            Some(*v)
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_integer(&self) -> Option<i128> {
+        match self {
+            Self::Integer(v) => Some(*v),
+            #[cfg(feature = "big_integer")]
+            Self::BigInteger(v) => v.try_into().ok(),
+            _ => None,
+        }
+    }
+
+    #[cfg(feature = "big_integer")]
+    #[must_use]
+    fn as_big_integer(&self) -> Option<BigInt> {
+        match self {
+            Self::Integer(v) => Some(BigInt::from(*v)),
+            Self::BigInteger(v) => Some(v.clone()),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    fn as_null(&self) -> Option<()> {
+        if let Self::Null = self {
+            Some(())
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_number(&self) -> Option<f64> {
+        match self {
+            Self::Number(v) => Some(*v),
+            Self::Decimal(v) => Some(v.as_f64()),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    fn as_number_str(&self) -> Option<Cow<str>> {
+        if let Self::Decimal(v) = self {
+            Some(Cow::Borrowed(v.as_str()))
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_object(&self) -> Option<JsonMap<Self>> {
+        if let Self::Object(_) = self {
+            Some(JsonMap::new(self))
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_string(&self) -> Option<&str> {
+        if let Self::String(s) = self {
+            Some(s)
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn get_attribute(&self, attribute_name: &str) -> Option<&Self> {
+        if let Self::Object(object) = self {
+            object.get(attribute_name)
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn get_index(&self, index: usize) -> Option<&Self> {
+        if let Self::List(array) = self {
+            array.get(index)
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a> ThreadSafeJsonType for RustTypeRef<'a> {}
+
+impl<'json, 'a> JsonMapTrait<'json, RustTypeRef<'a>> for JsonMap<'json, RustTypeRef<'a>> {
+    #[must_use]
+    fn items(&'json self) -> Box<dyn Iterator<Item = (&str, &RustTypeRef<'a>)> + 'json> {
+        if let RustTypeRef::Object(hash_map) = {
+            #[allow(clippy::explicit_deref_methods)] // Explicit deref call is needed to ensure that &RustTypeRef is retrieved from JsonMap
+            &self.deref()
+        } {
+            Box::new(hash_map.iter().map(|(k, v)| (k.as_ref(), v)))
+        } else {
+            Box::new(std::iter::empty())
+        }
+    }
+}
+
+#[cfg(test)]
+mod smoke_test {
+    use crate::{
+        json_type::{JsonMapTrait, JsonType, JsonTypeToString, PrimitiveType, ToRustType},
+        rust_type_impl::{Decimal, RustType},
+        rust_type_ref_impl::RustTypeRef,
+    };
+    use std::{borrow::Cow, collections::hash_map::HashMap};
+
+    lazy_static! {
+        static ref TESTING_RUST_TYPE: RustType = rust_type!({"key": "value", "list": [1, 2.3, null, true], "decimal": 0});
+    }
+
+    #[test]
+    fn test_from_rust_type_borrows_strings() {
+        let value = RustTypeRef::from(&*TESTING_RUST_TYPE);
+        if let Some(RustTypeRef::String(Cow::Borrowed(string))) = value.get_attribute("key") {
+            assert_eq!(string.as_ptr(), TESTING_RUST_TYPE.get_attribute("key").and_then(JsonType::as_string).unwrap().as_ptr());
+        } else {
+            panic!("Expected borrowed string, got {:?}", value.get_attribute("key"));
+        }
+        assert_eq!(value.to_rust_type(), *TESTING_RUST_TYPE);
+        assert_eq!(value.into_owned(), *TESTING_RUST_TYPE);
+    }
+
+    #[test]
+    fn test_json_type() {
+        let value = RustTypeRef::from(&*TESTING_RUST_TYPE);
+        assert_eq!(value.primitive_type(), PrimitiveType::Object);
+        assert_eq!(value.get_attribute("key").and_then(JsonType::as_string), Some("value"));
+        assert_eq!(value.get_attribute("not-present"), None);
+
+        let list = value.get_attribute("list").unwrap();
+        assert_eq!(
+            list.as_array().map(|iterator| iterator.map(JsonType::primitive_type).collect::<Vec<_>>()),
+            Some(vec![PrimitiveType::Integer, PrimitiveType::Number, PrimitiveType::Null, PrimitiveType::Boolean,])
+        );
+        assert_eq!(list.get_index(1).and_then(JsonType::as_number), Some(2.3));
+        assert_eq!(list.get_index(4), None);
+
+        let object = value.as_object().unwrap();
+        let mut keys = object.keys().collect::<Vec<_>>();
+        keys.sort_unstable();
+        assert_eq!(keys, vec!["decimal", "key", "list"]);
+    }
+
+    #[test]
+    fn test_decimal() {
+        let decimal = RustTypeRef::Decimal(Cow::Owned(Decimal::new("0.1000000000000000055511151231257827").unwrap()));
+        assert_eq!(decimal.primitive_type(), PrimitiveType::Number);
+        assert_eq!(decimal.as_number(), Some(0.1));
+        assert_eq!(decimal.as_number_str().as_deref(), Some("0.1000000000000000055511151231257827"));
+        assert_eq!(decimal.into_owned(), RustType::Decimal(Decimal::new("0.1000000000000000055511151231257827").unwrap()));
+    }
+
+    #[test]
+    fn test_into_owned_outlives_buffer() {
+        let owned = {
+            let buffer = String::from("borrowed");
+            let object: HashMap<_, _> = vec![(Cow::Borrowed(buffer.as_str()), RustTypeRef::from(buffer.as_str()))].into_iter().collect();
+            RustTypeRef::from(object).into_owned()
+        };
+        assert_eq!(owned, rust_type!({"borrowed": "borrowed"}));
+    }
+
+    #[test]
+    fn test_to_json_string() {
+        assert_eq!(
+            RustTypeRef::from(vec![RustTypeRef::from("a"), RustTypeRef::from(1), RustTypeRef::Null]).to_json_string(),
+            r#"["a",1,null]"#
+        );
+    }
+}
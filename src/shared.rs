@@ -0,0 +1,488 @@
+// Immutable, structurally shared JSON documents.
+//
+// Strings are `Arc<str>` while arrays and objects are persistent collections (`im::Vector` and `im::HashMap`), so
+// cloning a `SharedValue` is O(1) and clones can be freely sent to other threads.
+// Updates (`SharedValue::with_fragment` and `SharedValue::without_fragment`) return a new document: only the values
+// on the path towards the updated fragment are copied, the rest of the document is shared with the original one.
+use crate::{
+    convert::from_json_type,
+    error::Error,
+    fragment_helpers::{fragment_from_fragment_components, try_fragment_components_from_fragment},
+    json_builder::JsonBuilder,
+    json_type::{JsonMap, JsonMapTrait, JsonType, PrimitiveType, ThreadSafeJsonType, ToRustType},
+    rust_type_impl::{Decimal, RustType},
+};
+use im::{HashMap, Vector};
+#[cfg(feature = "big_integer")]
+use num_bigint::BigInt;
+#[cfg(feature = "big_integer")]
+use std::convert::TryInto;
+use std::{borrow::Cow, sync::Arc};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SharedValue {
+    Null,
+    Boolean(bool),
+    String(Arc<str>),
+    Integer(i128),
+    #[cfg(feature = "big_integer")]
+    BigInteger(Arc<BigInt>),
+    Number(f64),
+    Decimal(Arc<Decimal>),
+    List(Vector<SharedValue>),
+    Object(HashMap<Arc<str>, SharedValue>),
+}
+
+impl Default for SharedValue {
+    #[must_use]
+    fn default() -> Self {
+        Self::Null
+    }
+}
+
+impl SharedValue {
+    // Copy any `JsonType` into a `SharedValue`
+    /// # Errors
+    ///
+    /// Will return `Err` if `value` is not a valid JSON value (see `convert::from_json_type`)
+    pub fn from_json_type<T: JsonType>(value: &T) -> Result<Self, Error> {
+        from_json_type(value)
+    }
+
+    /// Copy of the document with the value referenced by `fragment` set to `value`.
+    /// The last component of `fragment` could refer to a missing object key (which is added) or to the end of an
+    /// array (its length or `-`, the value is appended).
+    ///
+    /// # Errors
+    /// * `Error::InvalidPointer` if the fragment is not a valid JSON pointer
+    /// * `Error::MissingKey` if an attribute, or an index, referenced by the fragment does not exist
+    /// * `Error::TypeMismatch` if the fragment traverses a value that is neither an object nor an array
+    pub fn with_fragment(&self, fragment: &str, value: Self) -> Result<Self, Error> {
+        let fragment_components = try_fragment_components_from_fragment(fragment)?;
+        self.updated(fragment, &fragment_components, 0, Some(value))
+    }
+
+    /// Copy of the document without the value referenced by `fragment`.
+    ///
+    /// # Errors
+    /// * `Error::InvalidPointer` if the fragment is not a valid JSON pointer or if it refers to the whole document
+    /// * `Error::MissingKey` if an attribute, or an index, referenced by the fragment does not exist
+    /// * `Error::TypeMismatch` if the fragment traverses a value that is neither an object nor an array
+    pub fn without_fragment(&self, fragment: &str) -> Result<Self, Error> {
+        let fragment_components = try_fragment_components_from_fragment(fragment)?;
+        if fragment_components.is_empty() {
+            return Err(Error::InvalidPointer {
+                pointer: fragment.to_string(),
+                reason: "the whole document cannot be removed".to_string(),
+            });
+        }
+        self.updated(fragment, &fragment_components, 0, None)
+    }
+
+    // Path copying update. `value` set to `None` removes the referenced value
+    fn updated(&self, fragment: &str, fragment_components: &[String], depth: usize, value: Option<Self>) -> Result<Self, Error> {
+        let fragment_part = match fragment_components.get(depth) {
+            Some(fragment_part) => fragment_part,
+            None => return Ok(value.unwrap_or_default()),
+        };
+        let is_last = depth + 1 == fragment_components.len();
+        let missing_key = || Error::MissingKey {
+            path: fragment_from_fragment_components(&fragment_components[..depth]),
+            key: fragment_part.clone(),
+        };
+
+        match self {
+            Self::Object(object) => match (object.get(fragment_part.as_str()), is_last, value) {
+                (_, true, Some(value)) => Ok(Self::Object(object.update(Arc::from(fragment_part.as_str()), value))),
+                (Some(_), true, None) => Ok(Self::Object(object.without(fragment_part.as_str()))),
+                (Some(child), false, value) => {
+                    let child = child.updated(fragment, fragment_components, depth + 1, value)?;
+                    Ok(Self::Object(object.update(Arc::from(fragment_part.as_str()), child)))
+                }
+                (None, _, _) => Err(missing_key()),
+            },
+            Self::List(list) => {
+                let index = if fragment_part == "-" {
+                    list.len()
+                } else {
+                    fragment_part.parse::<usize>().map_err(|_| Error::InvalidPointer {
+                        pointer: fragment.to_string(),
+                        reason: format!("`{}` is not a valid array index", fragment_part),
+                    })?
+                };
+                let mut list = list.clone();
+                match (index.cmp(&list.len()), is_last, value) {
+                    (std::cmp::Ordering::Equal, true, Some(value)) => list.push_back(value),
+                    (std::cmp::Ordering::Less, true, Some(value)) => {
+                        let _ = list.set(index, value);
+                    }
+                    (std::cmp::Ordering::Less, true, None) => {
+                        let _ = list.remove(index);
+                    }
+                    (std::cmp::Ordering::Less, false, value) => {
+                        let child = list[index].updated(fragment, fragment_components, depth + 1, value)?;
+                        let _ = list.set(index, child);
+                    }
+                    _ => return Err(missing_key()),
+                }
+                Ok(Self::List(list))
+            }
+            _ => Err(Error::TypeMismatch {
+                path: fragment_from_fragment_components(&fragment_components[..depth]),
+                expected: PrimitiveType::Object,
+                found: self.primitive_type(),
+            }),
+        }
+    }
+}
+
+impl From<&RustType> for SharedValue {
+    #[must_use]
+    fn from(value: &RustType) -> Self {
+        match value {
+            RustType::Null => Self::Null,
+            RustType::Boolean(value) => Self::Boolean(*value),
+            RustType::String(value) => Self::String(Arc::from(value.as_str())),
+            RustType::Integer(value) => Self::Integer(*value),
+            #[cfg(feature = "big_integer")]
+            RustType::BigInteger(value) => Self::BigInteger(Arc::new(value.clone())),
+            RustType::Number(value) => Self::Number(*value),
+            RustType::Decimal(value) => Self::Decimal(Arc::new(value.clone())),
+            RustType::List(items) => Self::List(items.iter().map(Self::from).collect()),
+            RustType::Object(object) => Self::Object(object.iter().map(|(key, value)| (Arc::from(key.as_str()), Self::from(value))).collect()),
+        }
+    }
+}
+
+impl ToRustType for SharedValue {
+    fn to_rust_type(&self) -> RustType {
+        match self {
+            Self::Null => RustType::Null,
+            Self::Boolean(value) => RustType::from(*value),
+            Self::String(value) => RustType::from(value.as_ref()),
+            Self::Integer(value) => RustType::from(*value),
+            #[cfg(feature = "big_integer")]
+            Self::BigInteger(value) => RustType::from(BigInt::clone(value)),
+            Self::Number(value) => RustType::from(*value),
+            Self::Decimal(value) => RustType::Decimal(Decimal::clone(value)),
+            Self::List(items) => RustType::from(items.iter().map(ToRustType::to_rust_type).collect::<Vec<_>>()),
+            Self::Object(object) => RustType::from(
+                object
+                    .iter()
+                    .map(|(key, value)| (key.to_string(), value.to_rust_type()))
+                    .collect::<std::collections::HashMap<_, _>>(),
+            ),
+        }
+    }
+}
+
+impl<'json> JsonMapTrait<'json, SharedValue> for JsonMap<'json, SharedValue> {
+    #[must_use]
+    fn items(&'json self) -> Box<dyn Iterator<Item = (&str, &SharedValue)> + 'json> {
+        if let SharedValue::Object(object) = &**self {
+            Box::new(object.iter().map(|(key, value)| (key.as_ref(), value)))
+        } else {
+            Box::new(std::iter::empty())
+        }
+    }
+}
+
+impl JsonType for SharedValue {
+    #[must_use]
+    fn as_array<'json>(&'json self) -> Option<Box<dyn ExactSizeIterator<Item = &Self> + 'json>> {
+        if let Self::List(items) = self {
+            Some(Box::new(items.iter()))
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_boolean(&self) -> Option<bool> {
+        if let Self::Boolean(value) = self {
+            Some(*value)
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_integer(&self) -> Option<i128> {
+        match self {
+            Self::Integer(value) => Some(*value),
+            #[cfg(feature = "big_integer")]
+            Self::BigInteger(value) => value.as_ref().try_into().ok(),
+            _ => None,
+        }
+    }
+
+    #[cfg(feature = "big_integer")]
+    #[must_use]
+    fn as_big_integer(&self) -> Option<BigInt> {
+        match self {
+            Self::Integer(value) => Some(BigInt::from(*value)),
+            Self::BigInteger(value) => Some(BigInt::clone(value)),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    fn as_null(&self) -> Option<()> {
+        if let Self::Null = self {
+            Some(())
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_number(&self) -> Option<f64> {
+        match self {
+            Self::Number(value) => Some(*value),
+            Self::Decimal(value) => Some(value.as_f64()),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    fn as_number_str(&self) -> Option<Cow<str>> {
+        if let Self::Decimal(value) = self {
+            Some(Cow::Borrowed(value.as_str()))
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_object(&self) -> Option<JsonMap<Self>> {
+        if let Self::Object(_) = self {
+            Some(JsonMap::new(self))
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_string(&self) -> Option<&str> {
+        if let Self::String(value) = self {
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn get_attribute(&self, attribute_name: &str) -> Option<&Self> {
+        if let Self::Object(object) = self {
+            object.get(attribute_name)
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn get_index(&self, index: usize) -> Option<&Self> {
+        if let Self::List(items) = self {
+            items.get(index)
+        } else {
+            None
+        }
+    }
+}
+
+impl ThreadSafeJsonType for SharedValue {}
+
+impl JsonBuilder for SharedValue {
+    fn null() -> Self {
+        Self::Null
+    }
+
+    fn bool(value: bool) -> Self {
+        Self::Boolean(value)
+    }
+
+    fn integer(value: i128) -> Result<Self, Error> {
+        Ok(Self::Integer(value))
+    }
+
+    #[cfg(feature = "big_integer")]
+    fn big_integer(value: &BigInt) -> Result<Self, Error> {
+        Ok(value.try_into().map_or_else(|_| Self::BigInteger(Arc::new(value.clone())), Self::Integer))
+    }
+
+    fn number(value: f64) -> Self {
+        Self::Number(value)
+    }
+
+    // The textual representation is preserved only if it cannot be losslessly represented by a f64
+    fn decimal(value: f64, number_str: &str) -> Self {
+        Decimal::new(number_str).map_or(Self::Number(value), |decimal| Self::Decimal(Arc::new(decimal)))
+    }
+
+    fn string(value: &str) -> Self {
+        Self::String(Arc::from(value))
+    }
+
+    fn array<I: IntoIterator<Item = Self>>(items: I) -> Self {
+        Self::List(items.into_iter().collect())
+    }
+
+    fn object<'key, I: IntoIterator<Item = (&'key str, Self)>>(items: I) -> Self {
+        Self::Object(items.into_iter().map(|(key, value)| (Arc::from(key), value)).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests_primitive_type_trait {
+    use super::SharedValue;
+    use crate::{
+        json_type::{JsonType, PrimitiveType},
+        rust_type_impl::{Decimal, RustType},
+    };
+    use test_case::test_case;
+
+    #[test_case(&rust_type!([]), PrimitiveType::Array)]
+    #[test_case(&rust_type!(true), PrimitiveType::Boolean)]
+    #[test_case(&rust_type!(1), PrimitiveType::Integer)]
+    #[test_case(&rust_type!(null), PrimitiveType::Null)]
+    #[test_case(&rust_type!(1.2), PrimitiveType::Number)]
+    #[test_case(&RustType::Decimal(Decimal::new("0.1000000000000000055511151231257827").unwrap()), PrimitiveType::Number ; "decimal")]
+    #[test_case(&rust_type!({"prop": "value"}), PrimitiveType::Object)]
+    #[test_case(&rust_type!("string"), PrimitiveType::String)]
+    fn test_primitive_type(value: &RustType, expected_value: PrimitiveType) {
+        assert_eq!(SharedValue::from(value).primitive_type(), expected_value);
+    }
+
+    #[test_case(&rust_type!({"present": 1}), "present", Some(&SharedValue::Integer(1)))]
+    #[test_case(&rust_type!({"present": 1}), "not-present", None)]
+    #[test_case(&rust_type!([1]), "0", None)]
+    fn test_get_attribute(value: &RustType, attribute_name: &str, expected_value: Option<&SharedValue>) {
+        assert_eq!(SharedValue::from(value).get_attribute(attribute_name), expected_value);
+    }
+
+    #[test_case(&rust_type!([0, 1, 2]), 1, Some(&SharedValue::Integer(1)))]
+    #[test_case(&rust_type!([0, 1, 2]), 4, None)]
+    fn test_get_index(value: &RustType, index: usize, expected_value: Option<&SharedValue>) {
+        assert_eq!(SharedValue::from(value).get_index(index), expected_value);
+    }
+
+    #[test_case(&rust_type!("1"), Some("1"))]
+    #[test_case(&rust_type!(1), None)]
+    fn test_as_string(value: &RustType, expected_value: Option<&str>) {
+        assert_eq!(SharedValue::from(value).as_string(), expected_value);
+    }
+}
+
+#[cfg(test)]
+mod tests_shared_value {
+    use super::SharedValue;
+    use crate::{
+        error::Error,
+        json_builder::JsonBuilder,
+        json_type::{JsonMapTrait, JsonType, PrimitiveType, ToRustType},
+        rust_type_impl::{Decimal, RustType},
+    };
+    use std::sync::Arc;
+    use test_case::test_case;
+
+    lazy_static! {
+        static ref TESTING_DOCUMENT: RustType = rust_type!({"db": {"host": "localhost", "port": 5432}, "replicas": ["a", "b"]});
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let value = SharedValue::from(&*TESTING_DOCUMENT);
+        assert_eq!(value.to_rust_type(), *TESTING_DOCUMENT);
+        let mut keys = value.as_object().unwrap().keys().map(str::to_string).collect::<Vec<_>>();
+        keys.sort();
+        assert_eq!(keys, vec!["db", "replicas"]);
+        assert_eq!(SharedValue::from_json_type(&*TESTING_DOCUMENT), Ok(value));
+    }
+
+    #[test]
+    fn test_decimals() {
+        let decimal = Decimal::new("0.1000000000000000055").unwrap();
+        assert_eq!(
+            SharedValue::from_json_type(&RustType::Decimal(decimal.clone())),
+            Ok(SharedValue::Decimal(Arc::new(decimal)))
+        );
+        assert_eq!(SharedValue::decimal(1.1, "1.10"), SharedValue::Number(1.1));
+    }
+
+    #[test]
+    fn test_clones_share_the_same_nodes() {
+        let value = SharedValue::from(&*TESTING_DOCUMENT);
+        let clone = value.clone();
+        match (value.get_attribute("db"), clone.get_attribute("db")) {
+            (Some(SharedValue::Object(original)), Some(SharedValue::Object(cloned))) => assert!(original.ptr_eq(cloned)),
+            values => panic!("Unexpected values {:?}", values),
+        }
+    }
+
+    #[test]
+    fn test_updates_copy_only_the_updated_path() {
+        let value = SharedValue::from(&*TESTING_DOCUMENT);
+        let updated = value.with_fragment("/db/port", SharedValue::Integer(6543)).unwrap();
+
+        assert_eq!(value.get_attribute("db").and_then(|db| db.get_attribute("port")), Some(&SharedValue::Integer(5432)));
+        assert_eq!(updated.get_attribute("db").and_then(|db| db.get_attribute("port")), Some(&SharedValue::Integer(6543)));
+        match (value.get_attribute("replicas"), updated.get_attribute("replicas")) {
+            (Some(SharedValue::List(original)), Some(SharedValue::List(updated))) => assert!(original.ptr_eq(updated)),
+            values => panic!("Unexpected values {:?}", values),
+        }
+        match (
+            value.get_attribute("db").and_then(|db| db.get_attribute("host")),
+            updated.get_attribute("db").and_then(|db| db.get_attribute("host")),
+        ) {
+            (Some(SharedValue::String(original)), Some(SharedValue::String(updated))) => assert!(Arc::ptr_eq(original, updated)),
+            values => panic!("Unexpected values {:?}", values),
+        }
+    }
+
+    #[test_case("", &rust_type!(true) ; "root")]
+    #[test_case("/db/user", &rust_type!({"db": {"host": "localhost", "port": 5432, "user": true}, "replicas": ["a", "b"]}) ; "new key")]
+    #[test_case("/replicas/0", &rust_type!({"db": {"host": "localhost", "port": 5432}, "replicas": [true, "b"]}) ; "array item")]
+    #[test_case("/replicas/-", &rust_type!({"db": {"host": "localhost", "port": 5432}, "replicas": ["a", "b", true]}) ; "array append")]
+    #[test_case("/replicas/2", &rust_type!({"db": {"host": "localhost", "port": 5432}, "replicas": ["a", "b", true]}) ; "array length")]
+    fn test_with_fragment(fragment: &str, expected_value: &RustType) {
+        let value = SharedValue::from(&*TESTING_DOCUMENT);
+        assert_eq!(
+            value.with_fragment(fragment, SharedValue::Boolean(true)).map(|updated| updated.to_rust_type()).as_ref(),
+            Ok(expected_value)
+        );
+    }
+
+    #[test_case("/db/port", Ok(rust_type!({"db": {"host": "localhost"}, "replicas": ["a", "b"]})) ; "object key")]
+    #[test_case("/replicas/0", Ok(rust_type!({"db": {"host": "localhost", "port": 5432}, "replicas": ["b"]})) ; "array item")]
+    #[test_case("/db/user", Err(Error::MissingKey { path: "/db".to_string(), key: "user".to_string() }) ; "missing key")]
+    #[test_case("/replicas/2", Err(Error::MissingKey { path: "/replicas".to_string(), key: "2".to_string() }) ; "missing index")]
+    #[test_case("", Err(Error::InvalidPointer { pointer: String::new(), reason: "the whole document cannot be removed".to_string() }) ; "root")]
+    fn test_without_fragment(fragment: &str, expected_value: Result<RustType, Error>) {
+        let value = SharedValue::from(&*TESTING_DOCUMENT);
+        assert_eq!(value.without_fragment(fragment).map(|updated| updated.to_rust_type()), expected_value);
+    }
+
+    #[test_case("/missing/key", Error::MissingKey { path: String::new(), key: "missing".to_string() } ; "missing intermediate key")]
+    #[test_case("/db/port/value", Error::TypeMismatch { path: "/db/port".to_string(), expected: PrimitiveType::Object, found: PrimitiveType::Integer } ; "scalar traversal")]
+    #[test_case("/replicas/first", Error::InvalidPointer { pointer: "/replicas/first".to_string(), reason: "`first` is not a valid array index".to_string() } ; "invalid index")]
+    #[test_case("/replicas/3", Error::MissingKey { path: "/replicas".to_string(), key: "3".to_string() } ; "index out of range")]
+    #[test_case("db", Error::InvalidPointer { pointer: "db".to_string(), reason: "a non empty pointer has to start with `/`".to_string() } ; "invalid pointer")]
+    fn test_with_fragment_errors(fragment: &str, expected_error: Error) {
+        let value = SharedValue::from(&*TESTING_DOCUMENT);
+        assert_eq!(value.with_fragment(fragment, SharedValue::Null), Err(expected_error));
+    }
+
+    #[test]
+    fn test_clones_are_usable_from_other_threads() {
+        let value = SharedValue::from(&*TESTING_DOCUMENT);
+        let handles = (0..4)
+            .map(|_| {
+                let value = value.clone();
+                std::thread::spawn(move || value.get_attribute("db").and_then(|db| db.get_attribute("port")).and_then(JsonType::as_integer))
+            })
+            .collect::<Vec<_>>();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), Some(5432));
+        }
+    }
+}
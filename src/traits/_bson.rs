@@ -0,0 +1,545 @@
+// BSON to JSON mapping
+// The mapping follows MongoDB Extended JSON v2 (https://docs.mongodb.com/manual/reference/mongodb-extended-json/).
+// JSON native types (double, string, array, document, boolean, null, int32 and int64) are exposed as they are, while
+// BSON specific types are exposed via the payload of their relaxed Extended JSON v2 wrapper
+// * ObjectId (`{"$oid": "<hex>"}`) is reported as string (24 hexadecimal characters)
+// * Date (`{"$date": "<ISO-8601>"}`) is reported as RFC 3339 string (or as integer, milliseconds since epoch, if the
+//   date is not representable as RFC 3339 string)
+// * Decimal128 (`{"$numberDecimal": "<decimal>"}`) is reported as number, preserving the exact decimal representation
+//   (`NaN` and infinities, which have no JSON counterpart, are reported as null)
+// * Binary (`{"$binary": {"base64": "<payload>", "subType": "<subtype>"}}`) has no JSON counterpart. As for MessagePack
+//   binary blobs, it is omitted from documents while it is reported as `null` where omitting it would alter the
+//   document structure (array items or root value). `binary_as_base64` exposes its base64 (with padding) payload
+// * Timestamp (`{"$timestamp": {"t": <t>, "i": <i>}}`) is reported as integer (`t << 32 | i`)
+// * Regular expression is reported as string (`/<pattern>/<options>`), JavaScript code and symbol as string
+// * Undefined, MinKey, MaxKey and DBPointer (deprecated or internal types) are reported as null
+// As for TOML datetimes, the strings of ObjectIds, dates and regular expressions are not stored in the BSON value, so
+// they are exposed by `JsonType::as_string_cow` only (`JsonType::as_string` returns `None` for them).
+// The raw values are accessible via the `bson::Bson` accessors.
+// The full Extended JSON v2 representation (both canonical and relaxed) is available via `to_extended_json`.
+use crate::{
+    error::Error,
+    json_builder::{numeric_overflow, JsonBuilder},
+    json_type::{JsonMap, JsonMapTrait, JsonType, PrimitiveType, ThreadSafeJsonType, ToRustType},
+    rust_type_impl::RustType,
+};
+use bson::{Binary, Bson, Decimal128, Regex, Timestamp};
+#[cfg(feature = "big_integer")]
+use num_bigint::BigInt;
+use std::{borrow::Cow, collections::HashMap, convert::TryFrom};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExtendedJsonMode {
+    Canonical,
+    Relaxed,
+}
+
+// Convert a BSON value into its Extended JSON v2 representation
+#[must_use]
+pub fn to_extended_json(value: &Bson, mode: ExtendedJsonMode) -> serde_json::Value {
+    match mode {
+        ExtendedJsonMode::Canonical => value.clone().into_canonical_extjson(),
+        ExtendedJsonMode::Relaxed => value.clone().into_relaxed_extjson(),
+    }
+}
+
+fn timestamp_as_integer(timestamp: Timestamp) -> i128 {
+    i128::from(u64::from(timestamp.time) << 32 | u64::from(timestamp.increment))
+}
+
+// Base64 (with padding) payload of a binary, as in its Extended JSON v2 representation
+#[must_use]
+pub fn binary_as_base64(binary: &Binary) -> String {
+    match Bson::Binary(binary.clone()).into_relaxed_extjson() {
+        serde_json::Value::Object(mut object) => match object.remove("$binary") {
+            Some(serde_json::Value::Object(mut payload)) => match payload.remove("base64") {
+                Some(serde_json::Value::String(base64)) => base64,
+                _ => String::new(),
+            },
+            _ => String::new(),
+        },
+        _ => String::new(),
+    }
+}
+
+fn regex_as_string(regex: &Regex) -> String {
+    format!("/{}/{}", regex.pattern, regex.options)
+}
+
+fn is_finite_decimal(decimal: &Decimal128) -> bool {
+    !matches!(decimal.to_string().trim_start_matches('-'), "NaN" | "Infinity")
+}
+
+fn is_null(value: &Bson) -> bool {
+    match value {
+        Bson::Null | Bson::Undefined | Bson::MaxKey | Bson::MinKey | Bson::DbPointer(_) | Bson::Binary(_) => true,
+        Bson::Decimal128(decimal) => !is_finite_decimal(decimal),
+        _ => false,
+    }
+}
+
+impl Into<RustType> for Bson {
+    fn into(self) -> RustType {
+        self.to_rust_type()
+    }
+}
+
+impl ToRustType for Bson {
+    fn to_rust_type(&self) -> RustType {
+        match self {
+            Self::Double(value) => RustType::from(*value),
+            Self::String(value) | Self::Symbol(value) | Self::JavaScriptCode(value) => RustType::from(value.as_str()),
+            Self::JavaScriptCodeWithScope(value) => RustType::from(value.code.as_str()),
+            Self::Array(array) => RustType::from(array.iter().map(ToRustType::to_rust_type).collect::<Vec<_>>()),
+            Self::Document(document) => RustType::from(
+                document
+                    .iter()
+                    .filter(|(_, value)| !matches!(value, Self::Binary(_)))
+                    .map(|(key, value)| (key.clone(), value.to_rust_type()))
+                    .collect::<HashMap<_, _>>(),
+            ),
+            Self::Boolean(value) => RustType::from(*value),
+            Self::Int32(value) => RustType::from(*value),
+            Self::Int64(value) => RustType::from(*value),
+            Self::Timestamp(value) => RustType::from(timestamp_as_integer(*value)),
+            Self::ObjectId(value) => RustType::from(value.to_hex()),
+            Self::DateTime(value) => value.try_to_rfc3339_string().map_or_else(|_| RustType::from(value.timestamp_millis()), RustType::from),
+            Self::Decimal128(value) if is_finite_decimal(value) => {
+                let number_str = value.to_string();
+                number_str.parse().map_or(RustType::Null, |number| RustType::decimal(number, &number_str))
+            }
+            Self::RegularExpression(value) => RustType::from(regex_as_string(value)),
+            Self::Null | Self::Undefined | Self::MaxKey | Self::MinKey | Self::DbPointer(_) | Self::Binary(_) | Self::Decimal128(_) => RustType::Null,
+        }
+    }
+}
+
+impl<'json> JsonMapTrait<'json, Bson> for JsonMap<'json, Bson> {
+    #[must_use]
+    fn keys(&'json self) -> Box<dyn Iterator<Item = &str> + 'json> {
+        #[allow(clippy::option_if_let_else)]
+        if let Some(document) = self.as_document() {
+            Box::new(document.iter().filter(|(_, value)| !matches!(value, Bson::Binary(_))).map(|(key, _)| key.as_str()))
+        } else {
+            Box::new(std::iter::empty())
+        }
+    }
+
+    #[must_use]
+    fn values(&'json self) -> Box<dyn Iterator<Item = &Bson> + 'json> {
+        #[allow(clippy::option_if_let_else)]
+        if let Some(document) = self.as_document() {
+            Box::new(document.values().filter(|value| !matches!(value, Bson::Binary(_))))
+        } else {
+            Box::new(std::iter::empty())
+        }
+    }
+
+    #[must_use]
+    fn items(&'json self) -> Box<dyn Iterator<Item = (&str, &Bson)> + 'json> {
+        #[allow(clippy::option_if_let_else)]
+        if let Some(document) = self.as_document() {
+            Box::new(
+                document
+                    .iter()
+                    .filter(|(_, value)| !matches!(value, Bson::Binary(_)))
+                    .map(|(key, value)| (key.as_str(), value)),
+            )
+        } else {
+            Box::new(std::iter::empty())
+        }
+    }
+}
+
+impl JsonType for Bson {
+    #[must_use]
+    fn as_array<'json>(&'json self) -> Option<Box<dyn ExactSizeIterator<Item = &Self> + 'json>> {
+        self.as_array().map(|vec| {
+            let b: Box<dyn ExactSizeIterator<Item = _>> = Box::new(vec.iter());
+            b
+        })
+    }
+
+    #[must_use]
+    fn as_boolean(&self) -> Option<bool> {
+        self.as_bool()
+    }
+
+    #[must_use]
+    fn as_integer(&self) -> Option<i128> {
+        match self {
+            Self::Int32(value) => Some(i128::from(*value)),
+            Self::Int64(value) => Some(i128::from(*value)),
+            Self::Timestamp(value) => Some(timestamp_as_integer(*value)),
+            Self::DateTime(value) if value.try_to_rfc3339_string().is_err() => Some(i128::from(value.timestamp_millis())),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    fn as_null(&self) -> Option<()> {
+        if is_null(self) {
+            Some(())
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_number(&self) -> Option<f64> {
+        match self {
+            Self::Double(value) => Some(*value),
+            Self::Decimal128(value) if is_finite_decimal(value) => value.to_string().parse().ok(),
+            #[allow(clippy::cast_precision_loss)]
+            _ => self.as_integer().map(|value| value as f64),
+        }
+    }
+
+    #[must_use]
+    fn as_number_str(&self) -> Option<Cow<str>> {
+        match self {
+            Self::Decimal128(value) if is_finite_decimal(value) => Some(Cow::Owned(value.to_string())),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    fn as_object(&self) -> Option<JsonMap<Self>> {
+        if let Self::Document(_) = self {
+            Some(JsonMap::new(self))
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_string(&self) -> Option<&str> {
+        match self {
+            Self::String(value) | Self::Symbol(value) | Self::JavaScriptCode(value) => Some(value),
+            Self::JavaScriptCodeWithScope(value) => Some(&value.code),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    fn as_string_cow(&self) -> Option<Cow<str>> {
+        match self {
+            Self::ObjectId(value) => Some(Cow::Owned(value.to_hex())),
+            Self::DateTime(value) => value.try_to_rfc3339_string().ok().map(Cow::Owned),
+            Self::RegularExpression(value) => Some(Cow::Owned(regex_as_string(value))),
+            _ => self.as_string().map(Cow::Borrowed),
+        }
+    }
+
+    #[must_use]
+    fn get_attribute(&self, attribute_name: &str) -> Option<&Self> {
+        self.as_document()
+            .and_then(|document| document.get(attribute_name))
+            .filter(|value| !matches!(value, Self::Binary(_)))
+    }
+
+    #[must_use]
+    fn get_index(&self, index: usize) -> Option<&Self> {
+        self.as_array().and_then(|array| array.get(index))
+    }
+
+    #[must_use]
+    fn is_string(&self) -> bool {
+        match self {
+            Self::String(_) | Self::Symbol(_) | Self::JavaScriptCode(_) | Self::JavaScriptCodeWithScope(_) | Self::ObjectId(_) | Self::RegularExpression(_) => true,
+            Self::DateTime(value) => value.try_to_rfc3339_string().is_ok(),
+            _ => false,
+        }
+    }
+
+    #[must_use]
+    fn primitive_type(&self) -> PrimitiveType {
+        match self {
+            Self::Array(_) => PrimitiveType::Array,
+            Self::Boolean(_) => PrimitiveType::Boolean,
+            Self::Int32(_) | Self::Int64(_) | Self::Timestamp(_) => PrimitiveType::Integer,
+            Self::Decimal128(value) if is_finite_decimal(value) => PrimitiveType::Number,
+            Self::Null | Self::Undefined | Self::MaxKey | Self::MinKey | Self::DbPointer(_) | Self::Binary(_) | Self::Decimal128(_) => PrimitiveType::Null,
+            Self::Double(_) => PrimitiveType::Number,
+            Self::Document(_) => PrimitiveType::Object,
+            Self::DateTime(value) if value.try_to_rfc3339_string().is_err() => PrimitiveType::Integer,
+            Self::String(_) | Self::Symbol(_) | Self::JavaScriptCode(_) | Self::JavaScriptCodeWithScope(_) | Self::ObjectId(_) | Self::RegularExpression(_) | Self::DateTime(_) => {
+                PrimitiveType::String
+            }
+        }
+    }
+}
+
+impl ThreadSafeJsonType for Bson {}
+
+// Integers are stored as int32 if possible, as int64 otherwise (up to `(2^63)-1`)
+impl JsonBuilder for Bson {
+    fn null() -> Self {
+        Self::Null
+    }
+
+    fn bool(value: bool) -> Self {
+        Self::Boolean(value)
+    }
+
+    fn integer(value: i128) -> Result<Self, Error> {
+        i32::try_from(value)
+            .map(Self::Int32)
+            .or_else(|_| i64::try_from(value).map(Self::Int64))
+            .map_err(|_| numeric_overflow(&value, "BSON integer"))
+    }
+
+    #[cfg(feature = "big_integer")]
+    fn big_integer(value: &BigInt) -> Result<Self, Error> {
+        Err(numeric_overflow(value, "BSON integer"))
+    }
+
+    fn number(value: f64) -> Self {
+        Self::Double(value)
+    }
+
+    fn string(value: &str) -> Self {
+        Self::String(value.to_string())
+    }
+
+    fn array<I: IntoIterator<Item = Self>>(items: I) -> Self {
+        Self::Array(items.into_iter().collect())
+    }
+
+    fn object<'key, I: IntoIterator<Item = (&'key str, Self)>>(items: I) -> Self {
+        Self::Document(items.into_iter().map(|(key, value)| (key.to_string(), value)).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests_bson_map_trait {
+    use crate::json_type::{JsonMap, JsonMapTrait};
+    use bson::{bson, Bson};
+
+    lazy_static! {
+        static ref TESTING_MAP: Bson = bson!({"k1": "v1", "k2": "v2"});
+    }
+
+    #[test]
+    fn keys() {
+        let testing_map: &Bson = &TESTING_MAP;
+        assert_eq!(JsonMap::new(testing_map).keys().collect::<Vec<_>>(), vec!["k1", "k2"]);
+    }
+
+    #[test]
+    fn values() {
+        let testing_map: &Bson = &TESTING_MAP;
+        assert_eq!(JsonMap::new(testing_map).values().collect::<Vec<_>>(), vec![&bson!("v1"), &bson!("v2")]);
+    }
+
+    #[test]
+    fn items() {
+        let testing_map: &Bson = &TESTING_MAP;
+        assert_eq!(JsonMap::new(testing_map).items().collect::<Vec<_>>(), vec![("k1", &bson!("v1")), ("k2", &bson!("v2"))]);
+    }
+}
+
+#[cfg(test)]
+mod tests_primitive_type_trait {
+    use crate::json_type::{JsonType, PrimitiveType};
+    use bson::{bson, oid::ObjectId, spec::BinarySubtype, Binary, Bson, DateTime, Decimal128, Regex};
+    use std::ops::Deref;
+    use test_case::test_case;
+
+    #[test_case(&bson!([]), PrimitiveType::Array)]
+    #[test_case(&bson!(true), PrimitiveType::Boolean)]
+    #[test_case(&bson!(1), PrimitiveType::Integer)]
+    #[test_case(&bson!(null), PrimitiveType::Null)]
+    #[test_case(&bson!(1.2), PrimitiveType::Number)]
+    #[test_case(&bson!({"prop": "value"}), PrimitiveType::Object)]
+    #[test_case(&bson!("string"), PrimitiveType::String)]
+    #[test_case(&Bson::ObjectId(ObjectId::new()), PrimitiveType::String)]
+    #[test_case(&Bson::DateTime(DateTime::from_millis(0)), PrimitiveType::String)]
+    #[test_case(&Bson::DateTime(DateTime::MAX), PrimitiveType::Integer)]
+    #[test_case(&Bson::Decimal128("1.5".parse::<Decimal128>().unwrap()), PrimitiveType::Number)]
+    #[test_case(&Bson::Decimal128("NaN".parse::<Decimal128>().unwrap()), PrimitiveType::Null ; "decimal128 nan")]
+    #[test_case(&Bson::Decimal128("-Infinity".parse::<Decimal128>().unwrap()), PrimitiveType::Null ; "decimal128 negative infinity")]
+    #[test_case(&Bson::Binary(Binary { subtype: BinarySubtype::Generic, bytes: b"foo".to_vec() }), PrimitiveType::Null ; "binary")]
+    #[test_case(&Bson::MinKey, PrimitiveType::Null)]
+    fn test_primitive_type(value: &Bson, expected_value: PrimitiveType) {
+        assert_eq!(JsonType::primitive_type(value), expected_value);
+    }
+
+    #[test_case(&bson!({"present": 1}), "present", Some(&bson!(1)))]
+    #[test_case(&bson!({"present": 1}), "not-present", None)]
+    #[test_case(&bson!({"binary": (Bson::Binary(Binary { subtype: BinarySubtype::Generic, bytes: b"foo".to_vec() }))}), "binary", None)]
+    fn test_get_attribute(value: &Bson, attribute_name: &str, expected_value: Option<&Bson>) {
+        assert_eq!(JsonType::get_attribute(value, attribute_name), expected_value);
+    }
+
+    #[test_case(&bson!([0, 1, 2]), 1, &Some(bson!(1)))]
+    #[test_case(&bson!([0, 1, 2]), 4, &None)]
+    fn test_get_index(value: &Bson, index: usize, expected_value: &Option<Bson>) {
+        assert_eq!(JsonType::get_index(value, index), expected_value.as_ref());
+    }
+
+    #[test_case(&bson!({"present": 1}), "present", true)]
+    #[test_case(&bson!({"present": 1}), "not-present", false)]
+    #[test_case(&bson!([1, 2, 3]), "not-present", false)]
+    fn test_has_attribute(value: &Bson, attr_name: &str, expected_value: bool) {
+        assert_eq!(JsonType::has_attribute(value, attr_name), expected_value);
+    }
+
+    #[test_case(&bson!([1]), &Some(vec![bson!(1)]))]
+    #[test_case(&bson!([1, "a"]), &Some(vec![bson!(1), bson!("a")]))]
+    #[test_case(&bson!(null), &None)]
+    fn test_as_array(value: &Bson, expected_value: &Option<Vec<Bson>>) {
+        assert_eq!(&JsonType::as_array(value).map(|iterator| iterator.cloned().collect()), expected_value);
+    }
+
+    #[test_case(&bson!(true), Some(true))]
+    #[test_case(&bson!(false), Some(false))]
+    #[test_case(&bson!(1), None)]
+    fn test_as_boolean(value: &Bson, expected_value: Option<bool>) {
+        assert_eq!(JsonType::as_boolean(value), expected_value);
+    }
+
+    #[test_case(&bson!(1), Some(1))]
+    #[test_case(&bson!(-1), Some(-1) ; "negative integer")]
+    #[test_case(&bson!(i64::max_value()), Some(i128::from(i64::max_value())) ; "int64")]
+    #[test_case(&bson!(1.2), None)]
+    #[test_case(&bson!("1"), None)]
+    fn test_as_integer(value: &Bson, expected_value: Option<i128>) {
+        assert_eq!(JsonType::as_integer(value), expected_value);
+    }
+
+    #[test_case(&bson!(null), Some(()))]
+    #[test_case(&Bson::Undefined, Some(()))]
+    #[test_case(&bson!("1"), None)]
+    fn test_as_null(value: &Bson, expected_value: Option<()>) {
+        assert_eq!(JsonType::as_null(value), expected_value);
+    }
+
+    #[test_case(&bson!(1), Some(1_f64))]
+    #[test_case(&bson!(1.2), Some(1.2))]
+    #[test_case(&Bson::Decimal128("1.5".parse::<Decimal128>().unwrap()), Some(1.5))]
+    #[test_case(&Bson::Decimal128("Infinity".parse::<Decimal128>().unwrap()), None)]
+    #[test_case(&bson!("1"), None)]
+    fn test_as_number(value: &Bson, expected_value: Option<f64>) {
+        assert_eq!(JsonType::as_number(value), expected_value);
+    }
+
+    #[test_case(&bson!(1), &None)]
+    #[test_case(&bson!(1.2), &None)]
+    #[test_case(&bson!({"1": 1}), &Some(bson!({"1": 1})))]
+    fn test_as_object(value: &Bson, expected_value: &Option<Bson>) {
+        assert_eq!(
+            match JsonType::as_object(value) {
+                Some(ref v) => Some({
+                    #[allow(clippy::explicit_deref_methods)] // Explicit deref call is needed to ensure that &Bson is retrieved from JsonMap
+                    v.deref()
+                }),
+                None => None,
+            },
+            expected_value.as_ref(),
+        );
+    }
+
+    #[test_case(&bson!(1), None)]
+    #[test_case(&bson!(1.2), None)]
+    #[test_case(&bson!("1"), Some("1"))]
+    #[test_case(&Bson::Symbol("symbol".to_string()), Some("symbol"))]
+    #[test_case(&Bson::ObjectId(ObjectId::parse_str("5f1a5d6b8e2b4a0b9c0d1e2f").unwrap()), None)]
+    #[test_case(&Bson::Binary(Binary { subtype: BinarySubtype::Generic, bytes: b"foo".to_vec() }), None)]
+    fn test_as_string(value: &Bson, expected_value: Option<&str>) {
+        assert_eq!(JsonType::as_string(value), expected_value);
+    }
+
+    #[test_case(&bson!(1), None)]
+    #[test_case(&bson!("1"), Some("1"))]
+    #[test_case(&Bson::Symbol("symbol".to_string()), Some("symbol"))]
+    #[test_case(&Bson::ObjectId(ObjectId::parse_str("5f1a5d6b8e2b4a0b9c0d1e2f").unwrap()), Some("5f1a5d6b8e2b4a0b9c0d1e2f"))]
+    #[test_case(&Bson::DateTime(DateTime::from_millis(1_363_896_240_500)), Some("2013-03-21T20:04:00.5Z"))]
+    #[test_case(&Bson::DateTime(DateTime::MAX), None)]
+    #[test_case(&Bson::RegularExpression(Regex { pattern: "^a".to_string(), options: "i".to_string() }), Some("/^a/i"))]
+    #[test_case(&Bson::Binary(Binary { subtype: BinarySubtype::Generic, bytes: b"foo".to_vec() }), None)]
+    fn test_as_string_cow(value: &Bson, expected_value: Option<&str>) {
+        assert_eq!(value.as_string_cow().as_deref(), expected_value);
+    }
+}
+
+#[cfg(test)]
+mod tests_extended_json {
+    use super::{binary_as_base64, to_extended_json, ExtendedJsonMode};
+    use crate::{
+        convert::from_json_type,
+        json_type::ToRustType,
+        rust_type_impl::{Decimal, RustType},
+    };
+    use bson::{bson, oid::ObjectId, spec::BinarySubtype, Binary, Bson, DateTime, Decimal128, Regex, Timestamp};
+    use test_case::test_case;
+
+    lazy_static! {
+        static ref OBJECT_ID: ObjectId = ObjectId::parse_str("5f1a5d6b8e2b4a0b9c0d1e2f").unwrap();
+        static ref BINARY: Bson = Bson::Binary(Binary {
+            subtype: BinarySubtype::Generic,
+            bytes: b"foo".to_vec(),
+        });
+    }
+
+    #[test_case(&Bson::ObjectId(*OBJECT_ID), RustType::from("5f1a5d6b8e2b4a0b9c0d1e2f") ; "object id")]
+    #[test_case(&Bson::DateTime(DateTime::from_millis(1_363_896_240_500)), RustType::from("2013-03-21T20:04:00.5Z") ; "date")]
+    #[test_case(&Bson::DateTime(DateTime::MAX), RustType::from(i64::max_value()) ; "date out of RFC 3339 range")]
+    #[test_case(&Bson::Decimal128("0.1".parse::<Decimal128>().unwrap()), RustType::from(0.1) ; "decimal128")]
+    #[test_case(&Bson::Decimal128("0.1000000000000000055".parse::<Decimal128>().unwrap()), RustType::Decimal(Decimal::new("0.1000000000000000055").unwrap()) ; "inexact decimal128")]
+    #[test_case(&Bson::Decimal128("NaN".parse::<Decimal128>().unwrap()), RustType::Null ; "decimal128 nan")]
+    #[test_case(&Bson::Decimal128("-Infinity".parse::<Decimal128>().unwrap()), RustType::Null ; "decimal128 negative infinity")]
+    #[test_case(&BINARY, RustType::Null ; "binary")]
+    #[test_case(&bson!({"binary": (BINARY.clone()), "array": [(BINARY.clone())]}), rust_type!({"array": [null]}) ; "binary in document")]
+    #[test_case(&Bson::Timestamp(Timestamp { time: 1, increment: 2 }), RustType::from((1_i128 << 32) + 2) ; "timestamp")]
+    #[test_case(&Bson::RegularExpression(Regex { pattern: "^a".to_string(), options: "i".to_string() }), RustType::from("/^a/i") ; "regular expression")]
+    #[test_case(&Bson::MaxKey, RustType::Null ; "max key")]
+    fn test_to_rust_type(value: &Bson, expected_value: RustType) {
+        assert_eq!(value.to_rust_type(), expected_value);
+        assert_eq!(from_json_type::<RustType, _>(value), Ok(expected_value));
+    }
+
+    #[test]
+    fn test_binary_as_base64() {
+        let binary = Binary {
+            subtype: BinarySubtype::Generic,
+            bytes: b"foo".to_vec(),
+        };
+        assert_eq!(binary_as_base64(&binary), "Zm9v");
+    }
+
+    #[test_case(&Bson::ObjectId(*OBJECT_ID), ExtendedJsonMode::Canonical, json!({"$oid": "5f1a5d6b8e2b4a0b9c0d1e2f"}))]
+    #[test_case(&Bson::DateTime(DateTime::from_millis(0)), ExtendedJsonMode::Canonical, json!({"$date": {"$numberLong": "0"}}))]
+    #[test_case(&Bson::DateTime(DateTime::from_millis(0)), ExtendedJsonMode::Relaxed, json!({"$date": "1970-01-01T00:00:00Z"}))]
+    #[test_case(&Bson::Decimal128("0.1".parse::<Decimal128>().unwrap()), ExtendedJsonMode::Relaxed, json!({"$numberDecimal": "0.1"}))]
+    #[test_case(&BINARY, ExtendedJsonMode::Relaxed, json!({"$binary": {"base64": "Zm9v", "subType": "00"}}))]
+    #[test_case(&Bson::Int32(1), ExtendedJsonMode::Canonical, json!({"$numberInt": "1"}))]
+    #[test_case(&Bson::Int32(1), ExtendedJsonMode::Relaxed, json!(1))]
+    fn test_to_extended_json(value: &Bson, mode: ExtendedJsonMode, expected_value: serde_json::Value) {
+        assert_eq!(to_extended_json(value, mode), expected_value);
+    }
+}
+
+#[cfg(test)]
+mod tests_to_json_string {
+    use crate::json_type::JsonTypeToString;
+    use bson::{bson, Bson, DateTime};
+
+    #[test]
+    fn smoke_test() {
+        let value = bson!([
+            {"array": []},
+            {"boolean": false},
+            {"date": (Bson::DateTime(DateTime::from_millis(0)))},
+            {"float": 2.3},
+            {"integer": 1},
+            {"null": null},
+            {"object": {}},
+            {"string": "string"},
+        ]);
+        assert_eq!(
+            value.to_json_string(),
+            r#"[{"array":[]},{"boolean":false},{"date":"1970-01-01T00:00:00Z"},{"float":2.3},{"integer":1},{"null":null},{"object":{}},{"string":"string"}]"#
+        );
+    }
+}
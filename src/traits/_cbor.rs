@@ -0,0 +1,754 @@
+// CBOR to JSON mapping (inspired by RFC 8949 section 6.1)
+// * Tags without a dedicated representation are transparent: the enclosed data item is exposed instead
+//   (ie. tag 0, standard date/time string, is exposed as the enclosed text string)
+// * Bignums (tags 2 and 3) are reported as integers if they fit into `i128` (or always, if `big_integer` feature is enabled)
+//   otherwise they are reported as (lossy) numbers
+// * Epoch-based date/time (tag 1) is reported as its RFC 3339 representation (UTC). As the string is not stored
+//   in the CBOR value, it is exposed by `JsonType::as_string_cow` only (`JsonType::as_string` returns `None`)
+// * Byte strings have no JSON counterpart. As for MessagePack binary blobs, they are omitted from objects while they
+//   are reported as `null` where omitting them would alter the document structure (array items or root value).
+//   `bytes_as_string` converts them as suggested by RFC 8949 (base64url without padding, or base64 with padding
+//   or lowercase base16 if they are enclosed by expected conversion tags 22 and 23)
+// * Map entries are exposed only if the key is a text string
+// The raw data items are accessible via `ciborium::value::Value::as_tag`, `as_bytes` and `as_map`.
+use crate::{
+    convert::from_json_type,
+    error::Error,
+    json_builder::JsonBuilder,
+    json_type::{JsonMap, JsonMapTrait, JsonType, PrimitiveType, ThreadSafeJsonType, ToRustType},
+    rust_type_impl::RustType,
+};
+use ciborium::value::{Integer, Value};
+#[cfg(feature = "big_integer")]
+use num_bigint::{BigInt, Sign};
+use std::{borrow::Cow, collections::HashMap, convert::TryFrom};
+
+const EPOCH_DATE_TIME_TAG: u64 = 1;
+const POSITIVE_BIGNUM_TAG: u64 = 2;
+const NEGATIVE_BIGNUM_TAG: u64 = 3;
+const EXPECTED_BASE64_TAG: u64 = 22;
+const EXPECTED_BASE16_TAG: u64 = 23;
+
+const BASE64_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const BASE64URL_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+#[derive(Clone, Copy)]
+enum BytesEncoding {
+    Base16,
+    Base64,
+    Base64Url,
+}
+
+// JSON view of a CBOR data item
+enum Item<'json> {
+    Plain(&'json Value),
+    Bignum { negative: bool, magnitude: &'json [u8] },
+    Bytes(BytesEncoding, &'json [u8]),
+    EpochDateTime { seconds: i64, nanoseconds: u32 },
+}
+
+fn epoch_date_time(value: &Value) -> Option<(i64, u32)> {
+    match value {
+        Value::Integer(integer) => i64::try_from(*integer).ok().map(|seconds| (seconds, 0)),
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss, clippy::cast_precision_loss)]
+        Value::Float(float) if float.is_finite() && float.abs() < i64::max_value() as f64 => {
+            let seconds = float.floor();
+            let nanoseconds = ((float - seconds) * 1e9).round() as u32;
+            if nanoseconds >= 1_000_000_000 {
+                Some((seconds as i64 + 1, 0))
+            } else {
+                Some((seconds as i64, nanoseconds))
+            }
+        }
+        _ => None,
+    }
+}
+
+fn item(value: &Value) -> Item {
+    match value {
+        Value::Bytes(bytes) => Item::Bytes(BytesEncoding::Base64Url, bytes),
+        Value::Tag(tag, inner) => match (*tag, &**inner) {
+            (EPOCH_DATE_TIME_TAG, inner) => epoch_date_time(inner).map_or_else(|| item(inner), |(seconds, nanoseconds)| Item::EpochDateTime { seconds, nanoseconds }),
+            (POSITIVE_BIGNUM_TAG, Value::Bytes(bytes)) => Item::Bignum {
+                negative: false,
+                magnitude: bytes,
+            },
+            (NEGATIVE_BIGNUM_TAG, Value::Bytes(bytes)) => Item::Bignum { negative: true, magnitude: bytes },
+            (EXPECTED_BASE64_TAG, Value::Bytes(bytes)) => Item::Bytes(BytesEncoding::Base64, bytes),
+            (EXPECTED_BASE16_TAG, Value::Bytes(bytes)) => Item::Bytes(BytesEncoding::Base16, bytes),
+            _ => item(inner),
+        },
+        _ => Item::Plain(value),
+    }
+}
+
+fn is_bytes(value: &Value) -> bool {
+    matches!(item(value), Item::Bytes(..))
+}
+
+// Bignum value is `magnitude` for positive bignums and `-1 - magnitude` for negative bignums
+fn bignum_as_integer(negative: bool, magnitude: &[u8]) -> Option<i128> {
+    let significant_bytes = magnitude.iter().skip_while(|byte| **byte == 0).collect::<Vec<_>>();
+    if significant_bytes.len() > 16 {
+        return None;
+    }
+    let magnitude = i128::try_from(significant_bytes.iter().fold(0_u128, |acc, byte| (acc << 8) | u128::from(**byte))).ok()?;
+    Some(if negative { -1 - magnitude } else { magnitude })
+}
+
+fn bignum_as_number(negative: bool, magnitude: &[u8]) -> f64 {
+    let magnitude = magnitude.iter().fold(0_f64, |acc, byte| acc.mul_add(256_f64, f64::from(*byte)));
+    if negative {
+        -1_f64 - magnitude
+    } else {
+        magnitude
+    }
+}
+
+#[cfg(feature = "big_integer")]
+fn bignum_as_big_integer(negative: bool, magnitude: &[u8]) -> BigInt {
+    let magnitude = BigInt::from_bytes_be(Sign::Plus, magnitude);
+    if negative {
+        BigInt::from(-1) - magnitude
+    } else {
+        magnitude
+    }
+}
+
+fn encode_base64(bytes: &[u8], alphabet: &[u8; 64], padding: bool) -> String {
+    let mut result = String::with_capacity((bytes.len() + 2) / 3 * 4);
+    for chunk in bytes.chunks(3) {
+        let buffer = chunk.iter().enumerate().fold(0_u32, |acc, (index, byte)| acc | u32::from(*byte) << (16 - 8 * index));
+        for index in 0..=chunk.len() {
+            result.push(char::from(alphabet[(buffer >> (18 - 6 * index)) as usize & 0x3f]));
+        }
+        if padding {
+            for _ in chunk.len()..3 {
+                result.push('=');
+            }
+        }
+    }
+    result
+}
+
+fn encode_bytes(encoding: BytesEncoding, bytes: &[u8]) -> String {
+    match encoding {
+        BytesEncoding::Base16 => bytes.iter().map(|byte| format!("{:02x}", byte)).collect(),
+        BytesEncoding::Base64 => encode_base64(bytes, BASE64_ALPHABET, true),
+        BytesEncoding::Base64Url => encode_base64(bytes, BASE64URL_ALPHABET, false),
+    }
+}
+
+// Byte string (optionally enclosed by tags) converted according to RFC 8949 section 6.1, `None` for other data items
+#[must_use]
+pub fn bytes_as_string(value: &Value) -> Option<String> {
+    if let Item::Bytes(encoding, bytes) = item(value) {
+        Some(encode_bytes(encoding, bytes))
+    } else {
+        None
+    }
+}
+
+// Convert seconds since 1970-01-01T00:00:00Z into its RFC 3339 representation
+// Days to civil date conversion is based on http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+fn format_rfc3339(seconds: i64, nanoseconds: u32) -> String {
+    let days = seconds.div_euclid(86_400);
+    let seconds_of_day = seconds.rem_euclid(86_400);
+
+    let days_since_era_start = days + 719_468;
+    let era = days_since_era_start.div_euclid(146_097);
+    let day_of_era = days_since_era_start.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    let fraction = if nanoseconds == 0 {
+        String::new()
+    } else {
+        format!(".{:09}", nanoseconds).trim_end_matches('0').to_string()
+    };
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}{}Z",
+        year,
+        month,
+        day,
+        seconds_of_day / 3600,
+        seconds_of_day % 3600 / 60,
+        seconds_of_day % 60,
+        fraction
+    )
+}
+
+fn integer_to_value(integer: i128) -> Value {
+    Integer::try_from(integer).map_or_else(
+        |_| {
+            // Integers outside of the CBOR integer range are encoded as bignums
+            let (tag, magnitude) = if integer < 0 {
+                (NEGATIVE_BIGNUM_TAG, -1 - integer)
+            } else {
+                (POSITIVE_BIGNUM_TAG, integer)
+            };
+            let bytes = magnitude.to_be_bytes().iter().copied().skip_while(|byte| *byte == 0).collect();
+            Value::Tag(tag, Box::new(Value::Bytes(bytes)))
+        },
+        Value::Integer,
+    )
+}
+
+#[cfg(feature = "big_integer")]
+fn big_integer_to_value(big_integer: &BigInt) -> Value {
+    let (tag, magnitude) = if big_integer.sign() == Sign::Minus {
+        (NEGATIVE_BIGNUM_TAG, BigInt::from(-1) - big_integer)
+    } else {
+        (POSITIVE_BIGNUM_TAG, big_integer.clone())
+    };
+    Value::Tag(tag, Box::new(Value::Bytes(magnitude.to_bytes_be().1)))
+}
+
+// Convert any `JsonType` into its CBOR representation
+/// # Errors
+///
+/// Will return `Err` if `json_object` exposes inconsistent values (see `convert::from_json_type`)
+pub fn to_cbor_value<T: JsonType>(json_object: &T) -> Result<Value, Error> {
+    from_json_type(json_object)
+}
+
+// Serialize any `JsonType` into CBOR bytes
+/// # Errors
+///
+/// Will return `Err` if `json_object` can not be converted or if the CBOR serialization fails
+pub fn to_cbor<T: JsonType>(json_object: &T) -> Result<Vec<u8>, Error> {
+    let mut bytes = Vec::new();
+    ciborium::ser::into_writer(&to_cbor_value(json_object)?, &mut bytes).map_err(|error| Error::backend("ciborium", "", error))?;
+    Ok(bytes)
+}
+
+impl Into<RustType> for Value {
+    fn into(self) -> RustType {
+        self.to_rust_type()
+    }
+}
+
+impl ToRustType for Value {
+    fn to_rust_type(&self) -> RustType {
+        match item(self) {
+            Item::Plain(Value::Array(array)) => RustType::from(array.iter().map(ToRustType::to_rust_type).collect::<Vec<_>>()),
+            Item::Plain(Value::Bool(value)) => RustType::from(*value),
+            Item::Plain(Value::Integer(value)) => RustType::from(i128::from(*value)),
+            Item::Plain(Value::Float(value)) => RustType::from(*value),
+            Item::Plain(map @ Value::Map(_)) => RustType::from(
+                JsonMap::new(map)
+                    .items()
+                    .map(|(key, value)| (key.to_string(), value.to_rust_type()))
+                    .collect::<HashMap<_, _>>(),
+            ),
+            Item::Plain(Value::Text(value)) => RustType::from(value.as_str()),
+            #[cfg(feature = "big_integer")]
+            Item::Bignum { negative, magnitude } => RustType::from(bignum_as_big_integer(negative, magnitude)),
+            #[cfg(not(feature = "big_integer"))]
+            Item::Bignum { negative, magnitude } => bignum_as_integer(negative, magnitude).map_or_else(|| RustType::from(bignum_as_number(negative, magnitude)), RustType::from),
+            Item::EpochDateTime { seconds, nanoseconds } => RustType::from(format_rfc3339(seconds, nanoseconds)),
+            Item::Plain(_) | Item::Bytes(..) => RustType::Null,
+        }
+    }
+}
+
+impl<'json> JsonMapTrait<'json, Value> for JsonMap<'json, Value> {
+    #[must_use]
+    fn keys(&'json self) -> Box<dyn Iterator<Item = &str> + 'json> {
+        Box::new(self.items().map(|(key, _)| key))
+    }
+
+    #[must_use]
+    fn values(&'json self) -> Box<dyn Iterator<Item = &Value> + 'json> {
+        Box::new(self.items().map(|(_, value)| value))
+    }
+
+    #[must_use]
+    fn items(&'json self) -> Box<dyn Iterator<Item = (&str, &Value)> + 'json> {
+        #[allow(clippy::option_if_let_else)]
+        if let Some(map) = self.as_map() {
+            Box::new(
+                map.iter()
+                    .filter(|(_, value)| !is_bytes(value))
+                    .filter_map(|(key, value)| key.as_text().map(|key| (key, value))),
+            )
+        } else {
+            Box::new(std::iter::empty())
+        }
+    }
+}
+
+impl JsonType for Value {
+    #[must_use]
+    fn as_array<'json>(&'json self) -> Option<Box<dyn ExactSizeIterator<Item = &Self> + 'json>> {
+        if let Item::Plain(Self::Array(array)) = item(self) {
+            Some(Box::new(array.iter()))
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_boolean(&self) -> Option<bool> {
+        if let Item::Plain(Self::Bool(value)) = item(self) {
+            Some(*value)
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_integer(&self) -> Option<i128> {
+        match item(self) {
+            Item::Plain(Self::Integer(value)) => Some(i128::from(*value)),
+            Item::Bignum { negative, magnitude } => bignum_as_integer(negative, magnitude),
+            _ => None,
+        }
+    }
+
+    #[cfg(feature = "big_integer")]
+    #[must_use]
+    fn as_big_integer(&self) -> Option<BigInt> {
+        match item(self) {
+            Item::Plain(Self::Integer(value)) => Some(BigInt::from(i128::from(*value))),
+            Item::Bignum { negative, magnitude } => Some(bignum_as_big_integer(negative, magnitude)),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    fn as_null(&self) -> Option<()> {
+        if let Item::Plain(Self::Null) | Item::Bytes(..) = item(self) {
+            Some(())
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_number(&self) -> Option<f64> {
+        match item(self) {
+            #[allow(clippy::cast_precision_loss)]
+            Item::Plain(Self::Integer(value)) => Some(i128::from(*value) as f64),
+            Item::Plain(Self::Float(value)) => Some(*value),
+            Item::Bignum { negative, magnitude } => Some(bignum_as_number(negative, magnitude)),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    fn as_object(&self) -> Option<JsonMap<Self>> {
+        if let Item::Plain(map @ Self::Map(_)) = item(self) {
+            Some(JsonMap::new(map))
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_string(&self) -> Option<&str> {
+        if let Item::Plain(Self::Text(value)) = item(self) {
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_string_cow(&self) -> Option<Cow<str>> {
+        match item(self) {
+            Item::Plain(Self::Text(value)) => Some(Cow::Borrowed(value)),
+            Item::EpochDateTime { seconds, nanoseconds } => Some(Cow::Owned(format_rfc3339(seconds, nanoseconds))),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    fn get_attribute(&self, attribute_name: &str) -> Option<&Self> {
+        if let Item::Plain(Self::Map(map)) = item(self) {
+            map.iter()
+                .find(|(key, value)| key.as_text() == Some(attribute_name) && !is_bytes(value))
+                .map(|(_, value)| value)
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn get_index(&self, index: usize) -> Option<&Self> {
+        if let Item::Plain(Self::Array(array)) = item(self) {
+            array.get(index)
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn is_string(&self) -> bool {
+        matches!(item(self), Item::Plain(Self::Text(_)) | Item::EpochDateTime { .. })
+    }
+
+    #[must_use]
+    fn primitive_type(&self) -> PrimitiveType {
+        match item(self) {
+            Item::Plain(Self::Array(_)) => PrimitiveType::Array,
+            Item::Plain(Self::Bool(_)) => PrimitiveType::Boolean,
+            Item::Plain(Self::Integer(_)) => PrimitiveType::Integer,
+            Item::Plain(Self::Float(_)) => PrimitiveType::Number,
+            Item::Plain(Self::Map(_)) => PrimitiveType::Object,
+            Item::Plain(Self::Text(_)) | Item::EpochDateTime { .. } => PrimitiveType::String,
+            Item::Bignum { negative, magnitude } => {
+                if cfg!(feature = "big_integer") || bignum_as_integer(negative, magnitude).is_some() {
+                    PrimitiveType::Integer
+                } else {
+                    PrimitiveType::Number
+                }
+            }
+            Item::Plain(_) | Item::Bytes(..) => PrimitiveType::Null,
+        }
+    }
+}
+
+impl ThreadSafeJsonType for Value {}
+
+impl JsonBuilder for Value {
+    fn null() -> Self {
+        Self::Null
+    }
+
+    fn bool(value: bool) -> Self {
+        Self::Bool(value)
+    }
+
+    fn integer(value: i128) -> Result<Self, Error> {
+        Ok(integer_to_value(value))
+    }
+
+    #[cfg(feature = "big_integer")]
+    fn big_integer(value: &BigInt) -> Result<Self, Error> {
+        Ok(big_integer_to_value(value))
+    }
+
+    fn number(value: f64) -> Self {
+        Self::Float(value)
+    }
+
+    fn string(value: &str) -> Self {
+        Self::Text(value.to_string())
+    }
+
+    fn array<I: IntoIterator<Item = Self>>(items: I) -> Self {
+        Self::Array(items.into_iter().collect())
+    }
+
+    fn object<'key, I: IntoIterator<Item = (&'key str, Self)>>(items: I) -> Self {
+        Self::Map(items.into_iter().map(|(key, value)| (Self::string(key), value)).collect())
+    }
+}
+
+// JSON like literals built directly as `ciborium::value::Value` (not via `serde_json::Value`, whose numbers are
+// serialized as maps if `serde_json_arbitrary_precision` is enabled)
+#[cfg(test)]
+macro_rules! cbor_value {
+    (null) => {
+        ciborium::value::Value::Null
+    };
+    ([$($item:tt),*]) => {
+        ciborium::value::Value::Array(vec![$(cbor_value!($item)),*])
+    };
+    ({$($key:literal: $value:tt),*}) => {
+        ciborium::value::Value::Map(vec![$((ciborium::value::Value::from($key), cbor_value!($value))),*])
+    };
+    ($value:expr) => {
+        ciborium::value::Value::from($value)
+    };
+}
+
+#[cfg(test)]
+fn tagged(tag: u64, value: Value) -> Value {
+    Value::Tag(tag, Box::new(value))
+}
+
+#[cfg(test)]
+mod tests_cbor_map_trait {
+    use crate::json_type::{JsonMap, JsonMapTrait};
+    use ciborium::value::Value;
+
+    lazy_static! {
+        static ref TESTING_MAP: Value = Value::Map(vec![
+            (Value::from("k1"), Value::from("v1")),
+            (Value::from(1), Value::from("non text key")),
+            (Value::from("k2"), Value::from("v2")),
+        ]);
+    }
+
+    #[test]
+    fn keys() {
+        let testing_map: &Value = &TESTING_MAP;
+        assert_eq!(JsonMap::new(testing_map).keys().collect::<Vec<_>>(), vec!["k1", "k2"]);
+    }
+
+    #[test]
+    fn values() {
+        let testing_map: &Value = &TESTING_MAP;
+        assert_eq!(JsonMap::new(testing_map).values().collect::<Vec<_>>(), vec![&cbor_value!["v1"], &cbor_value!["v2"]]);
+    }
+
+    #[test]
+    fn items() {
+        let testing_map: &Value = &TESTING_MAP;
+        assert_eq!(
+            JsonMap::new(testing_map).items().collect::<Vec<_>>(),
+            vec![("k1", &cbor_value!["v1"]), ("k2", &cbor_value!["v2"])]
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests_primitive_type_trait {
+    use super::tagged;
+    use crate::json_type::{JsonType, PrimitiveType};
+    use ciborium::value::Value;
+    use std::ops::Deref;
+    use test_case::test_case;
+
+    #[test_case(&cbor_value![[]], PrimitiveType::Array)]
+    #[test_case(&cbor_value![true], PrimitiveType::Boolean)]
+    #[test_case(&cbor_value![1], PrimitiveType::Integer)]
+    #[test_case(&cbor_value![null], PrimitiveType::Null)]
+    #[test_case(&cbor_value![1.2], PrimitiveType::Number)]
+    #[test_case(&cbor_value![{"prop": "value"}], PrimitiveType::Object)]
+    #[test_case(&cbor_value!["string"], PrimitiveType::String)]
+    #[test_case(&Value::Bytes(vec![0, 1]), PrimitiveType::Null)]
+    #[test_case(&tagged(22, Value::Bytes(vec![0, 1])), PrimitiveType::Null)]
+    #[test_case(&tagged(1, Value::from(0)), PrimitiveType::String)]
+    #[test_case(&tagged(2, Value::Bytes(vec![1, 0])), PrimitiveType::Integer)]
+    #[test_case(&tagged(32, cbor_value!["http://example.com"]), PrimitiveType::String)]
+    fn test_primitive_type(value: &Value, expected_value: PrimitiveType) {
+        assert_eq!(JsonType::primitive_type(value), expected_value);
+    }
+
+    #[test_case(&cbor_value![{"present": 1}], "present", Some(&cbor_value![1]))]
+    #[test_case(&cbor_value![{"present": 1}], "not-present", None)]
+    #[test_case(&tagged(42, cbor_value![{"present": 1}]), "present", Some(&cbor_value![1]))]
+    #[test_case(&Value::Map(vec![(Value::from("bytes"), Value::Bytes(vec![0, 1]))]), "bytes", None)]
+    fn test_get_attribute(value: &Value, attribute_name: &str, expected_value: Option<&Value>) {
+        assert_eq!(JsonType::get_attribute(value, attribute_name), expected_value);
+    }
+
+    #[test_case(&cbor_value![[0, 1, 2]], 1, &Some(cbor_value![1]))]
+    #[test_case(&cbor_value![[0, 1, 2]], 4, &None)]
+    fn test_get_index(value: &Value, index: usize, expected_value: &Option<Value>) {
+        assert_eq!(JsonType::get_index(value, index), expected_value.as_ref());
+    }
+
+    #[test_case(&cbor_value![{"present": 1}], "present", true)]
+    #[test_case(&cbor_value![{"present": 1}], "not-present", false)]
+    #[test_case(&cbor_value![[1, 2, 3]], "not-present", false)]
+    fn test_has_attribute(value: &Value, attr_name: &str, expected_value: bool) {
+        assert_eq!(JsonType::has_attribute(value, attr_name), expected_value);
+    }
+
+    #[test_case(&cbor_value![[1]], &Some(vec![cbor_value![1]]))]
+    #[test_case(&cbor_value![[1, "a"]], &Some(vec![cbor_value![1], cbor_value!["a"]]))]
+    #[test_case(&cbor_value![null], &None)]
+    fn test_as_array(value: &Value, expected_value: &Option<Vec<Value>>) {
+        assert_eq!(&JsonType::as_array(value).map(|iterator| iterator.cloned().collect()), expected_value);
+    }
+
+    #[test_case(&cbor_value![true], Some(true))]
+    #[test_case(&cbor_value![false], Some(false))]
+    #[test_case(&cbor_value![1], None)]
+    fn test_as_boolean(value: &Value, expected_value: Option<bool>) {
+        assert_eq!(JsonType::as_boolean(value), expected_value);
+    }
+
+    #[test_case(&cbor_value![1], Some(1))]
+    #[test_case(&cbor_value![-1], Some(-1) ; "negative integer")]
+    #[test_case(&cbor_value![u64::max_value()], Some(i128::from(u64::max_value())) ; "integer above i64 max")]
+    #[test_case(&tagged(2, Value::Bytes(vec![1, 0])), Some(256) ; "positive bignum")]
+    #[test_case(&tagged(3, Value::Bytes(vec![1, 0])), Some(-257) ; "negative bignum")]
+    #[test_case(&tagged(2, Value::Bytes(vec![1; 17])), None ; "bignum above i128 max")]
+    #[test_case(&cbor_value![1.2], None)]
+    #[test_case(&cbor_value!["1"], None)]
+    fn test_as_integer(value: &Value, expected_value: Option<i128>) {
+        assert_eq!(JsonType::as_integer(value), expected_value);
+    }
+
+    #[test_case(&cbor_value![null], Some(()))]
+    #[test_case(&Value::Bytes(vec![0, 1]), Some(()))]
+    #[test_case(&cbor_value!["1"], None)]
+    fn test_as_null(value: &Value, expected_value: Option<()>) {
+        assert_eq!(JsonType::as_null(value), expected_value);
+    }
+
+    #[test_case(&cbor_value![1], Some(1_f64))]
+    #[test_case(&cbor_value![1.2], Some(1.2))]
+    #[test_case(&tagged(3, Value::Bytes(vec![1, 0])), Some(-257_f64))]
+    #[test_case(&cbor_value!["1"], None)]
+    fn test_as_number(value: &Value, expected_value: Option<f64>) {
+        assert_eq!(JsonType::as_number(value), expected_value);
+    }
+
+    #[test_case(&cbor_value![1], &None)]
+    #[test_case(&cbor_value![1.2], &None)]
+    #[test_case(&cbor_value![{"1": 1}], &Some(cbor_value![{"1": 1}]))]
+    fn test_as_object(value: &Value, expected_value: &Option<Value>) {
+        assert_eq!(
+            match JsonType::as_object(value) {
+                Some(ref v) => Some({
+                    #[allow(clippy::explicit_deref_methods)] // Explicit deref call is needed to ensure that &Value is retrieved from JsonMap
+                    v.deref()
+                }),
+                None => None,
+            },
+            expected_value.as_ref(),
+        );
+    }
+
+    #[test_case(&cbor_value![1], None)]
+    #[test_case(&cbor_value![1.2], None)]
+    #[test_case(&cbor_value!["1"], Some("1"))]
+    #[test_case(&tagged(0, cbor_value!["2013-03-21T20:04:00Z"]), Some("2013-03-21T20:04:00Z"))]
+    #[test_case(&tagged(1, Value::from(1_363_896_240)), None)]
+    #[test_case(&Value::Bytes(vec![0, 1]), None)]
+    fn test_as_string(value: &Value, expected_value: Option<&str>) {
+        assert_eq!(JsonType::as_string(value), expected_value);
+    }
+
+    #[test_case(&cbor_value![1], None)]
+    #[test_case(&cbor_value!["1"], Some("1"))]
+    #[test_case(&tagged(1, Value::from(1_363_896_240)), Some("2013-03-21T20:04:00Z"))]
+    #[test_case(&Value::Bytes(vec![0, 1]), None)]
+    fn test_as_string_cow(value: &Value, expected_value: Option<&str>) {
+        assert_eq!(value.as_string_cow().as_deref(), expected_value);
+    }
+}
+
+#[cfg(test)]
+mod tests_tags {
+    use super::{bytes_as_string, tagged};
+    use crate::{
+        convert::from_json_type,
+        json_type::{JsonType, ToRustType},
+        rust_type_impl::RustType,
+    };
+    use ciborium::value::Value;
+    use test_case::test_case;
+
+    #[test_case(&tagged(1, Value::from(0)), "1970-01-01T00:00:00Z")]
+    #[test_case(&tagged(1, Value::from(1_363_896_240)), "2013-03-21T20:04:00Z")]
+    #[test_case(&tagged(1, Value::Float(1_363_896_240.5)), "2013-03-21T20:04:00.5Z")]
+    #[test_case(&tagged(1, Value::from(-86_401)), "1969-12-30T23:59:59Z")]
+    #[test_case(&tagged(1, Value::from(951_782_400)), "2000-02-29T00:00:00Z")]
+    fn test_epoch_date_time(value: &Value, expected_value: &str) {
+        assert_eq!(value.as_string_cow().as_deref(), Some(expected_value));
+        assert_eq!(value.to_rust_type(), RustType::from(expected_value));
+        assert_eq!(from_json_type::<RustType, _>(value), Ok(RustType::from(expected_value)));
+    }
+
+    #[test_case(&Value::Bytes(vec![]), "")]
+    #[test_case(&Value::Bytes(vec![0xfb, 0xff]), "-_8")]
+    #[test_case(&Value::Bytes(b"foobar".to_vec()), "Zm9vYmFy")]
+    #[test_case(&tagged(21, Value::Bytes(vec![0xfb, 0xff])), "-_8")]
+    #[test_case(&tagged(22, Value::Bytes(vec![0xfb, 0xff])), "+/8=")]
+    #[test_case(&tagged(22, Value::Bytes(b"f".to_vec())), "Zg==")]
+    #[test_case(&tagged(23, Value::Bytes(vec![0xfb, 0x0f])), "fb0f")]
+    fn test_bytes(value: &Value, expected_value: &str) {
+        assert_eq!(bytes_as_string(value).as_deref(), Some(expected_value));
+        assert_eq!(value.to_rust_type(), RustType::Null);
+        assert_eq!(from_json_type::<RustType, _>(value), Ok(RustType::Null));
+    }
+
+    #[test]
+    fn test_bytes_are_omitted_from_objects() {
+        let value = Value::Map(vec![
+            (Value::from("bytes"), Value::Bytes(vec![0, 1])),
+            (Value::from("array"), Value::Array(vec![Value::Bytes(vec![0, 1])])),
+        ]);
+        assert_eq!(value.to_rust_type(), rust_type!({"array": [null]}));
+        assert_eq!(from_json_type::<RustType, _>(&value), Ok(rust_type!({"array": [null]})));
+        assert_eq!(bytes_as_string(&Value::from("text")), None);
+    }
+
+    #[test_case(&tagged(2, Value::Bytes(vec![0, 1, 0])), RustType::from(256) ; "positive bignum")]
+    #[test_case(&tagged(3, Value::Bytes(vec![])), RustType::from(-1) ; "negative bignum")]
+    #[test_case(&tagged(42, tagged(2, Value::Bytes(vec![1, 0]))), RustType::from(256) ; "nested tags")]
+    #[test_case(&tagged(2, cbor_value!["not bytes"]), RustType::from("not bytes") ; "invalid bignum")]
+    fn test_bignum(value: &Value, expected_value: RustType) {
+        assert_eq!(value.to_rust_type(), expected_value);
+    }
+
+    #[cfg(not(feature = "big_integer"))]
+    #[test]
+    fn test_bignum_above_i128_max_is_a_number() {
+        // 2^128 is encoded as 0x01 followed by 16 zero bytes
+        let mut magnitude = vec![0; 17];
+        magnitude[0] = 1;
+        assert_eq!(tagged(2, Value::Bytes(magnitude)).to_rust_type(), RustType::from(2_f64.powi(128)));
+    }
+
+    #[cfg(feature = "big_integer")]
+    #[test]
+    fn test_bignum_above_i128_max_is_a_big_integer() {
+        assert_eq!(
+            tagged(2, Value::Bytes(vec![1; 17])).to_rust_type(),
+            RustType::from(num_bigint::BigInt::from_bytes_be(num_bigint::Sign::Plus, &[1; 17]))
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests_to_json_string {
+    use super::tagged;
+    use crate::json_type::JsonTypeToString;
+    use ciborium::value::Value;
+
+    #[test]
+    fn smoke_test() {
+        let value = Value::Array(vec![
+            cbor_value![{"array": []}],
+            cbor_value![{"boolean": false}],
+            cbor_value![{"float": 2.3}],
+            cbor_value![{"integer": 1}],
+            cbor_value![{"null": null}],
+            cbor_value![{"object": {}}],
+            cbor_value![{"string": "string"}],
+            Value::Map(vec![(Value::from("bytes"), Value::Bytes(vec![0, 1]))]),
+            Value::Map(vec![(Value::from("epoch"), tagged(1, Value::from(0)))]),
+        ]);
+        assert_eq!(
+            value.to_json_string(),
+            r#"[{"array":[]},{"boolean":false},{"float":2.3},{"integer":1},{"null":null},{"object":{}},{"string":"string"},{},{"epoch":"1970-01-01T00:00:00Z"}]"#
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests_to_cbor {
+    use super::{tagged, to_cbor, to_cbor_value};
+    use crate::{json_type::ToRustType, rust_type_impl::RustType};
+    use ciborium::value::Value;
+    use test_case::test_case;
+
+    #[test]
+    fn test_round_trip() {
+        let value = rust_type!({"array": [1, 2.3, "a"], "boolean": false, "null": null, "object": {"key": "value"}});
+        let bytes = to_cbor(&value).unwrap();
+        let decoded: Value = ciborium::de::from_reader(bytes.as_slice()).unwrap();
+        assert_eq!(decoded.to_rust_type(), value);
+    }
+
+    #[test_case(i128::from(u64::max_value()), Value::from(u64::max_value()) ; "unsigned integer")]
+    #[test_case(i128::from(u64::max_value()) + 1, tagged(2, Value::Bytes(vec![1, 0, 0, 0, 0, 0, 0, 0, 0])) ; "positive bignum")]
+    #[test_case(-i128::from(u64::max_value()) - 2, tagged(3, Value::Bytes(vec![1, 0, 0, 0, 0, 0, 0, 0, 0])) ; "negative bignum")]
+    fn test_integers(integer: i128, expected_value: Value) {
+        let value = to_cbor_value(&RustType::from(integer)).unwrap();
+        assert_eq!(value, expected_value);
+        assert_eq!(value.to_rust_type(), RustType::from(integer));
+    }
+}
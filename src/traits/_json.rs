@@ -1,9 +1,13 @@
 use crate::{
+    error::Error,
+    json_builder::{numeric_overflow, JsonBuilder},
     json_type::{JsonMap, JsonMapTrait, JsonType, JsonTypeToString, ThreadSafeJsonType, ToRustType},
     rust_type_impl::RustType,
 };
 use json::JsonValue;
-use std::ops::Index;
+#[cfg(feature = "big_integer")]
+use num_bigint::BigInt;
+use std::{borrow::Cow, convert::TryFrom, ops::Index};
 
 impl Into<RustType> for JsonValue {
     fn into(self) -> RustType {
@@ -53,18 +57,29 @@ impl JsonType for JsonValue {
 
     #[must_use]
     fn as_integer(&self) -> Option<i128> {
-        self.as_f64().and_then(
-            // The ugly conversion here is needed because rust-json internally does not
-            // distinguish integers from floats, which leads to have "1.2".as_i64() == 1
-            |number| {
-                if number.fract() == 0.0 {
-                    #[allow(clippy::cast_possible_truncation)]
-                    Some(i128::from(number.trunc() as i64))
-                } else {
-                    None
+        // rust-json internally does not distinguish integers from floats (which leads to have "1.2".as_i64() == 1)
+        // but it stores numbers as mantissa and exponent, so we can losslessly extract integers that do not fit
+        // into a i64 (or into a f64 mantissa) as well
+        if let Self::Number(number) = self {
+            if number.is_nan() {
+                return None;
+            }
+            let (positive, mantissa, exponent) = number.as_parts();
+            let mantissa = i128::from(mantissa);
+            let power_of_ten = 10_i128.checked_pow(u32::from(exponent.unsigned_abs()));
+            let absolute_value = if exponent >= 0 {
+                power_of_ten.and_then(|power_of_ten| mantissa.checked_mul(power_of_ten))?
+            } else {
+                match power_of_ten {
+                    Some(power_of_ten) if mantissa % power_of_ten == 0 => mantissa / power_of_ten,
+                    None if mantissa == 0 => 0,
+                    _ => return None,
                 }
-            },
-        )
+            };
+            Some(if positive { absolute_value } else { -absolute_value })
+        } else {
+            None
+        }
     }
 
     #[must_use]
@@ -81,6 +96,21 @@ impl JsonType for JsonValue {
         self.as_f64()
     }
 
+    #[must_use]
+    fn as_number_str(&self) -> Option<Cow<str>> {
+        // rust-json stores numbers as decimal mantissa (u64) and exponent, so its representation is exact even if
+        // not the original text (ie. `1e2` is reported as `100`)
+        if let Self::Number(number) = self {
+            if number.is_nan() {
+                None
+            } else {
+                Some(Cow::Owned(number.to_string()))
+            }
+        } else {
+            None
+        }
+    }
+
     #[must_use]
     fn as_object(&self) -> Option<JsonMap<Self>> {
         if self.is_object() {
@@ -118,6 +148,57 @@ impl JsonType for JsonValue {
 
 impl ThreadSafeJsonType for JsonValue {}
 
+// Integers are supported up to u64
+impl JsonBuilder for JsonValue {
+    fn null() -> Self {
+        Self::Null
+    }
+
+    fn bool(value: bool) -> Self {
+        Self::Boolean(value)
+    }
+
+    fn integer(value: i128) -> Result<Self, Error> {
+        i64::try_from(value)
+            .map(Self::from)
+            .or_else(|_| u64::try_from(value).map(Self::from))
+            .map_err(|_| numeric_overflow(&value, "json number"))
+    }
+
+    #[cfg(feature = "big_integer")]
+    fn big_integer(value: &BigInt) -> Result<Self, Error> {
+        Err(numeric_overflow(value, "json number"))
+    }
+
+    fn number(value: f64) -> Self {
+        Self::from(value)
+    }
+
+    // `json::JsonValue` stores numbers as decimal mantissa and exponent, so the textual representation is preferred
+    fn decimal(value: f64, number_str: &str) -> Self {
+        match json::parse(number_str) {
+            Ok(number @ Self::Number(_)) => number,
+            _ => Self::number(value),
+        }
+    }
+
+    fn string(value: &str) -> Self {
+        Self::from(value)
+    }
+
+    fn array<I: IntoIterator<Item = Self>>(items: I) -> Self {
+        Self::Array(items.into_iter().collect())
+    }
+
+    fn object<'key, I: IntoIterator<Item = (&'key str, Self)>>(items: I) -> Self {
+        let mut object = json::object::Object::new();
+        for (key, value) in items {
+            object.insert(key, value);
+        }
+        Self::Object(object)
+    }
+}
+
 #[cfg(test)]
 macro_rules! rust_json {
     ($($json:tt)+) => {{
@@ -164,7 +245,10 @@ mod tests_json_map_trait {
 
 #[cfg(test)]
 mod tests_primitive_type_trait {
-    use crate::json_type::{JsonType, PrimitiveType};
+    use crate::{
+        json_type::{JsonType, PrimitiveType, ToRustType},
+        rust_type_impl::{Decimal, RustType},
+    };
     use json::JsonValue;
     use std::ops::Deref;
     use test_case::test_case;
@@ -291,6 +375,11 @@ mod tests_primitive_type_trait {
     }
 
     #[test_case(&rust_json![1], Some(1))]
+    #[test_case(&rust_json![-1], Some(-1) ; "negative integer")]
+    #[test_case(&rust_json![u64::max_value()], Some(i128::from(u64::max_value())) ; "integer above i64 max")]
+    #[test_case(&json::parse("1.0").unwrap(), Some(1))]
+    #[test_case(&json::parse("12e30").unwrap(), Some(12_000_000_000_000_000_000_000_000_000_000))]
+    #[test_case(&json::parse("1e40").unwrap(), None)]
     #[test_case(&rust_json![1.2], None)]
     #[test_case(&rust_json!["1"], None)]
     fn test_as_integer(value: &JsonValue, expected_value: Option<i128>) {
@@ -310,6 +399,23 @@ mod tests_primitive_type_trait {
         assert_eq!(JsonType::as_number(value), expected_value);
     }
 
+    #[test_case(&json::parse("1").unwrap(), Some("1"))]
+    #[test_case(&json::parse("1.10").unwrap(), Some("1.10"))]
+    #[test_case(&json::parse("0.1000000000000000055").unwrap(), Some("1.000000000000000055e-1"))]
+    #[test_case(&rust_json!["1"], None)]
+    fn test_as_number_str(value: &JsonValue, expected_value: Option<&str>) {
+        assert_eq!(JsonType::as_number_str(value).as_deref(), expected_value);
+    }
+
+    #[test_case(&json::parse("1.5").unwrap(), &RustType::Number(1.5))]
+    #[test_case(&json::parse("1.10").unwrap(), &RustType::Number(1.1))]
+    #[test_case(&json::parse("1.25e1").unwrap(), &RustType::Number(12.5))]
+    #[test_case(&json::parse("-0.50").unwrap(), &RustType::Number(-0.5))]
+    #[test_case(&json::parse("0.1000000000000000055").unwrap(), &RustType::Decimal(Decimal::new("1.000000000000000055e-1").unwrap()))]
+    fn test_to_rust_type_preserves_decimals(value: &JsonValue, expected_value: &RustType) {
+        assert_eq!(&value.to_rust_type(), expected_value);
+    }
+
     #[test_case(&rust_json![1], &None)]
     #[test_case(&rust_json![1.2], &None)]
     #[test_case(&rust_json![{"1": 1}], &Some(rust_json![{"1": 1}]))]
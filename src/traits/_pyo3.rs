@@ -1,14 +1,384 @@
 use crate::{
-    json_type::{JsonMap, JsonMapTrait, JsonType, ToRustType},
+    convert::inconsistent_value,
+    error::Error,
+    json_type::{JsonMap, JsonMapTrait, JsonType, ThreadSafeJsonType, ToRustType, TryJsonMapTrait, TryJsonType},
     rust_type_impl::RustType,
 };
-#[cfg(test)]
-use pyo3::Python;
+#[cfg(feature = "big_integer")]
+use num_bigint::BigInt;
 use pyo3::{
-    types::{PyAny, PyDict, PySequence},
-    PyTryInto,
+    exceptions::IndexError,
+    types::{PyAny, PyDict, PyList, PyLong, PySequence, PyTuple, PyType},
+    AsPyPointer, AsPyRef, IntoPy, PyErr, PyNativeType, PyObject, PyResult, PyTryFrom, PyTryInto, Python, ToPyObject,
+};
+use std::{
+    borrow::Cow,
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    convert::TryInto,
+    ops::Deref,
+    sync::OnceLock,
 };
-use std::{convert::TryInto, ops::Deref};
+
+fn py_err_message(py: Python, py_err: &PyErr) -> String {
+    let exception = py_err.to_object(py);
+    exception
+        .as_ref(py)
+        .repr()
+        .map_or_else(|_| format!("{:?}", py_err), |repr| repr.to_string_lossy().into_owned())
+}
+
+fn py_err_to_error(py: Python, py_err: &PyErr) -> Error {
+    Error::backend("pyo3", "", py_err_message(py, py_err))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PyBytesMode {
+    // Sequence of integers, as `bytes` and `bytearray` are Python sequences
+    Array,
+    // UTF-8 decoded string, invalid sequences are replaced by U+FFFD
+    Utf8String,
+    // Base64 (with padding) encoded string
+    Base64String,
+}
+
+// Options customising how Python objects, which have no direct JSON counterpart, are exposed by `JsonType for PyAny`.
+// The default options expose only `dict` instances as objects and any sequence (except strings) as arrays, while
+// objects that are still not representable after the conversions are reported as null.
+//
+// Objects are converted, in order, via
+// * `__json__` method (if `json_hook` is set), as for `simplejson` and similar libraries
+// * `dataclasses.asdict` (if `dataclasses` is set) for dataclass instances
+// * the `value` attribute (if `enums` is set) for `enum.Enum` instances
+// * `isoformat` method (if `datetimes` is set) for `datetime.date`, `datetime.datetime` and `datetime.time` instances
+// * `dict` (if `mappings` is set) for `collections.abc.Mapping` instances that are not dictionaries
+// * `list` (if `sets` is set) for `set` and `frozenset` instances
+// * `bytes` and `bytearray` instances according to `bytes`
+//
+// Options are applied via `PyJsonOptions::scope`. As Python objects are accessible only by the thread holding
+// the GIL, the options are thread local.
+//
+// Each object is converted once per scope, the result is retained until the scope is left so the conversions
+// (ie. `__json__` hooks) are not repeated by every accessor. Conversions raising an exception are reported as
+// `Error::Backend` by the `TryJsonType` accessors, while the infallible accessors report the object as null.
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PyJsonOptions {
+    pub bytes: PyBytesMode,
+    pub dataclasses: bool,
+    pub datetimes: bool,
+    pub enums: bool,
+    pub json_hook: bool,
+    pub mappings: bool,
+    pub sets: bool,
+}
+
+impl Default for PyJsonOptions {
+    #[must_use]
+    fn default() -> Self {
+        Self {
+            bytes: PyBytesMode::Array,
+            dataclasses: false,
+            datetimes: false,
+            enums: false,
+            json_hook: false,
+            mappings: false,
+            sets: false,
+        }
+    }
+}
+
+// Objects converted within a scope, by address of the original object, with their conversion result (or the
+// representation of the raised exception). The original object is retained, so its address is not reused by
+// other objects while the scope is active.
+type PyResolvedObjects = HashMap<usize, (PyObject, Result<PyObject, String>)>;
+
+thread_local! {
+    static PY_JSON_OPTIONS: Cell<PyJsonOptions> = Cell::new(PyJsonOptions::default());
+    static PY_RESOLVED_OBJECTS: RefCell<PyResolvedObjects> = RefCell::new(HashMap::new());
+}
+
+// Restore the previous options and conversions once the scope is left (even if it is left by a panic)
+struct PyJsonOptionsGuard(PyJsonOptions, PyResolvedObjects);
+
+impl Drop for PyJsonOptionsGuard {
+    fn drop(&mut self) {
+        PY_JSON_OPTIONS.with(|options| options.set(self.0));
+        let _ = PY_RESOLVED_OBJECTS.with(|resolved_objects| resolved_objects.replace(std::mem::take(&mut self.1)));
+    }
+}
+
+impl PyJsonOptions {
+    // Options enabling all the conversions, with bytes reported as UTF-8 strings
+    #[must_use]
+    pub const fn all() -> Self {
+        Self {
+            bytes: PyBytesMode::Utf8String,
+            dataclasses: true,
+            datetimes: true,
+            enums: true,
+            json_hook: true,
+            mappings: true,
+            sets: true,
+        }
+    }
+
+    // Use `self` while reading Python objects, on the current thread, within `function`
+    pub fn scope<R, F: FnOnce() -> R>(self, function: F) -> R {
+        let _guard = PyJsonOptionsGuard(
+            PY_JSON_OPTIONS.with(|options| options.replace(self)),
+            PY_RESOLVED_OBJECTS.with(|resolved_objects| resolved_objects.replace(HashMap::new())),
+        );
+        function()
+    }
+
+    fn resolve(self, value: &PyAny) -> PyResult<&PyAny> {
+        let mut value = value;
+        if self.json_hook && value.hasattr("__json__").unwrap_or(false) {
+            value = value.call_method0("__json__")?;
+        }
+        if self.dataclasses
+            && PyTryInto::<PyType>::try_into(value).is_err()
+            && call_function(value, "dataclasses", "is_dataclass").map_or(false, |is_dataclass| is_dataclass.is_true().unwrap_or(false))
+        {
+            value = call_function(value, "dataclasses", "asdict")?;
+        }
+        if self.enums && is_instance(value, "enum", &["Enum"]) {
+            value = value.getattr("value")?;
+        }
+        if self.datetimes && is_instance(value, "datetime", &["date", "time"]) {
+            value = value.call_method0("isoformat")?;
+        }
+        if self.mappings && PyTryInto::<PyDict>::try_into(value).is_err() && is_instance(value, "collections.abc", &["Mapping"]) {
+            value = call_function(value, "builtins", "dict")?;
+        }
+        if self.sets && is_instance(value, "builtins", &["set", "frozenset"]) {
+            value = call_function(value, "builtins", "list")?;
+        }
+        if self.bytes != PyBytesMode::Array && is_instance(value, "builtins", &["bytes", "bytearray"]) {
+            value = match self.bytes {
+                PyBytesMode::Utf8String => value.call_method1("decode", ("utf-8", "replace"))?,
+                _ => call_function(value, "base64", "b64encode").and_then(|encoded| encoded.call_method1("decode", ("ascii",)))?,
+            };
+        }
+        Ok(value)
+    }
+}
+
+fn call_function<'py>(value: &'py PyAny, module: &str, function: &str) -> PyResult<&'py PyAny> {
+    value.py().import(module).and_then(|module| module.call1(function, (value,)))
+}
+
+fn is_instance(value: &PyAny, module: &str, class_names: &[&str]) -> bool {
+    value.py().import(module).map_or(false, |module| {
+        class_names.iter().any(|class_name| {
+            module
+                .get(class_name)
+                .ok()
+                .and_then(|class| PyTryInto::<PyType>::try_into(class).ok())
+                .map_or(false, |class| class.is_instance(value).unwrap_or(false))
+        })
+    })
+}
+
+fn is_json_representable(value: &PyAny) -> bool {
+    value.is_none()
+        || PyTryInto::<PyDict>::try_into(value).is_ok()
+        // Strings are sequences too
+        || PyTryInto::<PySequence>::try_into(value).is_ok()
+        || PyTryInto::<PyLong>::try_into(value).is_ok()
+        // Booleans, floats and objects implementing `__float__` (ie. `decimal.Decimal`)
+        || value.extract::<f64>().is_ok()
+}
+
+// Python object exposed in place of `value`, according to the options of the current thread.
+// Objects without JSON counterpart (ie. arbitrary class instances) are reported as `None`, while conversions
+// raising an exception are reported as `Error::Backend`
+fn try_resolved(value: &PyAny) -> Result<&PyAny, Error> {
+    let options = PY_JSON_OPTIONS.with(Cell::get);
+    let py = value.py();
+    let value = if options == PyJsonOptions::default() {
+        value
+    } else {
+        let address = value.as_ptr() as usize;
+        // The conversion is performed without borrowing the resolved objects, as it might run Python code reading
+        // other objects (ie. `__json__` hooks)
+        if !PY_RESOLVED_OBJECTS.with(|resolved_objects| resolved_objects.borrow().contains_key(&address)) {
+            let resolved = options.resolve(value).map(|resolved| resolved.to_object(py)).map_err(|py_err| py_err_message(py, &py_err));
+            let _ = PY_RESOLVED_OBJECTS.with(|resolved_objects| resolved_objects.borrow_mut().insert(address, (value.to_object(py), resolved)));
+        }
+        PY_RESOLVED_OBJECTS.with(|resolved_objects| match &resolved_objects.borrow()[&address].1 {
+            Ok(resolved) => Ok(gil_owned(py, resolved)),
+            Err(message) => Err(Error::backend("pyo3", "", message.clone())),
+        })?
+    };
+    Ok(if is_json_representable(value) { value } else { py_none(py) })
+}
+
+fn resolved(value: &PyAny) -> &PyAny {
+    try_resolved(value).unwrap_or_else(|_| py_none(value.py()))
+}
+
+// Reference to `object` owned by the GIL pool (the tuple allows to get it without unsafe code)
+fn gil_owned<'py>(py: Python<'py>, object: &PyObject) -> &'py PyAny {
+    PyTuple::new(py, [object]).get_item(0)
+}
+
+fn py_none(py: Python) -> &PyAny {
+    gil_owned(py, &py.None())
+}
+
+// Item at `index` of a Python sequence, `None` if `index` is out of range.
+// Lists and tuples (excluding their subclasses, as they might override `__getitem__`) are accessed directly
+fn try_sequence_item(sequence: &PyAny, index: usize) -> Result<Option<&PyAny>, Error> {
+    if let Ok(python_list) = <PyList as PyTryFrom>::try_from_exact(sequence) {
+        // The length is checked on every access as the list might be modified by Python code (ie. `__json__` hooks)
+        if index < python_list.len() {
+            Ok(index.try_into().ok().map(|idx| python_list.get_item(idx)))
+        } else {
+            Ok(None)
+        }
+    } else if let Ok(python_tuple) = <PyTuple as PyTryFrom>::try_from_exact(sequence) {
+        if index < python_tuple.len() {
+            Ok(Some(python_tuple.get_item(index)))
+        } else {
+            Ok(None)
+        }
+    } else {
+        match (PyTryInto::<PySequence>::try_into(sequence), TryInto::<isize>::try_into(index)) {
+            (Ok(python_sequence), Ok(idx)) => match python_sequence.get_item(idx) {
+                Ok(item) => Ok(Some(item)),
+                Err(ref py_err) if py_err.is_instance::<IndexError>(sequence.py()) => Ok(None),
+                Err(ref py_err) => Err(py_err_to_error(sequence.py(), py_err)),
+            },
+            _ => Ok(None),
+        }
+    }
+}
+
+fn sequence_item(sequence: &PyAny, index: usize) -> Option<&PyAny> {
+    try_sequence_item(sequence, index).ok().flatten()
+}
+
+// Lazy iterator over the items of a Python sequence.
+// The length is determined when the iteration starts, items that cannot be retrieved (ie. the sequence has been
+// shrunk or `__getitem__` failed) are reported as `Error::Backend` with the path of the item
+struct PySequenceItems<'py> {
+    sequence: &'py PyAny,
+    index: usize,
+    len: usize,
+}
+
+impl<'py> PySequenceItems<'py> {
+    // `None` if `sequence` is a string or it is not a Python sequence
+    fn new(sequence: &'py PyAny) -> Result<Option<Self>, Error> {
+        if sequence.is_string() {
+            return Ok(None);
+        }
+        let len = if let Ok(python_list) = <PyList as PyTryFrom>::try_from_exact(sequence) {
+            python_list.len()
+        } else if let Ok(python_tuple) = <PyTuple as PyTryFrom>::try_from_exact(sequence) {
+            python_tuple.len()
+        } else if let Ok(python_sequence) = PyTryInto::<PySequence>::try_into(sequence) {
+            let len = python_sequence.len().map_err(|py_err| py_err_to_error(sequence.py(), &py_err))?;
+            len.try_into().unwrap_or(0)
+        } else {
+            return Ok(None);
+        };
+        Ok(Some(Self { sequence, index: 0, len }))
+    }
+}
+
+impl<'py> Iterator for PySequenceItems<'py> {
+    type Item = Result<&'py PyAny, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index < self.len {
+            let index = self.index;
+            self.index += 1;
+            let item = try_sequence_item(self.sequence, index).and_then(|item| item.ok_or_else(|| Error::backend("pyo3", "", "Sequence has been shrunk while iterating over it")));
+            Some(item.map_err(|error| error.prefix_path(&index.to_string())))
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.len - self.index;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'py> ExactSizeIterator for PySequenceItems<'py> {}
+
+// Convert any `JsonType` into the equivalent Python object (`dict`, `list`, `int`, `float`, `str`, `bool` or `None`)
+// Numbers carrying an exact textual representation (see `JsonType::as_number_str`) are converted into `decimal.Decimal`
+// instances, so they are read back losslessly.
+/// # Errors
+///
+/// Will return `Err` if a Python object cannot be created or if `json_object` is not a valid JSON value
+/// (see `convert::from_json_type`)
+pub fn to_pyobject<T: JsonType>(py: Python, json_object: &T) -> Result<PyObject, Error> {
+    #[cfg(feature = "big_integer")]
+    {
+        // Python integers have arbitrary precision, but pyo3 does not expose a direct conversion from BigInt
+        if let (None, Some(big_integer)) = (json_object.as_integer(), json_object.as_big_integer()) {
+            return py
+                .get_type::<PyLong>()
+                .call1((big_integer.to_string(),))
+                .map(|value| value.to_object(py))
+                .map_err(|py_err| py_err_to_error(py, &py_err));
+        }
+    }
+
+    if let Some(array) = json_object.as_array() {
+        let items = array
+            .enumerate()
+            .map(|(index, item)| to_pyobject(py, item).map_err(|error| error.prefix_path(&index.to_string())))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(PyList::new(py, items).to_object(py))
+    } else if let Some(bool) = json_object.as_boolean() {
+        Ok(bool.to_object(py))
+    } else if let Some(integer) = json_object.as_integer() {
+        Ok(integer.to_object(py))
+    } else if json_object.is_null() {
+        Ok(py.None())
+    } else if let Some(number) = json_object.as_number() {
+        match json_object.as_number_str() {
+            Some(number_str) => py
+                .import("decimal")
+                .and_then(|module| module.call1("Decimal", (number_str.as_ref(),)))
+                .map(|decimal| decimal.to_object(py))
+                .map_err(|py_err| py_err_to_error(py, &py_err)),
+            None => Ok(number.to_object(py)),
+        }
+    } else if let Some(object) = json_object.as_object() {
+        let python_dict = PyDict::new(py);
+        for (key, value) in object.items() {
+            let value = to_pyobject(py, value).map_err(|error| error.prefix_path(key))?;
+            python_dict.set_item(key, value).map_err(|py_err| py_err_to_error(py, &py_err).prefix_path(key))?;
+        }
+        Ok(python_dict.to_object(py))
+    } else if let Some(string) = json_object.as_string_cow() {
+        Ok(string.as_ref().to_object(py))
+    } else {
+        Err(inconsistent_value(json_object))
+    }
+}
+
+// `RustType` values are always valid JSON values, so the conversion fails only if Python fails to create the objects
+// (ie. on memory errors). As `ToPyObject` is infallible, `None` is returned in that case.
+impl ToPyObject for RustType {
+    fn to_object(&self, py: Python) -> PyObject {
+        to_pyobject(py, self).unwrap_or_else(|_| py.None())
+    }
+}
+
+impl IntoPy<PyObject> for RustType {
+    fn into_py(self, py: Python) -> PyObject {
+        self.to_object(py)
+    }
+}
 
 impl Into<RustType> for PyAny {
     fn into(self) -> RustType {
@@ -18,6 +388,9 @@ impl Into<RustType> for PyAny {
 
 impl ToRustType for PyAny {}
 
+// Dictionaries with non-string keys have no JSON counterpart: the infallible accessors (and so `ToRustType`) skip
+// the entries with non-string keys, while `TryJsonMapTrait` (and so `TryJsonType::try_to_rust_type`) reports them
+// as `Error::Backend`. Use the fallible accessors if silently dropping entries is not acceptable.
 impl<'json> JsonMapTrait<'json, PyAny> for JsonMap<'json, PyAny> {
     #[must_use]
     fn keys(&'json self) -> Box<dyn Iterator<Item = &str> + 'json> {
@@ -53,46 +426,113 @@ impl<'json> JsonMapTrait<'json, PyAny> for JsonMap<'json, PyAny> {
     }
 }
 
+impl<'json> TryJsonMapTrait<'json, PyAny> for JsonMap<'json, PyAny> {
+    fn try_keys(&'json self) -> Result<Box<dyn Iterator<Item = &str> + 'json>, Error> {
+        self.try_items().map(|items| {
+            let b: Box<dyn Iterator<Item = _>> = Box::new(items.map(|(key, _)| key));
+            b
+        })
+    }
+
+    fn try_values(&'json self) -> Result<Box<dyn Iterator<Item = &PyAny> + 'json>, Error> {
+        self.try_items().map(|items| {
+            let b: Box<dyn Iterator<Item = _>> = Box::new(items.map(|(_, value)| value));
+            b
+        })
+    }
+
+    fn try_items(&'json self) -> Result<Box<dyn Iterator<Item = (&str, &PyAny)> + 'json>, Error> {
+        match PyTryInto::<PyDict>::try_into({
+            #[allow(clippy::explicit_deref_methods)] // Explicit deref call is needed to ensure that &PyAny is retrieved from JsonMap
+            self.deref()
+        }) {
+            Ok(python_dict) => python_dict
+                .iter()
+                .map(|(key, value)| match key.as_string() {
+                    Some(key_string) => Ok((key_string, value)),
+                    None => Err(Error::backend("pyo3", "", format!("Dictionary key `{}` is not a string", key))),
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .map(|items| {
+                    let b: Box<dyn Iterator<Item = _>> = Box::new(items.into_iter());
+                    b
+                }),
+            Err(_) => Ok(Box::new(Vec::with_capacity(0).into_iter())),
+        }
+    }
+}
+
+impl TryJsonType for PyAny {
+    fn try_as_array<'json>(&'json self) -> Result<Option<Box<dyn ExactSizeIterator<Item = &Self> + 'json>>, Error> {
+        let value = try_resolved(self)?;
+        match PySequenceItems::new(value)? {
+            None => Ok(None),
+            Some(items) => items.collect::<Result<Vec<_>, _>>().map(|items| {
+                let b: Box<dyn ExactSizeIterator<Item = _>> = Box::new(items.into_iter());
+                Some(b)
+            }),
+        }
+    }
+
+    fn try_get_attribute(&self, attribute_name: &str) -> Result<Option<&Self>, Error> {
+        let value = try_resolved(self)?;
+        Ok(PyTryInto::<PyDict>::try_into(value).ok().and_then(|python_dict| python_dict.get_item(attribute_name)))
+    }
+
+    fn try_get_index(&self, index: usize) -> Result<Option<&Self>, Error> {
+        try_sequence_item(try_resolved(self)?, index)
+    }
+}
+
 impl JsonType for PyAny {
     #[must_use]
     fn as_array<'json>(&'json self) -> Option<Box<dyn ExactSizeIterator<Item = &Self> + 'json>> {
-        match PyTryInto::<PySequence>::try_into(self) {
-            Err(_) => None,
-            Ok(py_sequence) => match py_sequence.iter() {
-                Err(_) => None,
-                Ok(iterator) => {
-                    if self.is_string() {
-                        None
-                    } else {
-                        Some(Box::new(iterator.filter_map(Result::ok).collect::<Vec<_>>().into_iter()))
-                    }
-                }
-            },
-        }
+        let value = resolved(self);
+        let py = value.py();
+        // Items that cannot be retrieved are reported as `None` to honour `ExactSizeIterator`, use
+        // `TryJsonType::try_as_array` to get the failure
+        let items = PySequenceItems::new(value).ok().flatten()?;
+        Some(Box::new(items.map(move |item| item.unwrap_or_else(|_| py_none(py)))))
     }
 
     #[must_use]
     fn as_boolean(&self) -> Option<bool> {
-        self.extract().ok()
+        let value = resolved(self);
+        value.extract().ok()
     }
 
     #[must_use]
     fn as_integer(&self) -> Option<i128> {
-        self.extract().ok().and_then(|value| {
+        let value = resolved(self);
+        value.extract().ok().and_then(|integer| {
             // In python `assert isinstance(True, int) is True` is correct
             // So if we're able to convert the instance to a i128 instance then we need
             // to verify that we did not start from a boolean instance
-            if self.is_boolean() {
+            if value.is_boolean() {
                 None
             } else {
-                Some(value)
+                Some(integer)
             }
         })
     }
 
+    #[cfg(feature = "big_integer")]
+    #[must_use]
+    fn as_big_integer(&self) -> Option<BigInt> {
+        let value = resolved(self);
+        // Python integers have arbitrary precision and pyo3 does not expose a direct conversion
+        // into BigInt, so we rely on their decimal representation
+        if value.is_boolean() || PyTryInto::<PyLong>::try_into(value).is_err() {
+            None
+        } else {
+            value.str().ok().and_then(|string| string.to_string_lossy().parse().ok())
+        }
+    }
+
     #[must_use]
     fn as_null(&self) -> Option<()> {
-        if self.is_none() {
+        let value = resolved(self);
+        if value.is_none() {
             Some(())
         } else {
             None
@@ -101,31 +541,47 @@ impl JsonType for PyAny {
 
     #[must_use]
     fn as_number(&self) -> Option<f64> {
-        self.extract().ok().and_then(|value| {
+        let value = resolved(self);
+        value.extract().ok().and_then(|number| {
             // pyo3 is able to convert a boolean value into a f64 instance
             // So if we're converted the PyAny instance into a f64 instance then we need
             // to verify that we did not start from a boolean instance
-            if self.is_boolean() {
+            if value.is_boolean() {
                 None
             } else {
-                Some(value)
+                Some(number)
             }
         })
     }
 
+    #[must_use]
+    fn as_number_str(&self) -> Option<Cow<str>> {
+        let value = resolved(self);
+        // Python's `decimal.Decimal` instances are the only numbers carrying an exact textual representation
+        // NOTE: C types report their fully qualified name, while the pure python implementation (`_pydecimal`) does not
+        if matches!(value.get_type().name().as_ref(), "decimal.Decimal" | "Decimal") && value.call_method0("is_finite").and_then(PyAny::extract).unwrap_or(false) {
+            value.str().ok().map(|string| string.to_string_lossy())
+        } else {
+            None
+        }
+    }
+
     #[must_use]
     fn as_object(&self) -> Option<JsonMap<Self>> {
-        PyTryInto::<PyDict>::try_into(self).ok().map(|_| JsonMap::new(self))
+        let value = resolved(self);
+        PyTryInto::<PyDict>::try_into(value).ok().map(|_| JsonMap::new(value))
     }
 
     #[must_use]
     fn as_string(&self) -> Option<&str> {
-        self.extract().ok()
+        let value = resolved(self);
+        value.extract().ok()
     }
 
     #[must_use]
     fn get_attribute(&self, attribute_name: &str) -> Option<&Self> {
-        if let Ok(python_dict) = PyTryInto::<PyDict>::try_into(self) {
+        let value = resolved(self);
+        if let Ok(python_dict) = PyTryInto::<PyDict>::try_into(value) {
             return (python_dict as &PyDict).get_item(attribute_name);
         }
         None
@@ -133,15 +589,287 @@ impl JsonType for PyAny {
 
     #[must_use]
     fn get_index(&self, index: usize) -> Option<&Self> {
-        if let Ok(idx) = TryInto::<isize>::try_into(index) {
-            if let Ok(python_sequence) = PyTryInto::<PySequence>::try_into(self) {
-                return python_sequence.get_item(idx).ok();
+        sequence_item(resolved(self), index)
+    }
+}
+
+// Thread safe view of a Python object, usable where `ThreadSafeJsonType` is required.
+// Each value is read from Python (acquiring the GIL, with the `PyJsonOptions` active when the root `PySnapshot` has been
+// created) the first time it is accessed and then cached, so modifications of the Python object performed afterwards
+// are not reflected once a value has been read. Values are read one level at a time: reading an array or an object
+// only references its items, which are read (and copied) when they are accessed in turn. Read values are kept as long
+// as the root `PySnapshot` is alive, so fully reading a large object keeps a copy of all its values in memory.
+//
+// Values that cannot be read are exposed as `null` by `JsonType` while `TryJsonType` reports the read failure.
+//
+// As `PySnapshot` is `Send + Sync` it can be moved into `Python::allow_threads` closures or into other threads.
+// Values not yet cached are read by acquiring the GIL, so reading them blocks while another thread holds the GIL
+// (ie. a thread waiting for the reading thread, without calling `Python::allow_threads`, would deadlock).
+// Reading from a thread already holding the GIL is fine, as the GIL acquisition is reentrant.
+#[derive(Debug)]
+pub struct PySnapshot {
+    object: PyObject,
+    options: PyJsonOptions,
+    node: OnceLock<PySnapshotNode>,
+}
+
+#[derive(Debug)]
+enum PySnapshotNode {
+    Null,
+    Boolean(bool),
+    Integer(i128),
+    #[cfg(feature = "big_integer")]
+    BigInteger(BigInt),
+    Number(f64, Option<String>),
+    String(String),
+    Array(Vec<PySnapshot>),
+    Object {
+        entries: Vec<(String, PySnapshot)>,
+        positions: HashMap<String, usize>,
+    },
+    Failure {
+        backend: &'static str,
+        path: String,
+        message: String,
+    },
+}
+
+impl From<Error> for PySnapshotNode {
+    fn from(error: Error) -> Self {
+        match error {
+            Error::Backend { backend, path, source } => Self::Failure {
+                backend,
+                path,
+                message: source.to_string(),
+            },
+            error => Self::Failure {
+                backend: "pyo3",
+                path: String::new(),
+                message: error.to_string(),
+            },
+        }
+    }
+}
+
+impl PySnapshot {
+    // Snapshot of `object`, read according to the `PyJsonOptions` of the current thread
+    #[must_use]
+    pub fn new(object: PyObject) -> Self {
+        Self::with_options(object, PY_JSON_OPTIONS.with(Cell::get))
+    }
+
+    const fn with_options(object: PyObject, options: PyJsonOptions) -> Self {
+        Self {
+            object,
+            options,
+            node: OnceLock::new(),
+        }
+    }
+
+    // Underlying Python object
+    #[must_use]
+    pub const fn object(&self) -> &PyObject {
+        &self.object
+    }
+
+    fn read(&self, value: &PyAny) -> Result<PySnapshotNode, Error> {
+        let py = value.py();
+        let snapshot = |item: &PyAny| Self::with_options(item.to_object(py), self.options);
+
+        #[cfg(feature = "big_integer")]
+        {
+            if let (None, Some(big_integer)) = (value.as_integer(), value.as_big_integer()) {
+                return Ok(PySnapshotNode::BigInteger(big_integer));
             }
         }
-        None
+
+        Ok(if let Some(array) = value.try_as_array()? {
+            PySnapshotNode::Array(array.map(snapshot).collect())
+        } else if let Some(bool) = value.as_boolean() {
+            PySnapshotNode::Boolean(bool)
+        } else if let Some(integer) = value.as_integer() {
+            PySnapshotNode::Integer(integer)
+        } else if let Some(number) = value.as_number() {
+            PySnapshotNode::Number(number, value.as_number_str().map(Cow::into_owned))
+        } else if let Some(object) = value.as_object() {
+            let mut entries = Vec::new();
+            let mut positions = HashMap::new();
+            for (key, value) in object.try_items()? {
+                let _ = positions.insert(key.to_string(), entries.len());
+                entries.push((key.to_string(), snapshot(value)));
+            }
+            PySnapshotNode::Object { entries, positions }
+        } else if let Some(string) = value.as_string() {
+            PySnapshotNode::String(string.to_string())
+        } else if value.is_null() {
+            PySnapshotNode::Null
+        } else {
+            return Err(inconsistent_value(value));
+        })
+    }
+
+    fn node(&self) -> &PySnapshotNode {
+        if let Some(node) = self.node.get() {
+            return node;
+        }
+        // The node is not read within `OnceLock::get_or_init` as waiting for the initialisation performed by another
+        // thread, while holding the GIL, would deadlock. Concurrent readers might read the node more than once.
+        let node = {
+            let gil = Python::acquire_gil();
+            let py = gil.python();
+            self.options.scope(|| self.read(self.object.as_ref(py))).unwrap_or_else(PySnapshotNode::from)
+        };
+        self.node.get_or_init(|| node)
+    }
+
+    // Read failure of this value, reported by the `TryJsonType` accessors
+    fn failure(&self) -> Result<(), Error> {
+        if let PySnapshotNode::Failure { backend, path, message } = self.node() {
+            Err(Error::backend(backend, path.as_str(), message.clone()))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl From<&PyAny> for PySnapshot {
+    #[must_use]
+    fn from(value: &PyAny) -> Self {
+        Self::new(value.to_object(value.py()))
+    }
+}
+
+impl ToRustType for PySnapshot {}
+
+impl TryJsonType for PySnapshot {
+    fn try_as_array<'json>(&'json self) -> Result<Option<Box<dyn ExactSizeIterator<Item = &Self> + 'json>>, Error> {
+        self.failure().map(|()| self.as_array())
+    }
+
+    fn try_get_attribute(&self, attribute_name: &str) -> Result<Option<&Self>, Error> {
+        self.failure().map(|()| self.get_attribute(attribute_name))
+    }
+
+    fn try_get_index(&self, index: usize) -> Result<Option<&Self>, Error> {
+        self.failure().map(|()| self.get_index(index))
+    }
+}
+
+impl<'json> JsonMapTrait<'json, PySnapshot> for JsonMap<'json, PySnapshot> {
+    #[must_use]
+    fn items(&'json self) -> Box<dyn Iterator<Item = (&str, &PySnapshot)> + 'json> {
+        match self.node() {
+            PySnapshotNode::Object { entries, .. } => Box::new(entries.iter().map(|(key, value)| (key.as_str(), value))),
+            _ => Box::new(std::iter::empty()),
+        }
     }
 }
 
+impl JsonType for PySnapshot {
+    #[must_use]
+    fn as_array<'json>(&'json self) -> Option<Box<dyn ExactSizeIterator<Item = &Self> + 'json>> {
+        if let PySnapshotNode::Array(items) = self.node() {
+            Some(Box::new(items.iter()))
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_boolean(&self) -> Option<bool> {
+        if let PySnapshotNode::Boolean(value) = self.node() {
+            Some(*value)
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_integer(&self) -> Option<i128> {
+        if let PySnapshotNode::Integer(value) = self.node() {
+            Some(*value)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "big_integer")]
+    #[must_use]
+    fn as_big_integer(&self) -> Option<BigInt> {
+        match self.node() {
+            PySnapshotNode::Integer(value) => Some(BigInt::from(*value)),
+            PySnapshotNode::BigInteger(value) => Some(value.clone()),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    fn as_null(&self) -> Option<()> {
+        if let PySnapshotNode::Null | PySnapshotNode::Failure { .. } = self.node() {
+            Some(())
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_number(&self) -> Option<f64> {
+        match self.node() {
+            #[allow(clippy::cast_precision_loss)]
+            PySnapshotNode::Integer(value) => Some(*value as f64),
+            PySnapshotNode::Number(value, _) => Some(*value),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    fn as_number_str(&self) -> Option<Cow<str>> {
+        if let PySnapshotNode::Number(_, Some(number_str)) = self.node() {
+            Some(Cow::Borrowed(number_str))
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_object(&self) -> Option<JsonMap<Self>> {
+        if let PySnapshotNode::Object { .. } = self.node() {
+            Some(JsonMap::new(self))
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_string(&self) -> Option<&str> {
+        if let PySnapshotNode::String(value) = self.node() {
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn get_attribute(&self, attribute_name: &str) -> Option<&Self> {
+        if let PySnapshotNode::Object { entries, positions } = self.node() {
+            positions.get(attribute_name).map(|position| &entries[*position].1)
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn get_index(&self, index: usize) -> Option<&Self> {
+        if let PySnapshotNode::Array(items) = self.node() {
+            items.get(index)
+        } else {
+            None
+        }
+    }
+}
+
+impl ThreadSafeJsonType for PySnapshot {}
+
 #[cfg(test)]
 fn perform_python_check(python_code_string: &str, check: impl Fn(&PyAny)) {
     let gil = Python::acquire_gil();
@@ -199,7 +927,10 @@ mod tests_json_map_trait {
 #[cfg(test)]
 mod tests_primitive_type_trait {
     use super::perform_python_check;
-    use crate::json_type::{JsonType, PrimitiveType};
+    use crate::{
+        json_type::{JsonType, PrimitiveType, ToRustType},
+        rust_type_impl::{Decimal, RustType},
+    };
     use test_case::test_case;
 
     #[test_case("[]", PrimitiveType::Array)]
@@ -354,6 +1085,27 @@ mod tests_primitive_type_trait {
         perform_python_check(python_code_string, |python_object_ref| assert_eq!(JsonType::as_number(python_object_ref), expected_value))
     }
 
+    #[test_case("__import__('decimal').Decimal('1.10')", Some("1.10"))]
+    #[test_case("__import__('decimal').Decimal('NaN')", None)]
+    #[test_case("1.2", None)]
+    #[test_case("'1'", None)]
+    fn test_as_number_str(python_code_string: &str, expected_value: Option<&str>) {
+        perform_python_check(python_code_string, |python_object_ref| {
+            assert_eq!(JsonType::as_number_str(python_object_ref).as_deref(), expected_value)
+        })
+    }
+
+    #[test]
+    fn test_decimal_to_rust_type() {
+        perform_python_check("__import__('decimal').Decimal('0.1000000000000000055511151231257827')", |python_object_ref| {
+            assert_eq!(JsonType::primitive_type(python_object_ref), PrimitiveType::Number);
+            assert_eq!(
+                python_object_ref.to_rust_type(),
+                RustType::Decimal(Decimal::new("0.1000000000000000055511151231257827").unwrap())
+            );
+        })
+    }
+
     #[test_case("1", false)]
     #[test_case("1.2", false)]
     #[test_case("{'1': 1}", true)]
@@ -411,6 +1163,108 @@ mod tests_json_map {
     }
 }
 
+#[cfg(all(test, feature = "big_integer"))]
+mod tests_big_integer {
+    use super::perform_python_check;
+    use crate::{
+        json_type::{JsonType, PrimitiveType, ToRustType},
+        rust_type_impl::RustType,
+    };
+    use num_bigint::BigInt;
+    use test_case::test_case;
+
+    #[test_case("1", Some(BigInt::from(1)))]
+    #[test_case("-2 ** 200", Some(-BigInt::from(2).pow(200)))]
+    #[test_case("True", None)]
+    #[test_case("1.2", None)]
+    #[test_case("'1'", None)]
+    fn test_as_big_integer(python_code_string: &str, expected_value: Option<BigInt>) {
+        perform_python_check(python_code_string, |python_object_ref| {
+            assert_eq!(JsonType::as_big_integer(python_object_ref), expected_value)
+        })
+    }
+
+    #[test]
+    fn test_integers_above_i128_are_preserved() {
+        perform_python_check("2 ** 200", |python_object_ref| {
+            assert_eq!(JsonType::as_integer(python_object_ref), None);
+            assert_eq!(JsonType::primitive_type(python_object_ref), PrimitiveType::Integer);
+            assert_eq!(python_object_ref.to_rust_type(), RustType::BigInteger(BigInt::from(2).pow(200)));
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests_try_json_type {
+    use super::perform_python_check;
+    use crate::{
+        error::Error,
+        json_type::{try_get_fragment, JsonMapTrait, JsonType, ToRustType, TryJsonMapTrait, TryJsonType},
+    };
+
+    const FAILING_SEQUENCE: &str = "type('FailingSequence', (list,), {'__iter__': lambda self: (1 // item for item in [1, 0]), '__getitem__': lambda self, index: 1 // 0})([1, 2])";
+
+    #[test]
+    fn test_try_as_array_reports_iteration_failures() {
+        perform_python_check(FAILING_SEQUENCE, |python_object_ref| {
+            assert_eq!(
+                python_object_ref.try_as_array().map(|array| array.map(Iterator::count)).unwrap_err(),
+                Error::backend("pyo3", "/0", "ZeroDivisionError('integer division or modulo by zero')")
+            );
+        })
+    }
+
+    #[test]
+    fn test_try_get_index_reports_failures() {
+        perform_python_check(FAILING_SEQUENCE, |python_object_ref| {
+            assert_eq!(
+                python_object_ref.try_get_index(0).unwrap_err(),
+                Error::backend("pyo3", "", "ZeroDivisionError('integer division or modulo by zero')")
+            );
+        });
+        perform_python_check("[0, 1, 2]", |python_object_ref| {
+            assert_eq!(python_object_ref.try_get_index(1).map(|value| value.and_then(JsonType::as_integer)), Ok(Some(1)));
+            assert_eq!(python_object_ref.try_get_index(4).map(|value| value.is_some()), Ok(false));
+        });
+    }
+
+    #[test]
+    fn test_try_get_fragment_reports_failures() {
+        perform_python_check(&format!("{{'key': {}}}", FAILING_SEQUENCE), |python_object_ref| {
+            assert_eq!(
+                try_get_fragment(python_object_ref, "/key/1").map(|_| ()),
+                Err(Error::backend("pyo3", "/key", "ZeroDivisionError('integer division or modulo by zero')"))
+            );
+        });
+    }
+
+    #[test]
+    fn test_try_items_reports_non_string_keys() {
+        perform_python_check("{'key': 1, 2: 3}", |python_object_ref| {
+            // The infallible version skips the non string keys
+            assert_eq!(python_object_ref.as_object().unwrap().items().count(), 1);
+            assert_eq!(python_object_ref.to_rust_type(), rust_type!({"key": 1}));
+            assert_eq!(
+                python_object_ref.as_object().unwrap().try_items().map(Iterator::count).unwrap_err(),
+                Error::backend("pyo3", "", "Dictionary key `2` is not a string")
+            );
+        })
+    }
+
+    #[test]
+    fn test_try_to_rust_type_reports_path_of_the_failure() {
+        perform_python_check("{'key': [0, {1: 1}]}", |python_object_ref| {
+            assert_eq!(
+                python_object_ref.try_to_rust_type(),
+                Err(Error::backend("pyo3", "/key/1", "Dictionary key `1` is not a string"))
+            );
+        });
+        perform_python_check("{'key': [0, {'1': 1}]}", |python_object_ref| {
+            assert_eq!(python_object_ref.try_to_rust_type(), Ok(rust_type!({"key": [0, {"1": 1}]})));
+        });
+    }
+}
+
 #[cfg(test)]
 mod tests_to_json_string {
     use super::perform_python_check;
@@ -435,3 +1289,320 @@ mod tests_to_json_string {
         });
     }
 }
+
+#[cfg(test)]
+mod tests_to_pyobject {
+    use super::to_pyobject;
+    use crate::{
+        json_type::ToRustType,
+        rust_type_impl::{Decimal, RustType},
+    };
+    use pyo3::{types::PyDict, AsPyRef, IntoPy, PyObject, Python, ToPyObject};
+    use test_case::test_case;
+
+    fn check_python_value(value: &PyObject, python_check: &str) {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let locals = PyDict::new(py);
+        locals.set_item("value", value).unwrap();
+        assert!(py.eval(python_check, None, Some(locals)).unwrap().extract::<bool>().unwrap(), "{} failed", python_check);
+    }
+
+    #[test_case(&rust_type!(null), "value is None")]
+    #[test_case(&rust_type!(true), "value is True")]
+    #[test_case(&rust_type!(1), "type(value) is int and value == 1")]
+    #[test_case(&rust_type!(i128::max_value()), "type(value) is int and value == 2 ** 127 - 1" ; "i128 max")]
+    #[test_case(&rust_type!(1.5), "type(value) is float and value == 1.5")]
+    #[test_case(&RustType::Decimal(Decimal::new("0.1000000000000000055511151231257827").unwrap()), "value == __import__('decimal').Decimal('0.1000000000000000055511151231257827')" ; "decimal")]
+    #[test_case(&rust_type!("string"), "value == 'string'")]
+    #[test_case(&rust_type!([1, [null]]), "value == [1, [None]]")]
+    #[test_case(&rust_type!({"key": {"nested": "value"}}), "value == {'key': {'nested': 'value'}}")]
+    fn test_to_pyobject(value: &RustType, python_check: &str) {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        check_python_value(&to_pyobject(py, value).unwrap(), python_check);
+    }
+
+    #[test]
+    fn test_rust_type_round_trip() {
+        let value = rust_type!({"array": [1, -2.5, null, true], "decimal": (RustType::Decimal(Decimal::new("0.1000000000000000055").unwrap()))});
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        assert_eq!(value.to_object(py).as_ref(py).to_rust_type(), value);
+        let python_object: PyObject = value.clone().into_py(py);
+        assert_eq!(python_object.as_ref(py).to_rust_type(), value);
+    }
+
+    #[cfg(feature = "big_integer")]
+    #[test]
+    fn test_big_integer() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        check_python_value(&to_pyobject(py, &RustType::from(num_bigint::BigInt::from(2).pow(200))).unwrap(), "value == 2 ** 200");
+    }
+}
+
+#[cfg(test)]
+mod tests_py_json_options {
+    use super::{PyBytesMode, PyJsonOptions};
+    use crate::{
+        error::Error,
+        json_type::{try_get_fragment, ToRustType, TryJsonType},
+        rust_type_impl::RustType,
+    };
+    use pyo3::{types::PyDict, Python};
+    use test_case::test_case;
+
+    const PYTHON_SETUP: &str = r#"
+import collections, dataclasses, datetime, enum, types
+
+@dataclasses.dataclass
+class Point:
+    x: int
+    y: int
+
+class Color(enum.Enum):
+    RED = 'red'
+
+class Hooked:
+    def __json__(self):
+        return {'hooked': [Point(1, 2)]}
+
+class Counted:
+    calls = 0
+    def __json__(self):
+        Counted.calls += 1
+        return [Counted.calls]
+
+class Failing:
+    def __json__(self):
+        raise RuntimeError('hook failure')
+"#;
+
+    fn check_with_options(python_code_string: &str, options: PyJsonOptions, expected_value: &RustType) {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let globals = PyDict::new(py);
+        py.run(PYTHON_SETUP, Some(globals), None).unwrap();
+        let python_object_ref = py.eval(python_code_string, Some(globals), None).unwrap();
+        assert_eq!(&options.scope(|| python_object_ref.to_rust_type()), expected_value);
+    }
+
+    #[test_case("Point(1, 2)", &rust_type!({"x": 1, "y": 2}) ; "dataclass")]
+    #[test_case("[Point]", &rust_type!([null]) ; "dataclass type")]
+    #[test_case("Color.RED", &rust_type!("red") ; "enum member")]
+    #[test_case("Hooked()", &rust_type!({"hooked": [{"x": 1, "y": 2}]}) ; "json hook")]
+    #[test_case("[datetime.date(2020, 1, 2), datetime.datetime(2020, 1, 2, 3, 4, 5), datetime.time(3, 4)]", &rust_type!(["2020-01-02", "2020-01-02T03:04:05", "03:04:00"]) ; "datetimes")]
+    #[test_case("types.MappingProxyType({'key': 'value'})", &rust_type!({"key": "value"}) ; "mapping")]
+    #[test_case("collections.OrderedDict([('key', 'value')])", &rust_type!({"key": "value"}) ; "ordered dict")]
+    #[test_case("{'key': frozenset([1])}", &rust_type!({"key": [1]}) ; "frozenset")]
+    #[test_case("[b'bytes', bytearray(b'\\xff')]", &rust_type!(["bytes", "\u{fffd}"]) ; "bytes")]
+    fn test_all_options(python_code_string: &str, expected_value: &RustType) {
+        check_with_options(python_code_string, PyJsonOptions::all(), expected_value);
+    }
+
+    #[test_case("Point(1, 2)", &rust_type!(null) ; "dataclass")]
+    #[test_case("Color.RED", &rust_type!(null) ; "enum member")]
+    #[test_case("datetime.date(2020, 1, 2)", &rust_type!(null) ; "datetime")]
+    #[test_case("types.MappingProxyType({'key': 'value'})", &rust_type!(null) ; "mapping")]
+    #[test_case("{1}", &rust_type!(null) ; "set")]
+    #[test_case("b'ab'", &rust_type!([97, 98]) ; "bytes")]
+    #[test_case("(1, __import__('decimal').Decimal('1.5'))", &rust_type!([1, 1.5]) ; "tuple")]
+    fn test_default_options(python_code_string: &str, expected_value: &RustType) {
+        check_with_options(python_code_string, PyJsonOptions::default(), expected_value);
+    }
+
+    #[test]
+    fn test_base64_bytes() {
+        let options = PyJsonOptions {
+            bytes: PyBytesMode::Base64String,
+            ..PyJsonOptions::default()
+        };
+        check_with_options("b'\\x00\\x01'", options, &rust_type!("AAE="));
+    }
+
+    #[test]
+    fn test_conversions_are_performed_once_per_scope() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let globals = PyDict::new(py);
+        py.run(PYTHON_SETUP, Some(globals), None).unwrap();
+        let python_object_ref = py.eval("{'first': Counted(), 'second': Counted()}", Some(globals), None).unwrap();
+        let value = PyJsonOptions::all().scope(|| {
+            let value = python_object_ref.to_rust_type();
+            assert_eq!(python_object_ref.to_rust_type(), value);
+            value
+        });
+        assert_eq!(value, rust_type!({"first": [1], "second": [2]}));
+        assert_eq!(py.eval("Counted.calls", Some(globals), None).unwrap().extract::<i32>().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_failing_conversions() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let globals = PyDict::new(py);
+        py.run(PYTHON_SETUP, Some(globals), None).unwrap();
+        let python_object_ref = py.eval("{'key': [1, Failing()]}", Some(globals), None).unwrap();
+        PyJsonOptions::all().scope(|| {
+            let error = python_object_ref.try_to_rust_type().unwrap_err();
+            assert_eq!(error.to_string(), Error::backend("pyo3", "/key/1", "RuntimeError('hook failure')").to_string());
+            assert!(matches!(error, Error::Backend { .. }));
+            assert_eq!(python_object_ref.to_rust_type(), rust_type!({"key": [1, null]}));
+            assert_eq!(
+                try_get_fragment(python_object_ref, "/key/1/0").map(|_| ()),
+                Err(Error::backend("pyo3", "/key/1", "RuntimeError('hook failure')"))
+            );
+        });
+    }
+
+    #[test]
+    fn test_scope_restores_previous_options() {
+        let options = PyJsonOptions {
+            enums: true,
+            ..PyJsonOptions::default()
+        };
+        let result = std::panic::catch_unwind(|| options.scope(|| panic!("failure within the scope")));
+        assert!(result.is_err());
+        check_with_options("Color.RED", PyJsonOptions::default(), &rust_type!(null));
+        check_with_options("[Color.RED]", options, &rust_type!(["red"]));
+    }
+}
+
+#[cfg(test)]
+mod tests_as_array {
+    use super::perform_python_check;
+    use crate::{
+        error::Error,
+        json_type::{JsonType, TryJsonType},
+    };
+    use test_case::test_case;
+
+    // Sequence recording the accessed indexes, failing on odd indexes
+    const RECORDING_SEQUENCE: &str = "type('RecordingSequence', (__import__('collections.abc').abc.Sequence,), {'accesses': [], '__len__': lambda self: 4, '__getitem__': lambda self, index: self.accesses.append(index) or 1 // (index % 2 == 0)})()";
+
+    #[test_case("[1, 'a', None]", 3 ; "list")]
+    #[test_case("(1, 'a')", 2 ; "tuple")]
+    #[test_case("range(5)", 5 ; "generic sequence")]
+    #[test_case("type('ListSubclass', (list,), {})([1])", 1 ; "list subclass")]
+    #[test_case("[]", 0 ; "empty list")]
+    fn test_as_array_len(python_code_string: &str, expected_len: usize) {
+        perform_python_check(python_code_string, |python_object_ref| {
+            let array = python_object_ref.as_array().unwrap();
+            assert_eq!(array.len(), expected_len);
+            assert_eq!(array.count(), expected_len);
+        })
+    }
+
+    #[test]
+    fn test_as_array_is_lazy() {
+        perform_python_check(RECORDING_SEQUENCE, |python_object_ref| {
+            let mut array = python_object_ref.as_array().unwrap();
+            assert_eq!(array.next().and_then(JsonType::as_integer), Some(1));
+            assert_eq!(array.len(), 3);
+            assert_eq!(python_object_ref.getattr("accesses").unwrap().extract::<Vec<usize>>().unwrap(), vec![0]);
+        })
+    }
+
+    #[test]
+    fn test_as_array_reports_failing_items_as_null() {
+        perform_python_check(RECORDING_SEQUENCE, |python_object_ref| {
+            assert_eq!(
+                python_object_ref.as_array().unwrap().map(JsonType::as_integer).collect::<Vec<_>>(),
+                vec![Some(1), None, Some(1), None]
+            );
+            assert!(python_object_ref.get_index(1).is_none());
+            assert!(python_object_ref.as_array().unwrap().nth(1).map_or(false, JsonType::is_null));
+        })
+    }
+
+    #[test]
+    fn test_try_as_array_reports_failing_items() {
+        perform_python_check(RECORDING_SEQUENCE, |python_object_ref| {
+            let error = python_object_ref.try_as_array().map(|_| ()).unwrap_err();
+            assert_eq!(error, Error::backend("pyo3", "/1", "ZeroDivisionError('integer division or modulo by zero')"));
+            assert_eq!(python_object_ref.try_to_rust_type().unwrap_err(), error);
+        })
+    }
+
+    #[test_case("[0, 1, 2]", 2, Some(2) ; "list")]
+    #[test_case("[0, 1, 2]", 3, None ; "list out of range")]
+    #[test_case("(0, 1, 2)", 1, Some(1) ; "tuple")]
+    #[test_case("(0, 1, 2)", 3, None ; "tuple out of range")]
+    #[test_case("range(3)", 2, Some(2) ; "generic sequence")]
+    #[test_case("type('ListSubclass', (list,), {'__getitem__': lambda self, index: 42})([0, 1])", 1, Some(42) ; "list subclass overriding getitem")]
+    fn test_get_index(python_code_string: &str, index: usize, expected_value: Option<i128>) {
+        perform_python_check(python_code_string, |python_object_ref| {
+            assert_eq!(python_object_ref.get_index(index).and_then(JsonType::as_integer), expected_value);
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests_py_snapshot {
+    use super::{PyJsonOptions, PySnapshot};
+    use crate::{
+        error::Error,
+        json_type::{JsonType, ThreadSafeJsonType, ToRustType, TryJsonType},
+    };
+    use pyo3::{types::PyDict, Python};
+    use std::thread;
+
+    fn snapshot(python_code_string: &str) -> PySnapshot {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        PySnapshot::from(py.eval(python_code_string, None, None).unwrap())
+    }
+
+    fn read_from_other_thread<T: ThreadSafeJsonType + 'static>(value: T) -> crate::RustType {
+        thread::spawn(move || value.to_rust_type()).join().unwrap()
+    }
+
+    #[test]
+    fn test_read_from_other_thread() {
+        let value = snapshot("{'key': [1, 2.5, None, True, 'string', __import__('decimal').Decimal('0.1000000000000000055')]}");
+        assert_eq!(
+            read_from_other_thread(value),
+            rust_type!({"key": [1, 2.5, null, true, "string", (crate::RustType::Decimal(crate::Decimal::new("0.1000000000000000055").unwrap()))]})
+        );
+    }
+
+    #[test]
+    fn test_read_within_allow_threads() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let value = PySnapshot::from(py.eval("{'key': [1, 2]}", None, None).unwrap());
+        // The GIL has to be released, as the spawned thread acquires it to read the Python object
+        let rust_type = py.allow_threads(move || read_from_other_thread(value));
+        assert_eq!(rust_type, rust_type!({"key": [1, 2]}));
+    }
+
+    #[test]
+    fn test_read_values_are_cached() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let locals = PyDict::new(py);
+        let python_object_ref = py.eval("{'key': 'value'}", None, None).unwrap();
+        locals.set_item("python_object", python_object_ref).unwrap();
+        let value = PySnapshot::from(python_object_ref);
+
+        assert_eq!(value.get_attribute("key").and_then(JsonType::as_string), Some("value"));
+        py.run("python_object['key'] = 'modified'; python_object['other'] = 1", None, Some(locals)).unwrap();
+        assert_eq!(value.get_attribute("key").and_then(JsonType::as_string), Some("value"));
+        assert!(!value.has_attribute("other"));
+        assert_eq!(PySnapshot::from(python_object_ref).to_rust_type(), rust_type!({"key": "modified", "other": 1}));
+    }
+
+    #[test]
+    fn test_options_are_captured() {
+        let value = PyJsonOptions::all().scope(|| snapshot("[__import__('enum').Enum('Color', 'RED').RED]"));
+        assert_eq!(read_from_other_thread(value), rust_type!([1]));
+    }
+
+    #[test]
+    fn test_failures_are_reported() {
+        let value = snapshot("{'key': [0, {1: 'value'}]}");
+        assert_eq!(value.try_to_rust_type(), Err(Error::backend("pyo3", "/key/1", "Dictionary key `1` is not a string")));
+        assert_eq!(value.to_rust_type(), rust_type!({"key": [0, null]}));
+    }
+}
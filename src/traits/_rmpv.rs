@@ -0,0 +1,453 @@
+// MessagePack to JSON mapping
+// * Map entries are exposed only if the key is a (valid UTF-8) string and the value is JSON representable.
+//   Other entries are skipped by `JsonMapTrait` and `JsonType::get_attribute`, but are still reachable
+//   via `skipped_items` (or via the underlying `rmpv::Value::as_map`).
+// * Binary blobs, extension types and strings containing invalid UTF-8 sequences (opaque values) have no
+//   JSON counterpart. Similarly to `JSON.stringify` they are omitted from objects while they are reported
+//   as `null` where omitting them would alter the document structure (array items or root value).
+//   The raw values are accessible via `rmpv::Value::as_slice` and `rmpv::Value::as_ext`.
+use crate::{
+    convert::from_json_type,
+    error::Error,
+    json_builder::{numeric_overflow, JsonBuilder},
+    json_type::{JsonMap, JsonMapTrait, JsonType, ThreadSafeJsonType, ToRustType},
+    rust_type_impl::RustType,
+};
+#[cfg(feature = "big_integer")]
+use num_bigint::BigInt;
+use rmpv::Value;
+use std::convert::TryFrom;
+
+fn is_opaque(value: &Value) -> bool {
+    match value {
+        Value::Binary(_) | Value::Ext(..) => true,
+        Value::String(string) => !string.is_str(),
+        _ => false,
+    }
+}
+
+fn is_json_map_entry((key, value): &(Value, Value)) -> bool {
+    key.as_str().is_some() && !is_opaque(value)
+}
+
+// Map entries of `value` that are not exposed via `JsonMapTrait` (ie. non string keys or opaque values)
+#[must_use]
+pub fn skipped_items(value: &Value) -> Box<dyn Iterator<Item = (&Value, &Value)> + '_> {
+    match value {
+        Value::Map(map) => Box::new(map.iter().filter(|entry| !is_json_map_entry(entry)).map(|(key, value)| (key, value))),
+        _ => Box::new(std::iter::empty()),
+    }
+}
+
+// Convert any `JsonType` into its MessagePack representation
+// Integers that do not fit into MessagePack integers (`-(2^63)` up to `(2^64)-1`) are reported as `Error::NumericOverflow`
+/// # Errors
+///
+/// Will return `Err` if `json_object` contains an integer not representable in MessagePack
+pub fn to_rmpv_value<T: JsonType>(json_object: &T) -> Result<Value, Error> {
+    from_json_type(json_object)
+}
+
+// Serialize any `JsonType` into MessagePack bytes
+/// # Errors
+///
+/// Will return `Err` if `json_object` contains an integer not representable in MessagePack
+pub fn to_msgpack<T: JsonType>(json_object: &T) -> Result<Vec<u8>, Error> {
+    let value = to_rmpv_value(json_object)?;
+    let mut bytes = Vec::new();
+    rmpv::encode::write_value(&mut bytes, &value).map_err(|error| Error::backend("rmpv", "", error))?;
+    Ok(bytes)
+}
+
+impl Into<RustType> for Value {
+    fn into(self) -> RustType {
+        self.to_rust_type()
+    }
+}
+
+impl ToRustType for Value {}
+
+impl<'json> JsonMapTrait<'json, Value> for JsonMap<'json, Value> {
+    #[must_use]
+    fn keys(&'json self) -> Box<dyn Iterator<Item = &str> + 'json> {
+        Box::new(self.items().map(|(key, _)| key))
+    }
+
+    #[must_use]
+    fn values(&'json self) -> Box<dyn Iterator<Item = &Value> + 'json> {
+        Box::new(self.items().map(|(_, value)| value))
+    }
+
+    #[must_use]
+    fn items(&'json self) -> Box<dyn Iterator<Item = (&str, &Value)> + 'json> {
+        #[allow(clippy::option_if_let_else)]
+        if let Some(map) = self.as_map() {
+            Box::new(
+                map.iter()
+                    .filter(|entry| is_json_map_entry(entry))
+                    .filter_map(|(key, value)| key.as_str().map(|key| (key, value))),
+            )
+        } else {
+            Box::new(std::iter::empty())
+        }
+    }
+}
+
+impl JsonType for Value {
+    #[must_use]
+    fn as_array<'json>(&'json self) -> Option<Box<dyn ExactSizeIterator<Item = &Self> + 'json>> {
+        self.as_array().map(|vec| {
+            let b: Box<dyn ExactSizeIterator<Item = _>> = Box::new(vec.iter());
+            b
+        })
+    }
+
+    #[must_use]
+    fn as_boolean(&self) -> Option<bool> {
+        self.as_bool()
+    }
+
+    #[must_use]
+    fn as_integer(&self) -> Option<i128> {
+        self.as_i64().map(i128::from).or_else(|| self.as_u64().map(i128::from))
+    }
+
+    #[must_use]
+    fn as_null(&self) -> Option<()> {
+        if self.is_nil() || is_opaque(self) {
+            Some(())
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_number(&self) -> Option<f64> {
+        self.as_f64()
+    }
+
+    #[must_use]
+    fn as_object(&self) -> Option<JsonMap<Self>> {
+        if self.is_map() {
+            Some(JsonMap::new(self))
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_string(&self) -> Option<&str> {
+        self.as_str()
+    }
+
+    #[must_use]
+    fn get_attribute(&self, attribute_name: &str) -> Option<&Self> {
+        self.as_map()?
+            .iter()
+            .find(|entry| is_json_map_entry(entry) && entry.0.as_str() == Some(attribute_name))
+            .map(|(_, value)| value)
+    }
+
+    #[must_use]
+    fn get_index(&self, index: usize) -> Option<&Self> {
+        self.as_array()?.get(index)
+    }
+}
+
+impl ThreadSafeJsonType for Value {}
+
+// Integers are supported from `-(2^63)` up to `(2^64)-1`
+impl JsonBuilder for Value {
+    fn null() -> Self {
+        Self::Nil
+    }
+
+    fn bool(value: bool) -> Self {
+        Self::Boolean(value)
+    }
+
+    fn integer(value: i128) -> Result<Self, Error> {
+        i64::try_from(value)
+            .map(Self::from)
+            .or_else(|_| u64::try_from(value).map(Self::from))
+            .map_err(|_| numeric_overflow(&value, "MessagePack integer"))
+    }
+
+    // Integers not fitting into i128 are surely not fitting into MessagePack integers
+    #[cfg(feature = "big_integer")]
+    fn big_integer(value: &BigInt) -> Result<Self, Error> {
+        Err(numeric_overflow(value, "MessagePack integer"))
+    }
+
+    fn number(value: f64) -> Self {
+        Self::F64(value)
+    }
+
+    fn string(value: &str) -> Self {
+        Self::from(value)
+    }
+
+    fn array<I: IntoIterator<Item = Self>>(items: I) -> Self {
+        Self::Array(items.into_iter().collect())
+    }
+
+    fn object<'key, I: IntoIterator<Item = (&'key str, Self)>>(items: I) -> Self {
+        Self::Map(items.into_iter().map(|(key, value)| (Self::from(key), value)).collect())
+    }
+}
+
+// JSON like literals built directly as `rmpv::Value` (not via `serde_json::Value`, whose numbers are serialized as
+// maps if `serde_json_arbitrary_precision` is enabled)
+#[cfg(test)]
+macro_rules! rmpv_value {
+    (null) => {
+        rmpv::Value::Nil
+    };
+    ([$($item:tt),*]) => {
+        rmpv::Value::Array(vec![$(rmpv_value!($item)),*])
+    };
+    ({$($key:literal: $value:tt),*}) => {
+        rmpv::Value::Map(vec![$((rmpv::Value::from($key), rmpv_value!($value))),*])
+    };
+    ($value:expr) => {
+        rmpv::Value::from($value)
+    };
+}
+
+#[cfg(test)]
+fn invalid_utf8_string() -> Value {
+    // fixstr of length 1 containing an invalid UTF-8 byte
+    rmpv::decode::read_value(&mut &[0xa1, 0xff][..]).unwrap()
+}
+
+#[cfg(test)]
+mod tests_rmpv_map_trait {
+    use crate::json_type::{JsonMap, JsonMapTrait};
+    use rmpv::Value;
+
+    lazy_static! {
+        static ref TESTING_MAP: Value = Value::Map(vec![
+            (Value::from("k1"), Value::from("v1")),
+            (Value::from(1), Value::from("non string key")),
+            (Value::from("binary"), Value::Binary(vec![0, 1])),
+            (Value::from("ext"), Value::Ext(1, vec![0, 1])),
+            (Value::from("k2"), Value::from("v2")),
+        ]);
+    }
+
+    #[test]
+    fn keys() {
+        let testing_map: &Value = &TESTING_MAP;
+        assert_eq!(JsonMap::new(testing_map).keys().collect::<Vec<_>>(), vec!["k1", "k2"]);
+    }
+
+    #[test]
+    fn values() {
+        let testing_map: &Value = &TESTING_MAP;
+        assert_eq!(JsonMap::new(testing_map).values().collect::<Vec<_>>(), vec![&rmpv_value!["v1"], &rmpv_value!["v2"]]);
+    }
+
+    #[test]
+    fn items() {
+        let testing_map: &Value = &TESTING_MAP;
+        assert_eq!(
+            JsonMap::new(testing_map).items().collect::<Vec<_>>(),
+            vec![("k1", &rmpv_value!["v1"]), ("k2", &rmpv_value!["v2"])]
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests_primitive_type_trait {
+    use super::invalid_utf8_string;
+    use crate::json_type::{JsonType, PrimitiveType};
+    use rmpv::Value;
+    use std::ops::Deref;
+    use test_case::test_case;
+
+    #[test_case(&rmpv_value![[]], PrimitiveType::Array)]
+    #[test_case(&rmpv_value![true], PrimitiveType::Boolean)]
+    #[test_case(&rmpv_value![1], PrimitiveType::Integer)]
+    #[test_case(&rmpv_value![null], PrimitiveType::Null)]
+    #[test_case(&rmpv_value![1.2], PrimitiveType::Number)]
+    #[test_case(&rmpv_value![{"prop": "value"}], PrimitiveType::Object)]
+    #[test_case(&rmpv_value!["string"], PrimitiveType::String)]
+    #[test_case(&Value::Binary(vec![0, 1]), PrimitiveType::Null)]
+    #[test_case(&Value::Ext(1, vec![0, 1]), PrimitiveType::Null)]
+    #[test_case(&invalid_utf8_string(), PrimitiveType::Null)]
+    fn test_primitive_type(value: &Value, expected_value: PrimitiveType) {
+        assert_eq!(JsonType::primitive_type(value), expected_value);
+    }
+
+    #[test_case(&rmpv_value![{"present": 1}], "present", Some(&rmpv_value![1]))]
+    #[test_case(&rmpv_value![{"present": 1}], "not-present", None)]
+    #[test_case(&Value::Map(vec![(Value::from("binary"), Value::Binary(vec![0]))]), "binary", None)]
+    #[test_case(&Value::Map(vec![(Value::from(1), Value::from(1))]), "1", None)]
+    fn test_get_attribute(value: &Value, attribute_name: &str, expected_value: Option<&Value>) {
+        assert_eq!(JsonType::get_attribute(value, attribute_name), expected_value);
+    }
+
+    #[test_case(&rmpv_value![[0, 1, 2]], 1, &Some(rmpv_value![1]))]
+    #[test_case(&rmpv_value![[0, 1, 2]], 4, &None)]
+    fn test_get_index(value: &Value, index: usize, expected_value: &Option<Value>) {
+        assert_eq!(JsonType::get_index(value, index), expected_value.as_ref());
+    }
+
+    #[test_case(&rmpv_value![{"present": 1}], "present", true)]
+    #[test_case(&rmpv_value![{"present": 1}], "not-present", false)]
+    #[test_case(&rmpv_value![[1, 2, 3]], "not-present", false)]
+    fn test_has_attribute(value: &Value, attr_name: &str, expected_value: bool) {
+        assert_eq!(JsonType::has_attribute(value, attr_name), expected_value);
+    }
+
+    #[test_case(&rmpv_value![[1]], &Some(vec![rmpv_value![1]]))]
+    #[test_case(&rmpv_value![[1, "a"]], &Some(vec![rmpv_value![1], rmpv_value!["a"]]))]
+    #[test_case(&rmpv_value![null], &None)]
+    fn test_as_array(value: &Value, expected_value: &Option<Vec<Value>>) {
+        assert_eq!(&JsonType::as_array(value).map(|iterator| iterator.cloned().collect()), expected_value);
+    }
+
+    #[test_case(&rmpv_value![true], Some(true))]
+    #[test_case(&rmpv_value![false], Some(false))]
+    #[test_case(&rmpv_value![1], None)]
+    fn test_as_boolean(value: &Value, expected_value: Option<bool>) {
+        assert_eq!(JsonType::as_boolean(value), expected_value);
+    }
+
+    #[test_case(&rmpv_value![1], Some(1))]
+    #[test_case(&rmpv_value![-1], Some(-1) ; "negative integer")]
+    #[test_case(&Value::from(u64::max_value()), Some(i128::from(u64::max_value())) ; "integer above i64 max")]
+    #[test_case(&rmpv_value![1.2], None)]
+    #[test_case(&rmpv_value!["1"], None)]
+    fn test_as_integer(value: &Value, expected_value: Option<i128>) {
+        assert_eq!(JsonType::as_integer(value), expected_value);
+    }
+
+    #[test_case(&rmpv_value![null], Some(()))]
+    #[test_case(&Value::Binary(vec![0, 1]), Some(()))]
+    #[test_case(&rmpv_value!["1"], None)]
+    fn test_as_null(value: &Value, expected_value: Option<()>) {
+        assert_eq!(JsonType::as_null(value), expected_value);
+    }
+
+    #[test_case(&rmpv_value![1], Some(1_f64))]
+    #[test_case(&rmpv_value![1.2], Some(1.2))]
+    #[test_case(&Value::F32(1.5), Some(1.5))]
+    #[test_case(&rmpv_value!["1"], None)]
+    fn test_as_number(value: &Value, expected_value: Option<f64>) {
+        assert_eq!(JsonType::as_number(value), expected_value);
+    }
+
+    #[test_case(&rmpv_value![1], &None)]
+    #[test_case(&rmpv_value![1.2], &None)]
+    #[test_case(&rmpv_value![{"1": 1}], &Some(rmpv_value![{"1": 1}]))]
+    fn test_as_object(value: &Value, expected_value: &Option<Value>) {
+        assert_eq!(
+            match JsonType::as_object(value) {
+                Some(ref v) => Some({
+                    #[allow(clippy::explicit_deref_methods)] // Explicit deref call is needed to ensure that &Value is retrieved from JsonMap
+                    v.deref()
+                }),
+                None => None,
+            },
+            expected_value.as_ref(),
+        );
+    }
+
+    #[test_case(&rmpv_value![1], None)]
+    #[test_case(&rmpv_value![1.2], None)]
+    #[test_case(&rmpv_value!["1"], Some("1"))]
+    #[test_case(&invalid_utf8_string(), None)]
+    fn test_as_string(value: &Value, expected_value: Option<&str>) {
+        assert_eq!(JsonType::as_string(value), expected_value);
+    }
+}
+
+#[cfg(test)]
+mod tests_opaque_values {
+    use super::{invalid_utf8_string, skipped_items};
+    use crate::json_type::{JsonTypeToString, ToRustType};
+    use rmpv::Value;
+
+    lazy_static! {
+        static ref TESTING_VALUE: Value = Value::Map(vec![
+            (Value::from("key"), Value::from("value")),
+            (Value::from(1), Value::from("non string key")),
+            (Value::from("binary"), Value::Binary(vec![0, 1])),
+            (Value::from("ext"), Value::Ext(1, vec![0, 1])),
+            (Value::from("invalid_utf8"), invalid_utf8_string()),
+            (Value::from("array"), Value::Array(vec![Value::from(1), Value::Binary(vec![0, 1])])),
+        ]);
+    }
+
+    #[test]
+    fn test_to_rust_type() {
+        assert_eq!(TESTING_VALUE.to_rust_type(), rust_type!({"key": "value", "array": [1, null]}));
+    }
+
+    #[test]
+    fn test_to_json_string() {
+        assert_eq!(Value::Array(vec![Value::Ext(1, vec![0, 1]), Value::from(1)]).to_json_string(), "[null,1]");
+    }
+
+    #[test]
+    fn test_skipped_items() {
+        assert_eq!(
+            skipped_items(&TESTING_VALUE).map(|(key, value)| (key.clone(), value.clone())).collect::<Vec<_>>(),
+            vec![
+                (Value::from(1), Value::from("non string key")),
+                (Value::from("binary"), Value::Binary(vec![0, 1])),
+                (Value::from("ext"), Value::Ext(1, vec![0, 1])),
+                (Value::from("invalid_utf8"), invalid_utf8_string()),
+            ]
+        );
+        assert_eq!(TESTING_VALUE["binary"].as_slice(), Some(&[0_u8, 1][..]));
+        assert_eq!(skipped_items(&Value::from(1)).count(), 0);
+    }
+}
+
+#[cfg(test)]
+mod tests_to_msgpack {
+    use super::{to_msgpack, to_rmpv_value};
+    use crate::{error::Error, rust_type_impl::RustType};
+    use rmpv::Value;
+
+    #[test]
+    fn test_round_trip() {
+        let value = rust_type!({"array": [1, 2.3, "a"], "boolean": false, "null": null, "object": {"key": "value"}});
+        let bytes = to_msgpack(&value).unwrap();
+        let decoded = rmpv::decode::read_value(&mut bytes.as_slice()).unwrap();
+        assert_eq!(crate::json_type::ToRustType::to_rust_type(&decoded), value);
+    }
+
+    #[test]
+    fn test_unsigned_integer() {
+        assert_eq!(to_rmpv_value(&RustType::from(u64::max_value())), Ok(Value::from(u64::max_value())));
+    }
+
+    #[test]
+    fn test_integer_overflow() {
+        assert_eq!(
+            to_msgpack(&rust_type!({"key": [RustType::from(i128::max_value())]})),
+            Err(Error::NumericOverflow {
+                path: "/key/0".to_string(),
+                value: i128::max_value().to_string(),
+                target: "MessagePack integer",
+            })
+        );
+    }
+
+    #[cfg(feature = "big_integer")]
+    #[test]
+    fn test_big_integer_overflow() {
+        let big_integer = num_bigint::BigInt::from(i128::max_value()) * num_bigint::BigInt::from(2);
+        assert_eq!(
+            to_msgpack(&RustType::from(big_integer.clone())),
+            Err(Error::NumericOverflow {
+                path: String::new(),
+                value: big_integer.to_string(),
+                target: "MessagePack integer",
+            })
+        );
+    }
+}
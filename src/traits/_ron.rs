@@ -0,0 +1,464 @@
+// RON to JSON mapping
+// `ron::Value` does not preserve struct and enum variant names, so the mapping is based on the shape of the values
+// * structs and struct-like enum variants (ie. `Point(x: 1, y: 2)`) are reported as objects
+// * tuples, tuple structs and tuple-like enum variants (ie. `Circle(1.0)`) are reported as arrays
+// * unit, unit structs and unit enum variants (ie. `Empty`) are reported as null
+// * `None` is reported as null while `Some(value)` is transparent (`value` is exposed instead)
+// * chars are reported as strings (as they are not stored as strings, they are exposed by `JsonType::as_string_cow`
+//   only while `JsonType::as_string` returns `None`)
+// * byte strings are reported as strings if they contain valid UTF-8, as null otherwise
+// * map entries are exposed only if the key is a string (non string keys are accessible via `ron::Value::Map`)
+use crate::{
+    error::Error,
+    json_builder::{numeric_overflow, JsonBuilder},
+    json_type::{JsonMap, JsonMapTrait, JsonType, PrimitiveType, ThreadSafeJsonType, ToRustType},
+    rust_type_impl::RustType,
+};
+#[cfg(feature = "big_integer")]
+use num_bigint::BigInt;
+use ron::{value::Number, Value};
+use std::{borrow::Cow, collections::HashMap, convert::TryFrom};
+
+// Strip `Some` wrappers as they do not have a JSON counterpart
+fn unwrapped(value: &Value) -> &Value {
+    match value {
+        Value::Option(Some(inner)) => unwrapped(inner),
+        _ => value,
+    }
+}
+
+fn bytes_as_str(bytes: &[u8]) -> Option<&str> {
+    std::str::from_utf8(bytes).ok()
+}
+
+fn number_as_integer(number: Number) -> Option<i128> {
+    match number {
+        Number::I8(value) => Some(i128::from(value)),
+        Number::I16(value) => Some(i128::from(value)),
+        Number::I32(value) => Some(i128::from(value)),
+        Number::I64(value) => Some(i128::from(value)),
+        Number::U8(value) => Some(i128::from(value)),
+        Number::U16(value) => Some(i128::from(value)),
+        Number::U32(value) => Some(i128::from(value)),
+        Number::U64(value) => Some(i128::from(value)),
+        // Floats (and 128 bits integers, available only if `ron/integer128` feature is enabled)
+        _ => None,
+    }
+}
+
+fn number_as_f64(number: Number) -> f64 {
+    match number {
+        // RON stores floats with the smallest precision able to represent them, so the textual representation
+        // is used to prevent artifacts (ie. 1.2_f32 would be 1.2000000476837158_f64)
+        Number::F32(value) => value.get().to_string().parse().unwrap_or_else(|_| f64::from(value.get())),
+        _ => number.into_f64(),
+    }
+}
+
+impl Into<RustType> for Value {
+    fn into(self) -> RustType {
+        self.to_rust_type()
+    }
+}
+
+impl ToRustType for Value {
+    fn to_rust_type(&self) -> RustType {
+        match unwrapped(self) {
+            Self::Bool(value) => RustType::from(*value),
+            Self::Char(value) => RustType::from(value.to_string()),
+            map @ Self::Map(_) => RustType::from(
+                JsonMap::new(map)
+                    .items()
+                    .map(|(key, value)| (key.to_string(), value.to_rust_type()))
+                    .collect::<HashMap<_, _>>(),
+            ),
+            Self::Number(number) => number_as_integer(*number).map_or_else(|| RustType::from(number_as_f64(*number)), RustType::from),
+            Self::String(value) => RustType::from(value.as_str()),
+            Self::Bytes(value) => bytes_as_str(value).map_or(RustType::Null, RustType::from),
+            Self::Seq(seq) => RustType::from(seq.iter().map(ToRustType::to_rust_type).collect::<Vec<_>>()),
+            Self::Option(_) | Self::Unit => RustType::Null,
+        }
+    }
+}
+
+impl<'json> JsonMapTrait<'json, Value> for JsonMap<'json, Value> {
+    #[must_use]
+    fn keys(&'json self) -> Box<dyn Iterator<Item = &str> + 'json> {
+        Box::new(self.items().map(|(key, _)| key))
+    }
+
+    #[must_use]
+    fn values(&'json self) -> Box<dyn Iterator<Item = &Value> + 'json> {
+        Box::new(self.items().map(|(_, value)| value))
+    }
+
+    #[must_use]
+    fn items(&'json self) -> Box<dyn Iterator<Item = (&str, &Value)> + 'json> {
+        if let Value::Map(map) = &**self {
+            Box::new(
+                map.iter()
+                    .filter_map(|(key, value)| if let Value::String(key) = key { Some((key.as_str(), value)) } else { None }),
+            )
+        } else {
+            Box::new(std::iter::empty())
+        }
+    }
+}
+
+impl JsonType for Value {
+    #[must_use]
+    fn as_array<'json>(&'json self) -> Option<Box<dyn ExactSizeIterator<Item = &Self> + 'json>> {
+        if let Self::Seq(seq) = unwrapped(self) {
+            Some(Box::new(seq.iter()))
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_boolean(&self) -> Option<bool> {
+        if let Self::Bool(value) = unwrapped(self) {
+            Some(*value)
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_integer(&self) -> Option<i128> {
+        if let Self::Number(number) = unwrapped(self) {
+            number_as_integer(*number)
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_null(&self) -> Option<()> {
+        match unwrapped(self) {
+            Self::Option(None) | Self::Unit => Some(()),
+            Self::Bytes(value) if bytes_as_str(value).is_none() => Some(()),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    fn as_number(&self) -> Option<f64> {
+        if let Self::Number(number) = unwrapped(self) {
+            Some(number_as_f64(*number))
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_object(&self) -> Option<JsonMap<Self>> {
+        let value = unwrapped(self);
+        if let Self::Map(_) = value {
+            Some(JsonMap::new(value))
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_string(&self) -> Option<&str> {
+        match unwrapped(self) {
+            Self::String(value) => Some(value),
+            Self::Bytes(value) => bytes_as_str(value),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    fn as_string_cow(&self) -> Option<Cow<str>> {
+        if let Self::Char(value) = unwrapped(self) {
+            Some(Cow::Owned(value.to_string()))
+        } else {
+            self.as_string().map(Cow::Borrowed)
+        }
+    }
+
+    #[must_use]
+    fn get_attribute(&self, attribute_name: &str) -> Option<&Self> {
+        if let Self::Map(map) = unwrapped(self) {
+            map.get(&Self::String(attribute_name.to_string()))
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn get_index(&self, index: usize) -> Option<&Self> {
+        if let Self::Seq(seq) = unwrapped(self) {
+            seq.get(index)
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn primitive_type(&self) -> PrimitiveType {
+        match unwrapped(self) {
+            Self::Seq(_) => PrimitiveType::Array,
+            Self::Bool(_) => PrimitiveType::Boolean,
+            Self::Number(number) => {
+                if number_as_integer(*number).is_some() {
+                    PrimitiveType::Integer
+                } else {
+                    PrimitiveType::Number
+                }
+            }
+            Self::Map(_) => PrimitiveType::Object,
+            Self::Char(_) | Self::String(_) => PrimitiveType::String,
+            Self::Bytes(value) if bytes_as_str(value).is_some() => PrimitiveType::String,
+            Self::Bytes(_) | Self::Option(_) | Self::Unit => PrimitiveType::Null,
+        }
+    }
+}
+
+impl ThreadSafeJsonType for Value {}
+
+// Integers are supported up to u64, null is represented as unit
+impl JsonBuilder for Value {
+    fn null() -> Self {
+        Self::Unit
+    }
+
+    fn bool(value: bool) -> Self {
+        Self::Bool(value)
+    }
+
+    fn integer(value: i128) -> Result<Self, Error> {
+        i64::try_from(value)
+            .map(Number::from)
+            .or_else(|_| u64::try_from(value).map(Number::from))
+            .map(Self::Number)
+            .map_err(|_| numeric_overflow(&value, "RON number"))
+    }
+
+    #[cfg(feature = "big_integer")]
+    fn big_integer(value: &BigInt) -> Result<Self, Error> {
+        Err(numeric_overflow(value, "RON number"))
+    }
+
+    fn number(value: f64) -> Self {
+        Self::Number(Number::from(value))
+    }
+
+    fn string(value: &str) -> Self {
+        Self::String(value.to_string())
+    }
+
+    fn array<I: IntoIterator<Item = Self>>(items: I) -> Self {
+        Self::Seq(items.into_iter().collect())
+    }
+
+    fn object<'key, I: IntoIterator<Item = (&'key str, Self)>>(items: I) -> Self {
+        Self::Map(items.into_iter().map(|(key, value)| (Self::string(key), value)).collect())
+    }
+}
+
+#[cfg(test)]
+macro_rules! ron_value {
+    ($ron:expr) => {{
+        let thing: ron::Value = ron::from_str($ron).unwrap();
+        thing
+    }};
+}
+
+#[cfg(test)]
+mod tests_ron_map_trait {
+    use crate::json_type::{JsonMap, JsonMapTrait};
+    use ron::Value;
+
+    lazy_static! {
+        static ref TESTING_MAP: Value = ron_value!(r#"{"k1": "v1", 1: "non string key", "k2": "v2"}"#);
+    }
+
+    #[test]
+    fn keys() {
+        let testing_map: &Value = &TESTING_MAP;
+        assert_eq!(JsonMap::new(testing_map).keys().collect::<Vec<_>>(), vec!["k1", "k2"]);
+    }
+
+    #[test]
+    fn values() {
+        let testing_map: &Value = &TESTING_MAP;
+        assert_eq!(JsonMap::new(testing_map).values().collect::<Vec<_>>(), vec![&ron_value!(r#""v1""#), &ron_value!(r#""v2""#)]);
+    }
+
+    #[test]
+    fn items() {
+        let testing_map: &Value = &TESTING_MAP;
+        assert_eq!(
+            JsonMap::new(testing_map).items().collect::<Vec<_>>(),
+            vec![("k1", &ron_value!(r#""v1""#)), ("k2", &ron_value!(r#""v2""#))]
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests_primitive_type_trait {
+    use crate::json_type::{JsonType, PrimitiveType};
+    use ron::Value;
+    use std::ops::Deref;
+    use test_case::test_case;
+
+    #[test_case(&ron_value!("[]"), PrimitiveType::Array)]
+    #[test_case(&ron_value!("(1, 2)"), PrimitiveType::Array ; "tuple")]
+    #[test_case(&ron_value!("true"), PrimitiveType::Boolean)]
+    #[test_case(&ron_value!("1"), PrimitiveType::Integer)]
+    #[test_case(&ron_value!("None"), PrimitiveType::Null)]
+    #[test_case(&ron_value!("()"), PrimitiveType::Null ; "unit")]
+    #[test_case(&ron_value!("1.2"), PrimitiveType::Number)]
+    #[test_case(&ron_value!(r#"{"prop": "value"}"#), PrimitiveType::Object)]
+    #[test_case(&ron_value!("Point(x: 1)"), PrimitiveType::Object ; "struct like")]
+    #[test_case(&ron_value!(r#""string""#), PrimitiveType::String)]
+    #[test_case(&ron_value!("'c'"), PrimitiveType::String ; "char")]
+    #[test_case(&ron_value!("Some(1)"), PrimitiveType::Integer ; "option")]
+    fn test_primitive_type(value: &Value, expected_value: PrimitiveType) {
+        assert_eq!(JsonType::primitive_type(value), expected_value);
+    }
+
+    #[test_case(&ron_value!(r#"{"present": 1}"#), "present", Some(&ron_value!("1")))]
+    #[test_case(&ron_value!(r#"{"present": 1}"#), "not-present", None)]
+    #[test_case(&ron_value!("Some(Point(x: 1))"), "x", Some(&ron_value!("1")) ; "optional struct")]
+    fn test_get_attribute(value: &Value, attribute_name: &str, expected_value: Option<&Value>) {
+        assert_eq!(JsonType::get_attribute(value, attribute_name), expected_value);
+    }
+
+    #[test_case(&ron_value!("[0, 1, 2]"), 1, &Some(ron_value!("1")))]
+    #[test_case(&ron_value!("[0, 1, 2]"), 4, &None)]
+    fn test_get_index(value: &Value, index: usize, expected_value: &Option<Value>) {
+        assert_eq!(JsonType::get_index(value, index), expected_value.as_ref());
+    }
+
+    #[test_case(&ron_value!(r#"{"present": 1}"#), "present", true)]
+    #[test_case(&ron_value!(r#"{"present": 1}"#), "not-present", false)]
+    #[test_case(&ron_value!("[1, 2, 3]"), "not-present", false)]
+    fn test_has_attribute(value: &Value, attr_name: &str, expected_value: bool) {
+        assert_eq!(JsonType::has_attribute(value, attr_name), expected_value);
+    }
+
+    #[test_case(&ron_value!("[1]"), &Some(vec![ron_value!("1")]))]
+    #[test_case(&ron_value!(r#"[1, "a"]"#), &Some(vec![ron_value!("1"), ron_value!(r#""a""#)]))]
+    #[test_case(&ron_value!("None"), &None)]
+    fn test_as_array(value: &Value, expected_value: &Option<Vec<Value>>) {
+        assert_eq!(&JsonType::as_array(value).map(|iterator| iterator.cloned().collect()), expected_value);
+    }
+
+    #[test_case(&ron_value!("true"), Some(true))]
+    #[test_case(&ron_value!("false"), Some(false))]
+    #[test_case(&ron_value!("1"), None)]
+    fn test_as_boolean(value: &Value, expected_value: Option<bool>) {
+        assert_eq!(JsonType::as_boolean(value), expected_value);
+    }
+
+    #[test_case(&ron_value!("1"), Some(1))]
+    #[test_case(&ron_value!("-1"), Some(-1) ; "negative integer")]
+    #[test_case(&ron_value!("18446744073709551615"), Some(i128::from(u64::max_value())) ; "integer above i64 max")]
+    #[test_case(&ron_value!("1.2"), None)]
+    #[test_case(&ron_value!(r#""1""#), None)]
+    fn test_as_integer(value: &Value, expected_value: Option<i128>) {
+        assert_eq!(JsonType::as_integer(value), expected_value);
+    }
+
+    #[test_case(&ron_value!("None"), Some(()))]
+    #[test_case(&ron_value!("Some(None)"), Some(()) ; "nested option")]
+    #[test_case(&ron_value!("Empty"), Some(()) ; "unit variant")]
+    #[test_case(&ron_value!(r#""1""#), None)]
+    fn test_as_null(value: &Value, expected_value: Option<()>) {
+        assert_eq!(JsonType::as_null(value), expected_value);
+    }
+
+    #[test_case(&ron_value!("1"), Some(1_f64))]
+    #[test_case(&ron_value!("1.2"), Some(1.2))]
+    #[test_case(&ron_value!("1.000000000000001"), Some(1.000_000_000_000_001) ; "double precision")]
+    #[test_case(&ron_value!(r#""1""#), None)]
+    fn test_as_number(value: &Value, expected_value: Option<f64>) {
+        assert_eq!(JsonType::as_number(value), expected_value);
+    }
+
+    #[test_case(&ron_value!("1"), &None)]
+    #[test_case(&ron_value!("1.2"), &None)]
+    #[test_case(&ron_value!(r#"{"1": 1}"#), &Some(ron_value!(r#"{"1": 1}"#)))]
+    #[test_case(&ron_value!(r#"Some({"1": 1})"#), &Some(ron_value!(r#"{"1": 1}"#)) ; "optional map")]
+    fn test_as_object(value: &Value, expected_value: &Option<Value>) {
+        assert_eq!(
+            match JsonType::as_object(value) {
+                Some(ref v) => Some({
+                    #[allow(clippy::explicit_deref_methods)] // Explicit deref call is needed to ensure that &Value is retrieved from JsonMap
+                    v.deref()
+                }),
+                None => None,
+            },
+            expected_value.as_ref(),
+        );
+    }
+
+    #[test_case(&ron_value!("1"), None)]
+    #[test_case(&ron_value!("1.2"), None)]
+    #[test_case(&ron_value!(r#""1""#), Some("1"))]
+    #[test_case(&ron_value!(r#"b"bytes""#), Some("bytes") ; "utf8 bytes")]
+    #[test_case(&ron_value!("'c'"), None ; "char")]
+    fn test_as_string(value: &Value, expected_value: Option<&str>) {
+        assert_eq!(JsonType::as_string(value), expected_value);
+    }
+
+    #[test_case(&ron_value!("1"), None)]
+    #[test_case(&ron_value!(r#""1""#), Some("1"))]
+    #[test_case(&ron_value!(r#"b"bytes""#), Some("bytes") ; "utf8 bytes")]
+    #[test_case(&ron_value!("'c'"), Some("c") ; "char")]
+    #[test_case(&ron_value!("Some('é')"), Some("é") ; "optional char")]
+    fn test_as_string_cow(value: &Value, expected_value: Option<&str>) {
+        assert_eq!(value.as_string_cow().as_deref(), expected_value);
+    }
+}
+
+#[cfg(test)]
+mod tests_to_rust_type {
+    use crate::{convert::from_json_type, json_type::ToRustType, rust_type_impl::RustType};
+
+    #[test]
+    fn smoke_test() {
+        let value = ron_value!(
+            r#"Level(
+                name: "intro",
+                spawn: (1, 2),
+                boss: Some(Dragon(hp: 100, element: 'f')),
+                music: None,
+                weather: Sunny,
+                shape: Circle(1.5),
+                seed: b"\xff",
+            )"#
+        );
+        assert_eq!(
+            value.to_rust_type(),
+            rust_type!({
+                "name": "intro",
+                "spawn": [1, 2],
+                "boss": {"hp": 100, "element": "f"},
+                "music": null,
+                "weather": null,
+                "shape": [1.5],
+                "seed": null,
+            })
+        );
+        assert_eq!(from_json_type::<RustType, _>(&value), Ok(value.to_rust_type()));
+    }
+}
+
+#[cfg(test)]
+mod tests_to_json_string {
+    use crate::json_type::JsonTypeToString;
+
+    #[test]
+    fn smoke_test() {
+        let value = ron_value!(r#"[{"array": []}, {"boolean": false}, {"char": 'c'}, {"float": 2.3}, {"integer": 1}, {"null": None}, {"object": {}}, {"string": "string"}]"#);
+        assert_eq!(
+            value.to_json_string(),
+            r#"[{"array":[]},{"boolean":false},{"char":"c"},{"float":2.3},{"integer":1},{"null":null},{"object":{}},{"string":"string"}]"#
+        );
+    }
+}
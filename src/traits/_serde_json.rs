@@ -1,8 +1,23 @@
 use crate::{
+    error::Error,
+    json_builder::{numeric_overflow, JsonBuilder},
     json_type::{JsonMap, JsonMapTrait, JsonType, JsonTypeToString, ThreadSafeJsonType, ToRustType},
     rust_type_impl::RustType,
 };
+#[cfg(feature = "big_integer")]
+use num_bigint::BigInt;
 use serde_json::Value;
+use std::{borrow::Cow, convert::TryFrom};
+
+#[cfg(feature = "serde_json_arbitrary_precision")]
+fn exact_number(number_str: &str) -> Option<Value> {
+    number_str.parse().ok().map(Value::Number)
+}
+
+#[cfg(not(feature = "serde_json_arbitrary_precision"))]
+const fn exact_number(_number_str: &str) -> Option<Value> {
+    None
+}
 
 impl Into<RustType> for Value {
     fn into(self) -> RustType {
@@ -75,7 +90,7 @@ impl JsonType for Value {
 
     #[must_use]
     fn as_integer(&self) -> Option<i128> {
-        self.as_i64().map(i128::from)
+        self.as_i64().map(i128::from).or_else(|| self.as_u64().map(i128::from))
     }
 
     #[must_use]
@@ -88,6 +103,18 @@ impl JsonType for Value {
         self.as_f64()
     }
 
+    #[must_use]
+    fn as_number_str(&self) -> Option<Cow<str>> {
+        // The original representation is preserved only with serde_json `arbitrary_precision` feature
+        #[cfg(feature = "serde_json_arbitrary_precision")]
+        {
+            if let Self::Number(number) = self {
+                return Some(Cow::Owned(number.to_string()));
+            }
+        }
+        None
+    }
+
     #[must_use]
     fn as_object(&self) -> Option<JsonMap<Self>> {
         if self.is_object() {
@@ -120,6 +147,52 @@ impl JsonType for Value {
 
 impl ThreadSafeJsonType for Value {}
 
+// Integers are supported up to u64 (any integer and exact decimals with `serde_json_arbitrary_precision` feature)
+impl JsonBuilder for Value {
+    fn null() -> Self {
+        Self::Null
+    }
+
+    fn bool(value: bool) -> Self {
+        Self::Bool(value)
+    }
+
+    fn integer(value: i128) -> Result<Self, Error> {
+        i64::try_from(value)
+            .map(Self::from)
+            .or_else(|_| u64::try_from(value).map(Self::from))
+            .ok()
+            .or_else(|| exact_number(&value.to_string()))
+            .ok_or_else(|| numeric_overflow(&value, "serde_json number"))
+    }
+
+    #[cfg(feature = "big_integer")]
+    fn big_integer(value: &BigInt) -> Result<Self, Error> {
+        exact_number(&value.to_string()).ok_or_else(|| numeric_overflow(value, "serde_json number"))
+    }
+
+    // Non finite numbers are reported as null, as `serde_json` does
+    fn number(value: f64) -> Self {
+        Self::from(value)
+    }
+
+    fn decimal(value: f64, number_str: &str) -> Self {
+        exact_number(number_str).unwrap_or_else(|| Self::number(value))
+    }
+
+    fn string(value: &str) -> Self {
+        Self::from(value)
+    }
+
+    fn array<I: IntoIterator<Item = Self>>(items: I) -> Self {
+        Self::Array(items.into_iter().collect())
+    }
+
+    fn object<'key, I: IntoIterator<Item = (&'key str, Self)>>(items: I) -> Self {
+        Self::Object(items.into_iter().map(|(key, value)| (key.to_string(), value)).collect())
+    }
+}
+
 #[cfg(test)]
 mod tests_json_map_trait {
     use crate::json_type::{JsonMap, JsonMapTrait};
@@ -277,6 +350,8 @@ mod tests_primitive_type_trait {
     }
 
     #[test_case(&json![1], Some(1))]
+    #[test_case(&json![-1], Some(-1) ; "negative integer")]
+    #[test_case(&json![u64::max_value()], Some(i128::from(u64::max_value())) ; "integer above i64 max")]
     #[test_case(&json![1.2], None)]
     #[test_case(&json!["1"], None)]
     fn test_as_integer(value: &Value, expected_value: Option<i128>) {
@@ -296,6 +371,21 @@ mod tests_primitive_type_trait {
         assert_eq!(JsonType::as_number(value), expected_value);
     }
 
+    #[cfg(feature = "serde_json_arbitrary_precision")]
+    #[test_case("1.10", Some("1.10"))]
+    #[test_case("0.1000000000000000055511151231257827", Some("0.1000000000000000055511151231257827"))]
+    #[test_case("\"1\"", None)]
+    fn test_as_number_str(json_string: &str, expected_value: Option<&str>) {
+        let value: Value = serde_json::from_str(json_string).unwrap();
+        assert_eq!(JsonType::as_number_str(&value).as_deref(), expected_value);
+    }
+
+    #[cfg(not(feature = "serde_json_arbitrary_precision"))]
+    #[test]
+    fn test_as_number_str_is_not_available_without_arbitrary_precision() {
+        assert_eq!(JsonType::as_number_str(&json![1.1]), None);
+    }
+
     #[test_case(&json![1], &None)]
     #[test_case(&json![1.2], &None)]
     #[test_case(&json![{"1": 1}], &Some(json![{"1": 1}]))]
@@ -1,8 +1,13 @@
 use crate::{
-    json_type::{JsonMap, JsonMapTrait, JsonType, ThreadSafeJsonType, ToRustType},
+    error::Error,
+    json_builder::{numeric_overflow, JsonBuilder},
+    json_type::{JsonMap, JsonMapTrait, JsonType, ThreadSafeJsonType, ToRustType, TryJsonMapTrait},
     rust_type_impl::RustType,
 };
+#[cfg(feature = "big_integer")]
+use num_bigint::BigInt;
 use serde_yaml::Value;
+use std::convert::TryFrom;
 
 impl Into<RustType> for Value {
     fn into(self) -> RustType {
@@ -12,12 +17,15 @@ impl Into<RustType> for Value {
 
 impl ToRustType for Value {}
 
+// YAML mappings with non-string keys (ie. `8080: web`) have no JSON counterpart: the infallible accessors (and so
+// `ToRustType`) skip the entries with non-string keys, while `TryJsonMapTrait` (and so `TryJsonType::try_to_rust_type`)
+// reports them as `Error::Backend`.
 impl<'json> JsonMapTrait<'json, Value> for JsonMap<'json, Value> {
     #[must_use]
     fn keys(&'json self) -> Box<dyn Iterator<Item = &str> + 'json> {
         #[allow(clippy::option_if_let_else)]
         if let Some(obj) = self.as_mapping() {
-            Box::new(obj.iter().map(|(key, _)| (key.as_str().unwrap())))
+            Box::new(obj.iter().filter_map(|(key, _)| key.as_str()))
         } else {
             #[allow(unsafe_code)]
             unsafe {
@@ -30,7 +38,7 @@ impl<'json> JsonMapTrait<'json, Value> for JsonMap<'json, Value> {
     fn values(&'json self) -> Box<dyn Iterator<Item = &Value> + 'json> {
         #[allow(clippy::option_if_let_else)]
         if let Some(obj) = self.as_mapping() {
-            Box::new(obj.iter().map(|(_, value)| value))
+            Box::new(obj.iter().filter_map(|(key, value)| key.as_str().map(|_| value)))
         } else {
             #[allow(unsafe_code)]
             unsafe {
@@ -43,7 +51,7 @@ impl<'json> JsonMapTrait<'json, Value> for JsonMap<'json, Value> {
     fn items(&'json self) -> Box<dyn Iterator<Item = (&str, &Value)> + 'json> {
         #[allow(clippy::option_if_let_else)]
         if let Some(obj) = self.as_mapping() {
-            Box::new(obj.iter().map(|(key, value)| (key.as_str().unwrap(), value)))
+            Box::new(obj.iter().filter_map(|(key, value)| key.as_str().map(|key| (key, value))))
         } else {
             #[allow(unsafe_code)]
             unsafe {
@@ -53,6 +61,42 @@ impl<'json> JsonMapTrait<'json, Value> for JsonMap<'json, Value> {
     }
 }
 
+impl<'json> TryJsonMapTrait<'json, Value> for JsonMap<'json, Value> {
+    fn try_keys(&'json self) -> Result<Box<dyn Iterator<Item = &str> + 'json>, Error> {
+        self.try_items().map(|items| {
+            let b: Box<dyn Iterator<Item = _>> = Box::new(items.map(|(key, _)| key));
+            b
+        })
+    }
+
+    fn try_values(&'json self) -> Result<Box<dyn Iterator<Item = &Value> + 'json>, Error> {
+        self.try_items().map(|items| {
+            let b: Box<dyn Iterator<Item = _>> = Box::new(items.map(|(_, value)| value));
+            b
+        })
+    }
+
+    #[allow(clippy::option_if_let_else)]
+    fn try_items(&'json self) -> Result<Box<dyn Iterator<Item = (&str, &Value)> + 'json>, Error> {
+        match self.as_mapping() {
+            Some(obj) => obj
+                .iter()
+                .map(|(key, value)| {
+                    key.as_str().map(|key_string| (key_string, value)).ok_or_else(|| {
+                        let key_string = serde_yaml::to_string(key).map_or_else(|_| format!("{:?}", key), |key_string| key_string.trim_end().to_string());
+                        Error::backend("serde_yaml", "", format!("Mapping key `{}` is not a string", key_string))
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .map(|items| {
+                    let b: Box<dyn Iterator<Item = _>> = Box::new(items.into_iter());
+                    b
+                }),
+            None => Ok(Box::new(std::iter::empty())),
+        }
+    }
+}
+
 impl JsonType for Value {
     #[must_use]
     fn as_array<'json>(&'json self) -> Option<Box<dyn ExactSizeIterator<Item = &Self> + 'json>> {
@@ -69,7 +113,7 @@ impl JsonType for Value {
 
     #[must_use]
     fn as_integer(&self) -> Option<i128> {
-        self.as_i64().map(i128::from)
+        self.as_i64().map(i128::from).or_else(|| self.as_u64().map(i128::from))
     }
 
     #[must_use]
@@ -114,6 +158,45 @@ impl JsonType for Value {
 
 impl ThreadSafeJsonType for Value {}
 
+// Integers are supported up to u64
+impl JsonBuilder for Value {
+    fn null() -> Self {
+        Self::Null
+    }
+
+    fn bool(value: bool) -> Self {
+        Self::Bool(value)
+    }
+
+    fn integer(value: i128) -> Result<Self, Error> {
+        i64::try_from(value)
+            .map(Self::from)
+            .or_else(|_| u64::try_from(value).map(Self::from))
+            .map_err(|_| numeric_overflow(&value, "serde_yaml number"))
+    }
+
+    #[cfg(feature = "big_integer")]
+    fn big_integer(value: &BigInt) -> Result<Self, Error> {
+        Err(numeric_overflow(value, "serde_yaml number"))
+    }
+
+    fn number(value: f64) -> Self {
+        Self::from(value)
+    }
+
+    fn string(value: &str) -> Self {
+        Self::from(value)
+    }
+
+    fn array<I: IntoIterator<Item = Self>>(items: I) -> Self {
+        Self::Sequence(items.into_iter().collect())
+    }
+
+    fn object<'key, I: IntoIterator<Item = (&'key str, Self)>>(items: I) -> Self {
+        Self::Mapping(items.into_iter().map(|(key, value)| (Self::from(key), value)).collect())
+    }
+}
+
 #[cfg(test)]
 macro_rules! yaml {
     ($($json:tt)+) => {{
@@ -129,7 +212,10 @@ macro_rules! yaml {
 
 #[cfg(test)]
 mod tests_yaml_map_trait {
-    use crate::json_type::{JsonMap, JsonMapTrait};
+    use crate::{
+        error::Error,
+        json_type::{JsonMap, JsonMapTrait, JsonType, ToRustType, TryJsonMapTrait, TryJsonType},
+    };
     use serde_yaml::Value;
 
     lazy_static! {
@@ -153,6 +239,19 @@ mod tests_yaml_map_trait {
         let testing_map: &Value = &TESTING_MAP;
         assert_eq!(JsonMap::new(testing_map).items().collect::<Vec<_>>(), vec![("k1", &yaml!["v1"]), ("k2", &yaml!["v2"])]);
     }
+    #[test]
+    fn non_string_keys() {
+        let testing_map: Value = serde_yaml::from_str("ports:\n  8080: web\n  name: ports\n").unwrap();
+        let ports = testing_map.get_attribute("ports").unwrap();
+        assert_eq!(JsonMap::new(ports).keys().collect::<Vec<_>>(), vec!["name"]);
+        assert_eq!(JsonMap::new(ports).values().collect::<Vec<_>>(), vec![&yaml!["ports"]]);
+        assert_eq!(JsonMap::new(ports).items().collect::<Vec<_>>(), vec![("name", &yaml!["ports"])]);
+        assert_eq!(testing_map.to_rust_type(), rust_type!({"ports": {"name": "ports"}}));
+
+        let error = Error::backend("serde_yaml", "", "Mapping key `8080` is not a string");
+        assert_eq!(JsonMap::new(ports).try_items().map(|_| ()).unwrap_err(), error);
+        assert_eq!(testing_map.try_to_rust_type(), Err(error.prefix_path("ports")));
+    }
 }
 
 #[cfg(test)]
@@ -284,6 +383,8 @@ mod tests_primitive_type_trait {
     }
 
     #[test_case(&yaml![1], Some(1))]
+    #[test_case(&yaml![-1], Some(-1) ; "negative integer")]
+    #[test_case(&yaml![u64::max_value()], Some(i128::from(u64::max_value())) ; "integer above i64 max")]
     #[test_case(&yaml![1.2], None)]
     #[test_case(&yaml!["1"], None)]
     fn test_as_integer(value: &Value, expected_value: Option<i128>) {
@@ -0,0 +1,512 @@
+use crate::{
+    error::Error,
+    json_builder::{numeric_overflow, JsonBuilder},
+    json_type::{JsonMap, JsonMapTrait, JsonType, JsonTypeToString, ThreadSafeJsonType, ToRustType},
+    rust_type_impl::RustType,
+};
+#[cfg(feature = "big_integer")]
+use num_bigint::BigInt;
+use simd_json::{
+    prelude::{ValueAsScalar, Writable},
+    BorrowedValue, OwnedValue,
+};
+use std::convert::TryFrom;
+
+impl Into<RustType> for OwnedValue {
+    fn into(self) -> RustType {
+        self.to_rust_type()
+    }
+}
+
+impl ToRustType for OwnedValue {}
+
+impl JsonTypeToString for OwnedValue {
+    fn to_json_string(&self) -> String {
+        self.encode()
+    }
+}
+
+impl<'json> JsonMapTrait<'json, OwnedValue> for JsonMap<'json, OwnedValue> {
+    #[must_use]
+    fn keys(&'json self) -> Box<dyn Iterator<Item = &str> + 'json> {
+        if let OwnedValue::Object(obj) = &**self {
+            Box::new(obj.keys().map(AsRef::as_ref))
+        } else {
+            Box::new(std::iter::empty())
+        }
+    }
+
+    #[must_use]
+    fn values(&'json self) -> Box<dyn Iterator<Item = &OwnedValue> + 'json> {
+        if let OwnedValue::Object(obj) = &**self {
+            Box::new(obj.values())
+        } else {
+            Box::new(std::iter::empty())
+        }
+    }
+
+    #[must_use]
+    fn items(&'json self) -> Box<dyn Iterator<Item = (&str, &OwnedValue)> + 'json> {
+        if let OwnedValue::Object(obj) = &**self {
+            Box::new(obj.iter().map(|(k, v)| (k.as_ref(), v)))
+        } else {
+            Box::new(std::iter::empty())
+        }
+    }
+}
+
+impl JsonType for OwnedValue {
+    #[must_use]
+    fn as_array<'json>(&'json self) -> Option<Box<dyn ExactSizeIterator<Item = &Self> + 'json>> {
+        if let Self::Array(array) = self {
+            Some(Box::new(array.iter()))
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_boolean(&self) -> Option<bool> {
+        if let Self::Static(node) = self {
+            node.as_bool()
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_integer(&self) -> Option<i128> {
+        if let Self::Static(node) = self {
+            node.as_i128().or_else(|| node.as_u64().map(i128::from))
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_null(&self) -> Option<()> {
+        if let Self::Static(node) = self {
+            node.as_null()
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_number(&self) -> Option<f64> {
+        if let Self::Static(node) = self {
+            node.cast_f64()
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_object(&self) -> Option<JsonMap<Self>> {
+        if let Self::Object(_) = self {
+            Some(JsonMap::new(self))
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_string(&self) -> Option<&str> {
+        if let Self::String(string) = self {
+            Some(string)
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn get_attribute(&self, attribute_name: &str) -> Option<&Self> {
+        if let Self::Object(object) = self {
+            object.get(attribute_name)
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn get_index(&self, index: usize) -> Option<&Self> {
+        if let Self::Array(array) = self {
+            array.get(index)
+        } else {
+            None
+        }
+    }
+}
+
+impl ThreadSafeJsonType for OwnedValue {}
+
+// Integers are supported up to u64
+impl JsonBuilder for OwnedValue {
+    fn null() -> Self {
+        Self::from(())
+    }
+
+    fn bool(value: bool) -> Self {
+        Self::from(value)
+    }
+
+    fn integer(value: i128) -> Result<Self, Error> {
+        i64::try_from(value)
+            .map(Self::from)
+            .or_else(|_| u64::try_from(value).map(Self::from))
+            .map_err(|_| numeric_overflow(&value, "simd_json number"))
+    }
+
+    #[cfg(feature = "big_integer")]
+    fn big_integer(value: &BigInt) -> Result<Self, Error> {
+        Err(numeric_overflow(value, "simd_json number"))
+    }
+
+    fn number(value: f64) -> Self {
+        Self::from(value)
+    }
+
+    fn string(value: &str) -> Self {
+        Self::from(value)
+    }
+
+    fn array<I: IntoIterator<Item = Self>>(items: I) -> Self {
+        Self::from(items.into_iter().collect::<Vec<_>>())
+    }
+
+    fn object<'key, I: IntoIterator<Item = (&'key str, Self)>>(items: I) -> Self {
+        items.into_iter().collect()
+    }
+}
+
+impl<'value> Into<RustType> for BorrowedValue<'value> {
+    fn into(self) -> RustType {
+        self.to_rust_type()
+    }
+}
+
+impl<'value> ToRustType for BorrowedValue<'value> {}
+
+impl<'value> JsonTypeToString for BorrowedValue<'value> {
+    fn to_json_string(&self) -> String {
+        self.encode()
+    }
+}
+
+impl<'json, 'value> JsonMapTrait<'json, BorrowedValue<'value>> for JsonMap<'json, BorrowedValue<'value>> {
+    #[must_use]
+    fn keys(&'json self) -> Box<dyn Iterator<Item = &str> + 'json> {
+        if let BorrowedValue::Object(obj) = &**self {
+            Box::new(obj.keys().map(AsRef::as_ref))
+        } else {
+            Box::new(std::iter::empty())
+        }
+    }
+
+    #[must_use]
+    fn values(&'json self) -> Box<dyn Iterator<Item = &BorrowedValue<'value>> + 'json> {
+        if let BorrowedValue::Object(obj) = &**self {
+            Box::new(obj.values())
+        } else {
+            Box::new(std::iter::empty())
+        }
+    }
+
+    #[must_use]
+    fn items(&'json self) -> Box<dyn Iterator<Item = (&str, &BorrowedValue<'value>)> + 'json> {
+        if let BorrowedValue::Object(obj) = &**self {
+            Box::new(obj.iter().map(|(k, v)| (k.as_ref(), v)))
+        } else {
+            Box::new(std::iter::empty())
+        }
+    }
+}
+
+impl<'value> JsonType for BorrowedValue<'value> {
+    #[must_use]
+    fn as_array<'json>(&'json self) -> Option<Box<dyn ExactSizeIterator<Item = &Self> + 'json>> {
+        if let Self::Array(array) = self {
+            Some(Box::new(array.iter()))
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_boolean(&self) -> Option<bool> {
+        if let Self::Static(node) = self {
+            node.as_bool()
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_integer(&self) -> Option<i128> {
+        if let Self::Static(node) = self {
+            node.as_i128().or_else(|| node.as_u64().map(i128::from))
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_null(&self) -> Option<()> {
+        if let Self::Static(node) = self {
+            node.as_null()
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_number(&self) -> Option<f64> {
+        if let Self::Static(node) = self {
+            node.cast_f64()
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_object(&self) -> Option<JsonMap<Self>> {
+        if let Self::Object(_) = self {
+            Some(JsonMap::new(self))
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_string(&self) -> Option<&str> {
+        if let Self::String(string) = self {
+            Some(string)
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn get_attribute(&self, attribute_name: &str) -> Option<&Self> {
+        if let Self::Object(object) = self {
+            object.get(attribute_name)
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn get_index(&self, index: usize) -> Option<&Self> {
+        if let Self::Array(array) = self {
+            array.get(index)
+        } else {
+            None
+        }
+    }
+}
+
+impl<'value> ThreadSafeJsonType for BorrowedValue<'value> {}
+
+#[cfg(test)]
+fn perform_simd_json_check(json_string: &str, check: impl Fn(&OwnedValue, &BorrowedValue)) {
+    let mut owned_buffer = json_string.as_bytes().to_vec();
+    let mut borrowed_buffer = json_string.as_bytes().to_vec();
+    check(
+        &simd_json::to_owned_value(&mut owned_buffer).unwrap(),
+        &simd_json::to_borrowed_value(&mut borrowed_buffer).unwrap(),
+    );
+}
+
+#[cfg(test)]
+mod tests_json_map_trait {
+    use super::perform_simd_json_check;
+    use crate::json_type::{JsonMap, JsonMapTrait, JsonTypeToString};
+    use std::collections::HashSet;
+
+    const TESTING_MAP: &str = r#"{"k1": "v1", "k2": "v2"}"#;
+
+    #[test]
+    fn keys() {
+        perform_simd_json_check(TESTING_MAP, |owned, borrowed| {
+            // HashSet needed as simd-json does not guarantee ordering of keys
+            let expected_keys = vec!["k1", "k2"].into_iter().collect::<HashSet<_>>();
+            assert_eq!(JsonMap::new(owned).keys().collect::<HashSet<_>>(), expected_keys);
+            assert_eq!(JsonMap::new(borrowed).keys().collect::<HashSet<_>>(), expected_keys);
+        });
+    }
+
+    #[test]
+    fn values() {
+        perform_simd_json_check(TESTING_MAP, |owned, borrowed| {
+            let expected_values = vec![r#""v1""#.to_string(), r#""v2""#.to_string()].into_iter().collect::<HashSet<_>>();
+            assert_eq!(JsonMap::new(owned).values().map(JsonTypeToString::to_json_string).collect::<HashSet<_>>(), expected_values);
+            assert_eq!(
+                JsonMap::new(borrowed).values().map(JsonTypeToString::to_json_string).collect::<HashSet<_>>(),
+                expected_values
+            );
+        });
+    }
+
+    #[test]
+    fn items() {
+        perform_simd_json_check(TESTING_MAP, |owned, borrowed| {
+            let expected_items = vec![("k1", r#""v1""#.to_string()), ("k2", r#""v2""#.to_string())].into_iter().collect::<HashSet<_>>();
+            assert_eq!(JsonMap::new(owned).items().map(|(k, v)| (k, v.to_json_string())).collect::<HashSet<_>>(), expected_items);
+            assert_eq!(JsonMap::new(borrowed).items().map(|(k, v)| (k, v.to_json_string())).collect::<HashSet<_>>(), expected_items);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests_primitive_type_trait {
+    use super::perform_simd_json_check;
+    use crate::json_type::{JsonType, PrimitiveType, ToRustType};
+    use test_case::test_case;
+
+    #[test_case("[]", PrimitiveType::Array)]
+    #[test_case("true", PrimitiveType::Boolean)]
+    #[test_case("1", PrimitiveType::Integer)]
+    #[test_case("null", PrimitiveType::Null)]
+    #[test_case("1.2", PrimitiveType::Number)]
+    #[test_case(r#"{"prop": "value"}"#, PrimitiveType::Object)]
+    #[test_case(r#""string""#, PrimitiveType::String)]
+    fn test_primitive_type(json_string: &str, expected_value: PrimitiveType) {
+        perform_simd_json_check(json_string, |owned, borrowed| {
+            assert_eq!(JsonType::primitive_type(owned), expected_value);
+            assert_eq!(JsonType::primitive_type(borrowed), expected_value);
+        })
+    }
+
+    #[test_case(r#"{"present": 1}"#, "present", Some(1))]
+    #[test_case(r#"{"present": 1}"#, "not-present", None)]
+    fn test_get_attribute(json_string: &str, attribute_name: &str, expected_value: Option<i128>) {
+        perform_simd_json_check(json_string, |owned, borrowed| {
+            assert_eq!(JsonType::get_attribute(owned, attribute_name).and_then(JsonType::as_integer), expected_value);
+            assert_eq!(JsonType::get_attribute(borrowed, attribute_name).and_then(JsonType::as_integer), expected_value);
+        })
+    }
+
+    #[test_case("[0, 1, 2]", 1, Some(1))]
+    #[test_case("[0, 1, 2]", 4, None)]
+    fn test_get_index(json_string: &str, index: usize, expected_value: Option<i128>) {
+        perform_simd_json_check(json_string, |owned, borrowed| {
+            assert_eq!(JsonType::get_index(owned, index).and_then(JsonType::as_integer), expected_value);
+            assert_eq!(JsonType::get_index(borrowed, index).and_then(JsonType::as_integer), expected_value);
+        })
+    }
+
+    #[test_case(r#"{"present": 1}"#, "present", true)]
+    #[test_case(r#"{"present": 1}"#, "not-present", false)]
+    #[test_case("[1, 2, 3]", "not-present", false)]
+    fn test_has_attribute(json_string: &str, attr_name: &str, expected_value: bool) {
+        perform_simd_json_check(json_string, |owned, borrowed| {
+            assert_eq!(JsonType::has_attribute(owned, attr_name), expected_value);
+            assert_eq!(JsonType::has_attribute(borrowed, attr_name), expected_value);
+        })
+    }
+
+    #[test_case("[1]", Some(vec![rust_type!(1)]))]
+    #[test_case(r#"[1, "a"]"#, Some(vec![rust_type!(1), rust_type!("a")]))]
+    #[test_case("null", None)]
+    fn test_as_array(json_string: &str, expected_value: Option<Vec<crate::RustType>>) {
+        perform_simd_json_check(json_string, |owned, borrowed| {
+            assert_eq!(JsonType::as_array(owned).map(|iterator| iterator.map(ToRustType::to_rust_type).collect()), expected_value);
+            assert_eq!(
+                JsonType::as_array(borrowed).map(|iterator| iterator.map(ToRustType::to_rust_type).collect()),
+                expected_value
+            );
+        })
+    }
+
+    #[test_case("true", Some(true))]
+    #[test_case("false", Some(false))]
+    #[test_case("1", None)]
+    fn test_as_boolean(json_string: &str, expected_value: Option<bool>) {
+        perform_simd_json_check(json_string, |owned, borrowed| {
+            assert_eq!(JsonType::as_boolean(owned), expected_value);
+            assert_eq!(JsonType::as_boolean(borrowed), expected_value);
+        })
+    }
+
+    #[test_case("1", Some(1))]
+    #[test_case("-1", Some(-1) ; "negative integer")]
+    #[test_case("18446744073709551615", Some(i128::from(u64::max_value())) ; "integer above i64 max")]
+    #[test_case("1.2", None)]
+    #[test_case(r#""1""#, None)]
+    fn test_as_integer(json_string: &str, expected_value: Option<i128>) {
+        perform_simd_json_check(json_string, |owned, borrowed| {
+            assert_eq!(JsonType::as_integer(owned), expected_value);
+            assert_eq!(JsonType::as_integer(borrowed), expected_value);
+        })
+    }
+
+    #[test_case("null", Some(()))]
+    #[test_case(r#""1""#, None)]
+    fn test_as_null(json_string: &str, expected_value: Option<()>) {
+        perform_simd_json_check(json_string, |owned, borrowed| {
+            assert_eq!(JsonType::as_null(owned), expected_value);
+            assert_eq!(JsonType::as_null(borrowed), expected_value);
+        })
+    }
+
+    #[test_case("1", Some(1_f64))]
+    #[test_case("1.2", Some(1.2))]
+    #[test_case(r#""1""#, None)]
+    fn test_as_number(json_string: &str, expected_value: Option<f64>) {
+        perform_simd_json_check(json_string, |owned, borrowed| {
+            assert_eq!(JsonType::as_number(owned), expected_value);
+            assert_eq!(JsonType::as_number(borrowed), expected_value);
+        })
+    }
+
+    #[test_case("1", false)]
+    #[test_case("1.2", false)]
+    #[test_case(r#"{"1": 1}"#, true)]
+    fn test_as_object(json_string: &str, is_some: bool) {
+        perform_simd_json_check(json_string, |owned, borrowed| {
+            assert_eq!(JsonType::as_object(owned).is_some(), is_some);
+            assert_eq!(JsonType::as_object(borrowed).is_some(), is_some);
+        })
+    }
+
+    #[test_case("1", None)]
+    #[test_case("1.2", None)]
+    #[test_case(r#""1""#, Some("1"))]
+    fn test_as_string(json_string: &str, expected_value: Option<&str>) {
+        perform_simd_json_check(json_string, |owned, borrowed| {
+            assert_eq!(JsonType::as_string(owned), expected_value);
+            assert_eq!(JsonType::as_string(borrowed), expected_value);
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests_to_rust_type {
+    use super::perform_simd_json_check;
+    use crate::json_type::ToRustType;
+
+    #[test]
+    fn smoke_test() {
+        let json_string = r#"{"array": [1, 2.3, "a"], "boolean": false, "null": null, "object": {"key": "value"}}"#;
+        perform_simd_json_check(json_string, |owned, borrowed| {
+            let expected_value = rust_type!({"array": [1, 2.3, "a"], "boolean": false, "null": null, "object": {"key": "value"}});
+            assert_eq!(owned.to_rust_type(), expected_value);
+            assert_eq!(borrowed.to_rust_type(), expected_value);
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests_to_json_string {
+    use super::perform_simd_json_check;
+    use crate::json_type::JsonTypeToString;
+
+    #[test]
+    fn smoke_test() {
+        let json_string = r#"[{"array":[]},{"boolean":false},{"float":2.3},{"integer":1},{"null":null},{"object":{}},{"string":"string"}]"#;
+        perform_simd_json_check(json_string, |owned, borrowed| {
+            assert_eq!(owned.to_json_string(), json_string);
+            assert_eq!(borrowed.to_json_string(), json_string);
+        })
+    }
+}
@@ -0,0 +1,478 @@
+// TOML to JSON mapping
+// * TOML has no `null` value, so `JsonType::as_null` always returns `None`
+// * TOML datetimes (offset date-time, local date-time, local date and local time) are reported as their RFC 3339
+//   representation (`PrimitiveType::String`).
+//   As `toml::value::Datetime` does not store its textual representation, there is no string to borrow: the
+//   representation is exposed by `JsonType::as_string_cow` only (`JsonType::as_string` returns `None`). The raw
+//   datetime is accessible via `toml::Value::as_datetime`.
+use crate::{
+    json_type::{JsonMap, JsonMapTrait, JsonType, PrimitiveType, ThreadSafeJsonType, ToRustType},
+    rust_type_impl::RustType,
+};
+use std::{borrow::Cow, collections::HashMap};
+use toml::Value;
+
+impl Into<RustType> for Value {
+    fn into(self) -> RustType {
+        self.to_rust_type()
+    }
+}
+
+impl ToRustType for Value {
+    fn to_rust_type(&self) -> RustType {
+        match self {
+            Self::Datetime(datetime) => RustType::from(datetime.to_string()),
+            Self::Array(array) => RustType::from(array.iter().map(ToRustType::to_rust_type).collect::<Vec<_>>()),
+            Self::Table(table) => RustType::from(table.iter().map(|(key, value)| (key.clone(), value.to_rust_type())).collect::<HashMap<_, _>>()),
+            Self::Boolean(value) => RustType::from(*value),
+            Self::Float(value) => RustType::from(*value),
+            Self::Integer(value) => RustType::from(*value),
+            Self::String(value) => RustType::from(value.as_str()),
+        }
+    }
+}
+
+impl<'json> JsonMapTrait<'json, Value> for JsonMap<'json, Value> {
+    #[must_use]
+    fn keys(&'json self) -> Box<dyn Iterator<Item = &str> + 'json> {
+        #[allow(clippy::option_if_let_else)]
+        if let Some(obj) = self.as_table() {
+            Box::new(obj.keys().map(AsRef::as_ref))
+        } else {
+            Box::new(std::iter::empty())
+        }
+    }
+
+    #[must_use]
+    fn values(&'json self) -> Box<dyn Iterator<Item = &Value> + 'json> {
+        #[allow(clippy::option_if_let_else)]
+        if let Some(obj) = self.as_table() {
+            Box::new(obj.values())
+        } else {
+            Box::new(std::iter::empty())
+        }
+    }
+
+    #[must_use]
+    fn items(&'json self) -> Box<dyn Iterator<Item = (&str, &Value)> + 'json> {
+        #[allow(clippy::option_if_let_else)]
+        if let Some(obj) = self.as_table() {
+            Box::new(obj.iter().map(|(key, value)| (key.as_str(), value)))
+        } else {
+            Box::new(std::iter::empty())
+        }
+    }
+}
+
+impl JsonType for Value {
+    #[must_use]
+    fn as_array<'json>(&'json self) -> Option<Box<dyn ExactSizeIterator<Item = &Self> + 'json>> {
+        self.as_array().map(|vec| {
+            let b: Box<dyn ExactSizeIterator<Item = _>> = Box::new(vec.iter());
+            b
+        })
+    }
+
+    #[must_use]
+    fn as_boolean(&self) -> Option<bool> {
+        self.as_bool()
+    }
+
+    #[must_use]
+    fn as_integer(&self) -> Option<i128> {
+        self.as_integer().map(i128::from)
+    }
+
+    #[must_use]
+    fn as_null(&self) -> Option<()> {
+        None
+    }
+
+    #[must_use]
+    fn as_number(&self) -> Option<f64> {
+        match self {
+            #[allow(clippy::cast_precision_loss)]
+            Self::Integer(value) => Some(*value as f64),
+            Self::Float(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    fn as_object(&self) -> Option<JsonMap<Self>> {
+        if self.is_table() {
+            Some(JsonMap::new(self))
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_string(&self) -> Option<&str> {
+        self.as_str()
+    }
+
+    #[must_use]
+    fn as_string_cow(&self) -> Option<Cow<str>> {
+        match self {
+            Self::String(value) => Some(Cow::Borrowed(value)),
+            Self::Datetime(datetime) => Some(Cow::Owned(datetime.to_string())),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    fn get_attribute(&self, attribute_name: &str) -> Option<&Self> {
+        self.get(attribute_name)
+    }
+
+    #[must_use]
+    fn get_index(&self, index: usize) -> Option<&Self> {
+        self.get(index)
+    }
+
+    #[must_use]
+    fn is_string(&self) -> bool {
+        self.is_str() || self.is_datetime()
+    }
+
+    #[must_use]
+    fn has_attribute(&self, attribute_name: &str) -> bool {
+        self.get(attribute_name).is_some()
+    }
+
+    #[must_use]
+    fn primitive_type(&self) -> PrimitiveType {
+        match self {
+            Self::Array(_) => PrimitiveType::Array,
+            Self::Boolean(_) => PrimitiveType::Boolean,
+            Self::Integer(_) => PrimitiveType::Integer,
+            Self::Float(_) => PrimitiveType::Number,
+            Self::Table(_) => PrimitiveType::Object,
+            Self::String(_) | Self::Datetime(_) => PrimitiveType::String,
+        }
+    }
+}
+
+impl ThreadSafeJsonType for Value {}
+
+#[cfg(test)]
+fn toml_datetime(datetime: &str) -> Value {
+    Value::Datetime(datetime.parse().unwrap())
+}
+
+#[cfg(test)]
+macro_rules! toml_value {
+    ($($json:tt)+) => {{
+        use serde_json;
+        #[allow(unused_qualifications)]
+        let thing: toml::Value = serde_json::from_value(json![$($json)+]).unwrap();
+        thing
+    }};
+}
+
+#[cfg(test)]
+mod tests_toml_map_trait {
+    use crate::json_type::{JsonMap, JsonMapTrait};
+    use toml::Value;
+
+    lazy_static! {
+        static ref TESTING_MAP: Value = toml_value![{"k1": "v1", "k2": "v2"}];
+    }
+
+    #[test]
+    fn keys() {
+        let testing_map: &Value = &TESTING_MAP;
+        assert_eq!(JsonMap::new(testing_map).keys().collect::<Vec<_>>(), vec!["k1", "k2"]);
+    }
+
+    #[test]
+    fn values() {
+        let testing_map: &Value = &TESTING_MAP;
+        assert_eq!(JsonMap::new(testing_map).values().collect::<Vec<_>>(), vec![&toml_value!["v1"], &toml_value!["v2"]]);
+    }
+
+    #[test]
+    fn items() {
+        let testing_map: &Value = &TESTING_MAP;
+        assert_eq!(
+            JsonMap::new(testing_map).items().collect::<Vec<_>>(),
+            vec![("k1", &toml_value!["v1"]), ("k2", &toml_value!["v2"])]
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests_primitive_type_trait {
+    use super::toml_datetime;
+    use crate::json_type::{JsonType, PrimitiveType};
+    use std::ops::Deref;
+    use test_case::test_case;
+    use toml::Value;
+
+    #[test_case(&toml_value![[]], PrimitiveType::Array)]
+    #[test_case(&toml_value![true], PrimitiveType::Boolean)]
+    #[test_case(&toml_value![1], PrimitiveType::Integer)]
+    #[test_case(&toml_datetime("1979-05-27T07:32:00Z"), PrimitiveType::String)]
+    #[test_case(&toml_value![1.2], PrimitiveType::Number)]
+    #[test_case(&toml_value![{"prop": "value"}], PrimitiveType::Object)]
+    #[test_case(&toml_value!["string"], PrimitiveType::String)]
+    fn test_primitive_type(value: &Value, expected_value: PrimitiveType) {
+        assert_eq!(JsonType::primitive_type(value), expected_value);
+    }
+
+    #[test_case(&toml_value![{"present": 1}], "present", Some(&toml_value![1]))]
+    #[test_case(&toml_value![{"present": 1}], "not-present", None)]
+    fn test_get_attribute(value: &Value, attribute_name: &str, expected_value: Option<&Value>) {
+        assert_eq!(JsonType::get_attribute(value, attribute_name), expected_value);
+    }
+
+    #[test_case(&toml_value![[0, 1, 2]], 1, &Some(toml_value![1]))]
+    #[test_case(&toml_value![[0, 1, 2]], 4, &None)]
+    fn test_get_index(value: &Value, index: usize, expected_value: &Option<Value>) {
+        assert_eq!(JsonType::get_index(value, index), expected_value.as_ref());
+    }
+
+    #[test_case(&toml_value![{"present": 1}], "present", true)]
+    #[test_case(&toml_value![{"present": 1}], "not-present", false)]
+    #[test_case(&toml_value![[1, 2, 3]], "not-present", false)]
+    fn test_has_attribute(value: &Value, attr_name: &str, expected_value: bool) {
+        assert_eq!(JsonType::has_attribute(value, attr_name), expected_value);
+    }
+
+    #[test_case(&toml_value![[0, 1, 2]], true)]
+    #[test_case(&toml_value![true], false)]
+    #[test_case(&toml_value![1_u32], false)]
+    #[test_case(&toml_datetime("1979-05-27T07:32:00Z"), false)]
+    #[test_case(&toml_value![1.2_f32], false)]
+    #[test_case(&toml_value![{"key": "value"}], false)]
+    #[test_case(&toml_value!["string"], false)]
+    fn test_is_array(value: &Value, expected_value: bool) {
+        assert_eq!(JsonType::is_array(value), expected_value);
+    }
+
+    #[test_case(&toml_value![[0, 1, 2]], false)]
+    #[test_case(&toml_value![true], true)]
+    #[test_case(&toml_value![1_u32], false)]
+    #[test_case(&toml_datetime("1979-05-27T07:32:00Z"), false)]
+    #[test_case(&toml_value![1.2_f32], false)]
+    #[test_case(&toml_value![{"key": "value"}], false)]
+    #[test_case(&toml_value!["string"], false)]
+    fn test_is_boolean(value: &Value, expected_value: bool) {
+        assert_eq!(JsonType::is_boolean(value), expected_value);
+    }
+
+    #[test_case(&toml_value![[0, 1, 2]], false)]
+    #[test_case(&toml_value![true], false)]
+    #[test_case(&toml_value![1_u32], true)]
+    #[test_case(&toml_datetime("1979-05-27T07:32:00Z"), false)]
+    #[test_case(&toml_value![1.2_f32], false)]
+    #[test_case(&toml_value![{"key": "value"}], false)]
+    #[test_case(&toml_value!["string"], false)]
+    fn test_is_integer(value: &Value, expected_value: bool) {
+        assert_eq!(JsonType::is_integer(value), expected_value);
+    }
+
+    #[test_case(&toml_value![[0, 1, 2]], false)]
+    #[test_case(&toml_value![true], false)]
+    #[test_case(&toml_value![1_u32], false)]
+    #[test_case(&toml_datetime("1979-05-27T07:32:00Z"), false)]
+    #[test_case(&toml_value![1.2_f32], false)]
+    #[test_case(&toml_value![{"key": "value"}], false)]
+    #[test_case(&toml_value!["string"], false)]
+    fn test_is_null(value: &Value, expected_value: bool) {
+        assert_eq!(JsonType::is_null(value), expected_value);
+    }
+
+    #[test_case(&toml_value![[0, 1, 2]], false)]
+    #[test_case(&toml_value![true], false)]
+    #[test_case(&toml_value![1_u32], true)]
+    #[test_case(&toml_datetime("1979-05-27T07:32:00Z"), false)]
+    #[test_case(&toml_value![1.2_f32], true)]
+    #[test_case(&toml_value![{"key": "value"}], false)]
+    #[test_case(&toml_value!["string"], false)]
+    fn test_is_number(value: &Value, expected_value: bool) {
+        assert_eq!(JsonType::is_number(value), expected_value);
+    }
+
+    #[test_case(&toml_value![[0, 1, 2]], false)]
+    #[test_case(&toml_value![true], false)]
+    #[test_case(&toml_value![1_u32], false)]
+    #[test_case(&toml_datetime("1979-05-27T07:32:00Z"), false)]
+    #[test_case(&toml_value![1.2_f32], false)]
+    #[test_case(&toml_value![{"key": "value"}], true)]
+    #[test_case(&toml_value!["string"], false)]
+    fn test_is_object(value: &Value, expected_value: bool) {
+        assert_eq!(JsonType::is_object(value), expected_value);
+    }
+
+    #[test_case(&toml_value![[0, 1, 2]], false)]
+    #[test_case(&toml_value![true], false)]
+    #[test_case(&toml_value![1_u32], false)]
+    #[test_case(&toml_datetime("1979-05-27T07:32:00Z"), true)]
+    #[test_case(&toml_value![1.2_f32], false)]
+    #[test_case(&toml_value![{"key": "value"}], false)]
+    #[test_case(&toml_value!["string"], true)]
+    fn test_is_string(value: &Value, expected_value: bool) {
+        assert_eq!(JsonType::is_string(value), expected_value);
+    }
+
+    #[test_case(&toml_value![[1]], &Some(vec![toml_value![1]]))]
+    #[test_case(&toml_value![[1, "a"]], &Some(vec![toml_value![1], toml_value!["a"]]))]
+    #[test_case(&toml_datetime("1979-05-27T07:32:00Z"), &None)]
+    fn test_as_array(value: &Value, expected_value: &Option<Vec<Value>>) {
+        assert_eq!(&JsonType::as_array(value).map(|iterator| iterator.cloned().collect()), expected_value);
+    }
+
+    #[test_case(&toml_value![true], Some(true))]
+    #[test_case(&toml_value![false], Some(false))]
+    #[test_case(&toml_value![1], None)]
+    fn test_as_boolean(value: &Value, expected_value: Option<bool>) {
+        assert_eq!(JsonType::as_boolean(value), expected_value);
+    }
+
+    #[test_case(&toml_value![1], Some(1))]
+    #[test_case(&toml_value![-1], Some(-1) ; "negative integer")]
+    #[test_case(&toml_value![i64::min_value()], Some(i128::from(i64::min_value())) ; "TOML integers are i64")]
+    #[test_case(&toml_value![1.2], None)]
+    #[test_case(&toml_value!["1"], None)]
+    fn test_as_integer(value: &Value, expected_value: Option<i128>) {
+        assert_eq!(JsonType::as_integer(value), expected_value);
+    }
+
+    #[test_case(&toml_datetime("1979-05-27T07:32:00Z"), None)]
+    #[test_case(&toml_value!["1"], None)]
+    fn test_as_null(value: &Value, expected_value: Option<()>) {
+        assert_eq!(JsonType::as_null(value), expected_value);
+    }
+
+    #[test_case(&toml_value![1], Some(1_f64))]
+    #[test_case(&toml_value![1.2], Some(1.2))]
+    #[test_case(&toml_value!["1"], None)]
+    fn test_as_number(value: &Value, expected_value: Option<f64>) {
+        assert_eq!(JsonType::as_number(value), expected_value);
+    }
+
+    #[test_case(&toml_value![1], &None)]
+    #[test_case(&toml_value![1.2], &None)]
+    #[test_case(&toml_value![{"1": 1}], &Some(toml_value![{"1": 1}]))]
+    fn test_as_object(value: &Value, expected_value: &Option<Value>) {
+        assert_eq!(
+            match JsonType::as_object(value) {
+                Some(ref v) => Some({
+                    #[allow(clippy::explicit_deref_methods)] // Explicit deref call is needed to ensure that &Value is retrieved from JsonMap
+                    v.deref()
+                }),
+                None => None,
+            },
+            expected_value.as_ref(),
+        );
+    }
+
+    #[test_case(&toml_value![1], None)]
+    #[test_case(&toml_value![1.2], None)]
+    #[test_case(&toml_value!["1"], Some("1"))]
+    #[test_case(&toml_datetime("1979-05-27T07:32:00Z"), None)]
+    fn test_as_string(value: &Value, expected_value: Option<&str>) {
+        assert_eq!(JsonType::as_string(value), expected_value);
+    }
+
+    #[test_case(&toml_value![1], None)]
+    #[test_case(&toml_value!["1"], Some("1"))]
+    #[test_case(&toml_datetime("1979-05-27T07:32:00Z"), Some("1979-05-27T07:32:00Z"))]
+    fn test_as_string_cow(value: &Value, expected_value: Option<&str>) {
+        assert_eq!(value.as_string_cow().as_deref(), expected_value);
+    }
+}
+
+#[cfg(test)]
+mod tests_json_map {
+    use crate::json_type::{JsonMapTrait, JsonType};
+    use toml::Value;
+
+    lazy_static! {
+        static ref TESTING_MAP: Value = toml_value![{"key1": {"key2": 1}}];
+    }
+
+    #[test]
+    fn test_keys() {
+        let key1: &Value = TESTING_MAP.get_attribute("key1").unwrap();
+        assert_eq!(JsonType::as_object(key1).unwrap().keys().collect::<Vec<_>>(), vec![String::from("key2")]);
+    }
+
+    #[test]
+    fn test_values() {
+        let key1 = TESTING_MAP.get_attribute("key1").unwrap();
+        assert_eq!(
+            JsonType::as_object(key1).unwrap().values().map(|v| format!("{:?}", v)).collect::<Vec<_>>(),
+            vec![format!("{:?}", Value::from(1))],
+        );
+    }
+
+    #[test]
+    fn test_items() {
+        let key1 = TESTING_MAP.get_attribute("key1").unwrap();
+        assert_eq!(
+            JsonType::as_object(key1).unwrap().items().map(|(k, v)| format!("{} -> {:?}", k, v)).collect::<Vec<_>>(),
+            vec![format!("key2 -> {:?}", Value::from(1))],
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests_to_json_string {
+    use crate::json_type::JsonTypeToString;
+
+    #[test]
+    fn smoke_test() {
+        let value = toml_value![[
+            {"array": []},
+            {"boolean": false},
+            {"float": 2.3},
+            {"integer": 1},
+            {"object": {}},
+            {"string": "string"},
+        ]];
+        assert_eq!(
+            value.to_json_string(),
+            r#"[{"array":[]},{"boolean":false},{"float":2.3},{"integer":1},{"object":{}},{"string":"string"}]"#
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests_datetime {
+    use super::toml_datetime;
+    use crate::{
+        convert::from_json_type,
+        get_fragment,
+        json_type::{JsonType, JsonTypeToString, PrimitiveType, ToRustType},
+        rust_type_impl::RustType,
+    };
+    use test_case::test_case;
+    use toml::Value;
+
+    #[test_case("1979-05-27T07:32:00Z" ; "offset date-time")]
+    #[test_case("1979-05-27T00:32:00.999999-07:00" ; "offset date-time with fractional seconds")]
+    #[test_case("1979-05-27T07:32:00" ; "local date-time")]
+    #[test_case("1979-05-27" ; "local date")]
+    #[test_case("07:32:00" ; "local time")]
+    fn test_datetime_is_exposed_as_string(datetime: &str) {
+        let value = toml_datetime(datetime);
+        assert_eq!(JsonType::primitive_type(&value), PrimitiveType::String);
+        assert_eq!(value.as_string_cow().as_deref(), Some(datetime));
+        assert_eq!(value.as_datetime().map(ToString::to_string), Some(datetime.to_string()));
+        assert_eq!(value.to_rust_type(), RustType::from(datetime));
+        assert_eq!(from_json_type::<RustType, _>(&value), Ok(RustType::from(datetime)));
+        assert_eq!(value.to_json_string(), format!(r#""{}""#, datetime));
+    }
+
+    #[test]
+    fn test_datetime_in_document() {
+        let value = Value::Table("[owner]\ndob = 1979-05-27T07:32:00-08:00".parse().unwrap());
+        let dob = get_fragment(&value, "/owner/dob").unwrap();
+        assert_eq!(dob.as_datetime().map(ToString::to_string), Some("1979-05-27T07:32:00-08:00".to_string()));
+        assert_eq!(dob.as_string_cow().as_deref(), Some("1979-05-27T07:32:00-08:00"));
+        assert_eq!(value.to_rust_type(), rust_type!({"owner": {"dob": "1979-05-27T07:32:00-08:00"}}));
+    }
+}
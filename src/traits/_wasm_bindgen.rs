@@ -0,0 +1,487 @@
+// JavaScript (via `wasm_bindgen::JsValue`) to JSON mapping
+// The mapping follows `JSON.stringify`:
+// * objects exposing a `toJSON` method are represented by the value returned by the method (ie. `Date` instances
+//   are ISO 8601 strings)
+// * arrays are exposed via their items, any other object via its own enumerable string keyed properties
+//   (`Object.entries`, so `Map` and `Set` instances are empty objects)
+// * `undefined`, functions and symbols are omitted from objects while they are reported as `null` where omitting
+//   them would alter the document structure (array items or root value). Similarly `NaN` and infinities are `null`
+// Integral numbers are reported as integers (ie. `1.0` is `1`), while `BigInt` values are integers independently
+// by their magnitude (`big_integer` feature is needed for values not fitting into i128, otherwise they are
+// reported as numbers preserving their exact representation, see `JsonType::as_number_str`).
+//
+// `JsonType` cannot be implemented on `JsValue` directly, as properties, items and strings are read from the
+// JavaScript heap into newly created values while `JsonType` returns references. `JsSnapshot` wraps a `JsValue` and
+// reads each value the first time it is accessed, caching it afterwards. Modifications of the JavaScript object
+// performed after the read are not reflected.
+// Objects referencing one of their ancestors (cyclic objects), which `JSON.stringify` rejects with `TypeError`, are
+// reported as `null` by `JsonType` while `TryJsonType` reports them as errors.
+//
+// `JsValue` is neither `Send` nor `Sync`, so `ThreadSafeJsonType` is not implemented.
+use crate::{
+    convert::from_json_type,
+    error::Error,
+    json_builder::JsonBuilder,
+    json_type::{JsonMap, JsonMapTrait, JsonType, ToRustType, TryJsonType},
+};
+use js_sys::{Array, Function, Object, Reflect};
+#[cfg(feature = "big_integer")]
+use num_bigint::BigInt;
+use std::{borrow::Cow, cell::OnceCell, collections::HashMap, rc::Rc};
+use wasm_bindgen::{JsCast, JsValue};
+
+// Convert any `JsonType` into its JavaScript representation
+// Integers not exactly representable by JavaScript numbers are represented as `BigInt`
+/// # Errors
+///
+/// Will return `Err` if `json_object` exposes inconsistent values (see `convert::from_json_type`)
+pub fn to_js_value<T: JsonType>(json_object: &T) -> Result<JsValue, Error> {
+    from_json_type(json_object)
+}
+
+#[derive(Debug)]
+pub struct JsSnapshot {
+    value: JsValue,
+    // Objects containing `value`, used to detect cycles
+    ancestors: Rc<Vec<JsValue>>,
+    node: OnceCell<JsSnapshotNode>,
+}
+
+#[derive(Debug)]
+enum JsSnapshotNode {
+    Null,
+    Boolean(bool),
+    Integer(i128),
+    #[cfg(feature = "big_integer")]
+    BigInteger(BigInt),
+    Number(f64, Option<String>),
+    String(String),
+    Array(Vec<JsSnapshot>),
+    Object {
+        entries: Vec<(String, JsSnapshot)>,
+        positions: HashMap<String, usize>,
+    },
+    Cycle,
+}
+
+// Value serialised by `JSON.stringify` in place of `value`
+fn to_json(value: JsValue) -> JsValue {
+    if value.is_object() {
+        if let Ok(to_json) = Reflect::get(&value, &JsValue::from_str("toJSON")) {
+            if let Some(to_json) = to_json.dyn_ref::<Function>() {
+                return to_json.call0(&value).unwrap_or(JsValue::UNDEFINED);
+            }
+        }
+    }
+    value
+}
+
+// Values without JSON representation, omitted from objects
+fn is_omitted(value: &JsValue) -> bool {
+    value.is_undefined() || value.is_function() || value.is_symbol()
+}
+
+fn number_node(number: f64) -> JsSnapshotNode {
+    #[allow(clippy::cast_precision_loss)]
+    let i128_bound = i128::max_value() as f64;
+    if !number.is_finite() {
+        JsSnapshotNode::Null
+    } else if number.fract() == 0.0 && -i128_bound <= number && number < i128_bound {
+        #[allow(clippy::cast_possible_truncation)]
+        JsSnapshotNode::Integer(number as i128)
+    } else {
+        JsSnapshotNode::Number(number, None)
+    }
+}
+
+fn big_integer_node(value: &JsValue) -> JsSnapshotNode {
+    let digits = value.unchecked_ref::<js_sys::BigInt>().to_string(10).map_or_else(|_| String::new(), String::from);
+    if let Ok(integer) = digits.parse() {
+        return JsSnapshotNode::Integer(integer);
+    }
+
+    #[cfg(feature = "big_integer")]
+    {
+        if let Ok(big_integer) = digits.parse() {
+            return JsSnapshotNode::BigInteger(big_integer);
+        }
+    }
+
+    digits.parse().map_or(JsSnapshotNode::Null, |number| JsSnapshotNode::Number(number, Some(digits)))
+}
+
+impl JsSnapshot {
+    #[must_use]
+    pub fn new(value: JsValue) -> Self {
+        Self::with_ancestors(value, Rc::default())
+    }
+
+    fn with_ancestors(value: JsValue, ancestors: Rc<Vec<JsValue>>) -> Self {
+        Self {
+            value: to_json(value),
+            ancestors,
+            node: OnceCell::new(),
+        }
+    }
+
+    // JavaScript value represented by the snapshot (the value returned by `toJSON`, if present)
+    #[must_use]
+    pub const fn value(&self) -> &JsValue {
+        &self.value
+    }
+
+    fn read(&self) -> JsSnapshotNode {
+        let value = &self.value;
+        if value.is_object() && self.ancestors.iter().any(|ancestor| Object::is(ancestor, value)) {
+            return JsSnapshotNode::Cycle;
+        }
+        let ancestors = || {
+            let mut ancestors = Vec::clone(&self.ancestors);
+            ancestors.push(value.clone());
+            Rc::new(ancestors)
+        };
+
+        if let Some(bool) = value.as_bool() {
+            JsSnapshotNode::Boolean(bool)
+        } else if let Some(number) = value.as_f64() {
+            number_node(number)
+        } else if value.is_bigint() {
+            big_integer_node(value)
+        } else if let Some(string) = value.as_string() {
+            JsSnapshotNode::String(string)
+        } else if Array::is_array(value) {
+            let ancestors = ancestors();
+            JsSnapshotNode::Array(
+                value
+                    .unchecked_ref::<Array>()
+                    .iter()
+                    .map(|item| Self::with_ancestors(item, Rc::clone(&ancestors)))
+                    .collect(),
+            )
+        } else if value.is_object() {
+            let ancestors = ancestors();
+            let mut entries = Vec::new();
+            let mut positions = HashMap::new();
+            for entry in Object::entries(value.unchecked_ref()).iter() {
+                let entry = entry.unchecked_into::<Array>();
+                let item = Self::with_ancestors(entry.get(1), Rc::clone(&ancestors));
+                if let (Some(key), false) = (entry.get(0).as_string(), is_omitted(&item.value)) {
+                    let _ = positions.insert(key.clone(), entries.len());
+                    entries.push((key, item));
+                }
+            }
+            JsSnapshotNode::Object { entries, positions }
+        } else {
+            JsSnapshotNode::Null
+        }
+    }
+
+    fn node(&self) -> &JsSnapshotNode {
+        self.node.get_or_init(|| self.read())
+    }
+
+    // Error reported by the `TryJsonType` accessors for cyclic objects (matching the `JSON.stringify` error)
+    fn cycle(&self) -> Result<(), Error> {
+        if let JsSnapshotNode::Cycle = self.node() {
+            Err(Error::backend("wasm_bindgen", "", "Converting circular structure to JSON"))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl From<JsValue> for JsSnapshot {
+    #[must_use]
+    fn from(value: JsValue) -> Self {
+        Self::new(value)
+    }
+}
+
+impl ToRustType for JsSnapshot {}
+
+impl TryJsonType for JsSnapshot {
+    fn try_as_array<'json>(&'json self) -> Result<Option<Box<dyn ExactSizeIterator<Item = &Self> + 'json>>, Error> {
+        self.cycle().map(|()| self.as_array())
+    }
+
+    fn try_get_attribute(&self, attribute_name: &str) -> Result<Option<&Self>, Error> {
+        self.cycle().map(|()| self.get_attribute(attribute_name))
+    }
+
+    fn try_get_index(&self, index: usize) -> Result<Option<&Self>, Error> {
+        self.cycle().map(|()| self.get_index(index))
+    }
+}
+
+impl<'json> JsonMapTrait<'json, JsSnapshot> for JsonMap<'json, JsSnapshot> {
+    #[must_use]
+    fn items(&'json self) -> Box<dyn Iterator<Item = (&str, &JsSnapshot)> + 'json> {
+        match self.node() {
+            JsSnapshotNode::Object { entries, .. } => Box::new(entries.iter().map(|(key, value)| (key.as_str(), value))),
+            _ => Box::new(std::iter::empty()),
+        }
+    }
+}
+
+impl JsonType for JsSnapshot {
+    #[must_use]
+    fn as_array<'json>(&'json self) -> Option<Box<dyn ExactSizeIterator<Item = &Self> + 'json>> {
+        if let JsSnapshotNode::Array(items) = self.node() {
+            Some(Box::new(items.iter()))
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_boolean(&self) -> Option<bool> {
+        if let JsSnapshotNode::Boolean(value) = self.node() {
+            Some(*value)
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_integer(&self) -> Option<i128> {
+        if let JsSnapshotNode::Integer(value) = self.node() {
+            Some(*value)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "big_integer")]
+    #[must_use]
+    fn as_big_integer(&self) -> Option<BigInt> {
+        match self.node() {
+            JsSnapshotNode::Integer(value) => Some(BigInt::from(*value)),
+            JsSnapshotNode::BigInteger(value) => Some(value.clone()),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    fn as_null(&self) -> Option<()> {
+        if let JsSnapshotNode::Null | JsSnapshotNode::Cycle = self.node() {
+            Some(())
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_number(&self) -> Option<f64> {
+        match self.node() {
+            #[allow(clippy::cast_precision_loss)]
+            JsSnapshotNode::Integer(value) => Some(*value as f64),
+            JsSnapshotNode::Number(value, _) => Some(*value),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    fn as_number_str(&self) -> Option<Cow<str>> {
+        if let JsSnapshotNode::Number(_, Some(number_str)) = self.node() {
+            Some(Cow::Borrowed(number_str))
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_object(&self) -> Option<JsonMap<Self>> {
+        if let JsSnapshotNode::Object { .. } = self.node() {
+            Some(JsonMap::new(self))
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn as_string(&self) -> Option<&str> {
+        if let JsSnapshotNode::String(value) = self.node() {
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn get_attribute(&self, attribute_name: &str) -> Option<&Self> {
+        if let JsSnapshotNode::Object { entries, positions } = self.node() {
+            positions.get(attribute_name).map(|position| &entries[*position].1)
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn get_index(&self, index: usize) -> Option<&Self> {
+        if let JsSnapshotNode::Array(items) = self.node() {
+            items.get(index)
+        } else {
+            None
+        }
+    }
+}
+
+// All the integers are representable, the ones not exactly representable by JavaScript numbers as `BigInt`
+impl JsonBuilder for JsValue {
+    fn null() -> Self {
+        Self::NULL
+    }
+
+    fn bool(value: bool) -> Self {
+        Self::from_bool(value)
+    }
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+    fn integer(value: i128) -> Result<Self, Error> {
+        let number = value as f64;
+        // `i128::max_value() as f64` is 2^127, which would saturate back to `i128::max_value()`
+        if number < i128::max_value() as f64 && number as i128 == value {
+            Ok(Self::from_f64(number))
+        } else {
+            Ok(Self::bigint_from_str(&value.to_string()))
+        }
+    }
+
+    #[cfg(feature = "big_integer")]
+    fn big_integer(value: &BigInt) -> Result<Self, Error> {
+        Ok(Self::bigint_from_str(&value.to_string()))
+    }
+
+    fn number(value: f64) -> Self {
+        Self::from_f64(value)
+    }
+
+    fn string(value: &str) -> Self {
+        Self::from_str(value)
+    }
+
+    fn array<I: IntoIterator<Item = Self>>(items: I) -> Self {
+        items.into_iter().collect::<Array>().into()
+    }
+
+    fn object<'key, I: IntoIterator<Item = (&'key str, Self)>>(items: I) -> Self {
+        let object = Object::new();
+        for (key, value) in items {
+            let _ = Reflect::set(&object, &Self::from_str(key), &value);
+        }
+        object.into()
+    }
+}
+
+// Tests are executed via `wasm-bindgen-test` (ie. `make test-wasm`), JavaScript values are created via `eval`
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use super::{to_js_value, JsSnapshot};
+    use crate::{
+        error::Error,
+        json_type::{try_get_fragment, JsonMapTrait, JsonType, ToRustType, TryJsonType},
+        rust_type_impl::RustType,
+    };
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    fn js_snapshot(javascript_code: &str) -> JsSnapshot {
+        JsSnapshot::new(js_sys::eval(&format!("({})", javascript_code)).unwrap())
+    }
+
+    fn assert_rust_type(javascript_code: &str, expected_value: RustType) {
+        assert_eq!(js_snapshot(javascript_code).to_rust_type(), expected_value, "{}", javascript_code);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_primitives() {
+        assert_rust_type("null", rust_type!(null));
+        assert_rust_type("undefined", rust_type!(null));
+        assert_rust_type("true", rust_type!(true));
+        assert_rust_type("'string'", rust_type!("string"));
+        assert_rust_type("2.5", rust_type!(2.5));
+        assert_rust_type("NaN", rust_type!(null));
+        assert_rust_type("() => 1", rust_type!(null));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_integral_numbers_are_integers() {
+        assert_eq!(js_snapshot("1.0").as_integer(), Some(1));
+        assert_eq!(js_snapshot("-3e20").as_integer(), Some(-300_000_000_000_000_000_000));
+        assert_eq!(js_snapshot("1.5").as_integer(), None);
+        assert_eq!(js_snapshot("1e300").as_integer(), None);
+        assert_eq!(js_snapshot("1e300").as_number(), Some(1e300));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_big_integers() {
+        assert_eq!(js_snapshot("-(2n ** 100n)").as_integer(), Some(-(1 << 100)));
+        assert_eq!(js_snapshot("2n ** 130n").as_integer(), None);
+        #[cfg(not(feature = "big_integer"))]
+        assert_eq!(js_snapshot("2n ** 130n").as_number_str().as_deref(), Some("1361129467683753853853498429727072845824"));
+        #[cfg(feature = "big_integer")]
+        assert_eq!(js_snapshot("2n ** 130n").as_big_integer(), "1361129467683753853853498429727072845824".parse().ok());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_arrays_and_objects() {
+        let snapshot = js_snapshot("{key: [1, {nested: 'value'}, undefined], other: true}");
+        assert_eq!(
+            snapshot.get_attribute("key").and_then(|key| key.get_index(1)).map(ToRustType::to_rust_type),
+            Some(rust_type!({"nested": "value"}))
+        );
+        assert_eq!(
+            snapshot.as_object().map(|object| object.keys().map(str::to_string).collect::<Vec<_>>()),
+            Some(vec!["key".to_string(), "other".to_string()])
+        );
+        assert_eq!(snapshot.get_attribute("missing").is_none(), true);
+        assert_rust_type(
+            "{key: [1, {nested: 'value'}, undefined], other: true}",
+            rust_type!({"key": [1, {"nested": "value"}, null], "other": true}),
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_json_stringify_semantic() {
+        assert_rust_type(
+            "{undefined: undefined, function: () => 1, symbol: Symbol(), [Symbol()]: 1, kept: null}",
+            rust_type!({"kept": null}),
+        );
+        assert_rust_type("{date: new Date(0)}", rust_type!({"date": "1970-01-01T00:00:00.000Z"}));
+        assert_rust_type("{toJSON: () => [1]}", rust_type!([1]));
+        assert_rust_type("new Map([['key', 1]])", rust_type!({}));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_cyclic_objects() {
+        let snapshot = js_snapshot("(() => { const object = {key: 1, nested: [{}]}; object.nested[0].self = object; return object; })()");
+        assert_eq!(snapshot.to_rust_type(), rust_type!({"key": 1, "nested": [{"self": null}]}));
+        assert_eq!(
+            snapshot.try_to_rust_type(),
+            Err(Error::backend("wasm_bindgen", "/nested/0/self", "Converting circular structure to JSON"))
+        );
+        assert_eq!(
+            try_get_fragment(&snapshot, "/nested/0/self/key").map(|_| ()),
+            Err(Error::backend("wasm_bindgen", "/nested/0/self", "Converting circular structure to JSON"))
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_shared_objects_are_not_cycles() {
+        assert_rust_type(
+            "(() => { const shared = {key: 1}; return [shared, {nested: shared}]; })()",
+            rust_type!([{"key": 1}, {"nested": {"key": 1}}]),
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_to_js_value() {
+        let value = rust_type!({"array": [1, -2, 2.5, null, true, "string"], "object": {"key": {}}, "big": (i128::max_value())});
+        assert_eq!(JsSnapshot::new(to_js_value(&value).unwrap()).to_rust_type(), value);
+        assert_eq!(to_js_value(&rust_type!(i128::max_value())).unwrap().is_bigint(), true);
+        assert_eq!(to_js_value(&rust_type!(1)).unwrap().is_bigint(), false);
+        // Integers exactly representable by JavaScript numbers are numbers, even outside of the safe integer range
+        assert_eq!(to_js_value(&rust_type!(100_000_000_000_000_000_000_i128)).unwrap().as_f64(), Some(1e20));
+        assert_eq!(to_js_value(&rust_type!((1_i128 << 53) + 1)).unwrap().is_bigint(), true);
+        assert_eq!(to_js_value(&rust_type!(i128::min_value())).unwrap().is_bigint(), false);
+    }
+}
@@ -1,8 +1,22 @@
+#[cfg(feature = "trait_bson")]
+pub mod _bson;
+#[cfg(feature = "trait_cbor")]
+pub mod _cbor;
 #[cfg(feature = "trait_json")]
 pub mod _json;
 #[cfg(feature = "trait_pyo3")]
 pub mod _pyo3;
+#[cfg(feature = "trait_rmpv")]
+pub mod _rmpv;
+#[cfg(feature = "trait_ron")]
+pub mod _ron;
 #[cfg(feature = "trait_serde_json")]
 pub mod _serde_json;
 #[cfg(feature = "trait_serde_yaml")]
 pub mod _serde_yaml;
+#[cfg(feature = "trait_simd_json")]
+pub mod _simd_json;
+#[cfg(feature = "trait_toml")]
+pub mod _toml;
+#[cfg(feature = "trait_wasm_bindgen")]
+pub mod _wasm_bindgen;